@@ -0,0 +1,212 @@
+use open_station_core::state::UiState;
+use open_station_protocol::types::*;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ConsoleError {
+    #[error("unknown mode {0:?}, expected auto, teleop or test")]
+    InvalidMode(String),
+    #[error("unknown station {0:?}, expected red1 to blue3")]
+    InvalidStation(String),
+    #[error("unknown command {0:?}, try help")]
+    UnknownCommand(String),
+}
+
+// One line typed at the terminal
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Enable,
+    Disable,
+    Estop,
+    Mode(Mode),
+    Alliance(Alliance),
+    // Empty clears it
+    GameData(String),
+    Reboot,
+    Restart,
+    Status,
+    Help,
+    Quit,
+}
+
+pub fn parse_mode(s: &str) -> Result<Mode, ConsoleError> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "auto" | "autonomous" => Ok(Mode::Autonomous),
+        "teleop" | "teleoperated" => Ok(Mode::Teleop),
+        "test" => Ok(Mode::Test),
+        _ => Err(ConsoleError::InvalidMode(s.to_string())),
+    }
+}
+
+pub fn parse_alliance(s: &str) -> Result<Alliance, ConsoleError> {
+    let lower = s.trim().to_ascii_lowercase();
+    let (color, station) = if let Some(station) = lower.strip_prefix("red") {
+        (AllianceColor::Red, station)
+    } else if let Some(station) = lower.strip_prefix("blue") {
+        (AllianceColor::Blue, station)
+    } else {
+        return Err(ConsoleError::InvalidStation(s.to_string()));
+    };
+    match station.parse::<u8>() {
+        Ok(n @ 1..=3) => Ok(Alliance::new(color, n)),
+        _ => Err(ConsoleError::InvalidStation(s.to_string())),
+    }
+}
+
+impl FromStr for Command {
+    type Err = ConsoleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match s.trim().split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (s.trim(), ""),
+        };
+
+        match name {
+            "enable" => Ok(Command::Enable),
+            "disable" => Ok(Command::Disable),
+            "estop" => Ok(Command::Estop),
+            "mode" => Ok(Command::Mode(parse_mode(arg)?)),
+            "alliance" => Ok(Command::Alliance(parse_alliance(arg)?)),
+            "gamedata" => Ok(Command::GameData(arg.to_string())),
+            "reboot" => Ok(Command::Reboot),
+            "restart" => Ok(Command::Restart),
+            "status" => Ok(Command::Status),
+            "help" => Ok(Command::Help),
+            "quit" | "exit" => Ok(Command::Quit),
+            _ => Err(ConsoleError::UnknownCommand(s.trim().to_string())),
+        }
+    }
+}
+
+pub fn status_line(ui: &UiState) -> String {
+    if !ui.connected {
+        return format!("team {} | {}", ui.team_number, ui.connection_state);
+    }
+
+    let mut line = format!(
+        "team {} | {:.2}V{} | {} {} | code {} | trip {:.1}ms | lost {}",
+        ui.team_number,
+        ui.voltage,
+        if ui.brownout { " BROWNOUT" } else { "" },
+        ui.mode,
+        if ui.estopped {
+            "E-STOPPED"
+        } else if ui.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        },
+        if ui.code_running {
+            "running"
+        } else {
+            "stopped"
+        },
+        ui.trip_time_ms,
+        ui.lost_packets,
+    );
+    if ui.fms_connected {
+        line.push_str(" | FMS");
+    }
+    if !ui.match_name.is_empty() {
+        line.push_str(" | ");
+        line.push_str(&ui.match_name);
+    }
+    line
+}
+
+// Stdout has its own channel, everything else the robot sends lands here.
+// Returns the line and whether it belongs on stderr.
+pub fn describe_message(message: &TcpMessage) -> Option<(String, bool)> {
+    match message {
+        TcpMessage::ErrorReport {
+            is_error,
+            details,
+            location,
+            ..
+        } => {
+            let kind = if *is_error { "ERROR" } else { "WARNING" };
+            let line = if location.is_empty() {
+                format!("{kind}: {details}")
+            } else {
+                format!("{kind}: {details} ({location})")
+            };
+            Some((line, true))
+        }
+        TcpMessage::Message(text) => Some((text.clone(), false)),
+        TcpMessage::Stdout(text) => Some((text.clone(), false)),
+        TcpMessage::VersionInfo { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!("enable".parse(), Ok(Command::Enable));
+        assert_eq!(" estop ".parse(), Ok(Command::Estop));
+        assert_eq!("mode auto".parse(), Ok(Command::Mode(Mode::Autonomous)));
+        assert_eq!(
+            "alliance Blue3".parse(),
+            Ok(Command::Alliance(Alliance::new(AllianceColor::Blue, 3)))
+        );
+        assert_eq!("gamedata LRL".parse(), Ok(Command::GameData("LRL".into())));
+        assert_eq!("gamedata".parse(), Ok(Command::GameData(String::new())));
+        assert_eq!(
+            "mode fast".parse::<Command>(),
+            Err(ConsoleError::InvalidMode("fast".into()))
+        );
+        assert_eq!(
+            "alliance red0".parse::<Command>(),
+            Err(ConsoleError::InvalidStation("red0".into()))
+        );
+        assert_eq!(
+            "launch".parse::<Command>(),
+            Err(ConsoleError::UnknownCommand("launch".into()))
+        );
+    }
+
+    #[test]
+    fn test_status_line() {
+        let mut ui = UiState {
+            team_number: 1234,
+            ..Default::default()
+        };
+        assert_eq!(status_line(&ui), "team 1234 | Disconnected");
+
+        ui.connected = true;
+        ui.code_running = true;
+        ui.voltage = 12.456;
+        ui.enabled = true;
+        ui.trip_time_ms = 2.31;
+        ui.match_name = "Practice 2".into();
+        assert_eq!(
+            status_line(&ui),
+            "team 1234 | 12.46V | Teleoperated enabled | code running | trip 2.3ms | lost 0 | Practice 2"
+        );
+    }
+
+    #[test]
+    fn test_error_reports_go_to_stderr() {
+        let report = TcpMessage::ErrorReport {
+            timestamp: 0.0,
+            sequence: 1,
+            error_code: 44004,
+            is_error: true,
+            details: "Joystick 0 missing".into(),
+            location: "Robot.java:12".into(),
+            call_stack: String::new(),
+        };
+        assert_eq!(
+            describe_message(&report),
+            Some(("ERROR: Joystick 0 missing (Robot.java:12)".into(), true))
+        );
+        assert_eq!(
+            describe_message(&TcpMessage::Message("hi".into())),
+            Some(("hi".into(), false))
+        );
+    }
+}
@@ -0,0 +1,210 @@
+mod console;
+
+use console::Command;
+use open_station_core::config::Config;
+use open_station_core::state::AppState;
+use open_station_protocol::types::*;
+use std::io::BufRead;
+use std::net::IpAddr;
+use std::process::ExitCode;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+Usage: open-station-cli [OPTIONS]
+
+Options:
+  --team NUMBER          Team to connect to (default from the config file)
+  --address ADDR         Robot address, skipping team/mDNS/USB discovery
+  --alliance STATION     red1 to blue3 (default red1)
+  --mode MODE            auto, teleop or test (default teleop)
+  --status-interval SECS Seconds between status lines, 0 for none (default 1)
+  --hotkeys              Listen for the global E-Stop, Disable and A-Stop keys,
+                         which fire even while typing into this terminal
+  -h, --help             Show this help
+
+Settings changed here are not saved to the config file.";
+
+const COMMANDS: &str = "\
+Commands:
+  enable, disable       Enable or disable the robot
+  estop                 E-Stop the robot until it is rebooted
+  mode MODE             auto, teleop or test
+  alliance STATION      red1 to blue3
+  gamedata [TEXT]       Set the game data, empty clears it
+  reboot                Reboot the roboRIO
+  restart               Restart robot code
+  status                Print a status line now
+  quit                  Disable and exit";
+
+// Same rate the desktop app polls at
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+struct Args {
+    config: Config,
+    alliance: Alliance,
+    mode: Mode,
+    status_interval: Option<Duration>,
+    hotkeys: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        config: Config::load(),
+        alliance: Alliance::new(AllianceColor::Red, 1),
+        mode: Mode::Teleop,
+        status_interval: Some(Duration::from_secs(1)),
+        hotkeys: false,
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "-h" || arg == "--help" {
+            return Err(String::new());
+        }
+        if arg == "--hotkeys" {
+            args.hotkeys = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {arg}"))?;
+        let invalid = || format!("invalid value for {arg}: {value}");
+
+        match arg.as_str() {
+            "--team" => args.config.team_number = value.parse().map_err(|_| invalid())?,
+            "--address" => {
+                args.config.network.target_address =
+                    Some(value.parse::<IpAddr>().map_err(|_| invalid())?)
+            }
+            "--alliance" => {
+                args.alliance = console::parse_alliance(&value).map_err(|e| e.to_string())?
+            }
+            "--mode" => args.mode = console::parse_mode(&value).map_err(|e| e.to_string())?,
+            "--status-interval" => {
+                let secs: f32 = value.parse().map_err(|_| invalid())?;
+                args.status_interval = match Duration::try_from_secs_f32(secs) {
+                    Ok(interval) if interval.is_zero() => None,
+                    Ok(interval) => Some(interval),
+                    Err(_) => return Err(invalid()),
+                };
+            }
+            _ => return Err(format!("unknown option {arg}")),
+        }
+    }
+
+    Ok(args)
+}
+
+fn run_command(app: &mut AppState, command: Command) {
+    match command {
+        Command::Enable => app.enable(),
+        Command::Disable => app.disable(),
+        Command::Estop => app.estop(),
+        Command::Mode(mode) => app.set_mode(mode),
+        Command::Alliance(alliance) => app.set_alliance(alliance),
+        Command::GameData(data) => app.set_game_data(data),
+        Command::Reboot => app.reboot_roborio(),
+        Command::Restart => app.restart_code(),
+        Command::Status => println!("{}", console::status_line(&app.subscribe_state().borrow())),
+        Command::Help => println!("{COMMANDS}"),
+        // Handled by the main loop
+        Command::Quit => {}
+    }
+}
+
+// Reads stdin on its own thread so the poll loop never blocks on it
+fn spawn_stdin_reader() -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) if e.is_empty() => {
+            println!("{USAGE}\n\n{COMMANDS}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if args.config.team_number == 0 && args.config.network.target_address.is_none() {
+        eprintln!("error: no team number, pass --team or --address\n\n{USAGE}");
+        return ExitCode::FAILURE;
+    }
+
+    let mut app = if args.hotkeys {
+        AppState::new(args.config)
+    } else {
+        AppState::new_headless(args.config)
+    };
+    app.set_alliance(args.alliance);
+    app.set_mode(args.mode);
+    let ui = app.subscribe_state();
+    let mut stdout_rx = app.take_stdout_rx();
+    let mut message_rx = app.take_message_rx();
+    let lines = spawn_stdin_reader();
+    println!("{COMMANDS}");
+
+    let mut last_status = Instant::now();
+    loop {
+        app.poll();
+
+        match lines.try_recv() {
+            Ok(line) if line.trim().is_empty() => {}
+            Ok(line) => match line.parse::<Command>() {
+                Ok(Command::Quit) => break,
+                Ok(command) => run_command(&mut app, command),
+                Err(e) => println!("{e}"),
+            },
+            // Stdin closed, e.g. a bench rig without a terminal. Keep driving.
+            Err(_) => {}
+        }
+
+        if let Some(rx) = &mut stdout_rx {
+            while let Ok(line) = rx.try_recv() {
+                println!("{line}");
+            }
+        }
+        if let Some(rx) = &mut message_rx {
+            while let Ok(message) = rx.try_recv() {
+                match console::describe_message(&message) {
+                    Some((line, true)) => eprintln!("{line}"),
+                    Some((line, false)) => println!("{line}"),
+                    None => {}
+                }
+            }
+        }
+
+        if let Some(interval) = args.status_interval {
+            if last_status.elapsed() >= interval {
+                println!("{}", console::status_line(&ui.borrow()));
+                last_status = Instant::now();
+            }
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    // Give the disable a few packets to reach the robot
+    app.disable();
+    for _ in 0..5 {
+        app.poll();
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    ExitCode::SUCCESS
+}
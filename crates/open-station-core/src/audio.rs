@@ -0,0 +1,68 @@
+use crate::practice::MatchSound;
+use rodio::Source;
+use std::io::Cursor;
+use std::sync::mpsc;
+use std::thread;
+
+fn cue(sound: MatchSound) -> &'static [u8] {
+    match sound {
+        MatchSound::StartAuto => include_bytes!("../sounds/start-auto.wav"),
+        MatchSound::EndAuto => include_bytes!("../sounds/end-auto.wav"),
+        MatchSound::StartTeleop => include_bytes!("../sounds/start-teleop.wav"),
+        MatchSound::Endgame => include_bytes!("../sounds/endgame.wav"),
+        MatchSound::EndMatch => include_bytes!("../sounds/end-match.wav"),
+        MatchSound::Abort => include_bytes!("../sounds/abort.wav"),
+    }
+}
+
+pub struct SoundPlayer {
+    tx: mpsc::Sender<MatchSound>,
+}
+
+impl SoundPlayer {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel::<MatchSound>();
+
+        // The output stream is not Send, so it lives on its own thread
+        let spawned = thread::Builder::new()
+            .name("open-station-audio".to_string())
+            .spawn(move || {
+                let (_stream, handle) = match rodio::OutputStream::try_default() {
+                    Ok(output) => output,
+                    Err(e) => {
+                        log::warn!("No audio output, match sounds disabled: {}", e);
+                        return;
+                    }
+                };
+
+                for sound in rx {
+                    let source = match rodio::Decoder::new(Cursor::new(cue(sound))) {
+                        Ok(source) => source,
+                        Err(e) => {
+                            log::warn!("Failed to decode {:?} cue: {}", sound, e);
+                            continue;
+                        }
+                    };
+                    // Cues overlap rather than queue, an abort must not wait
+                    if let Err(e) = handle.play_raw(source.convert_samples()) {
+                        log::warn!("Failed to play {:?} cue: {}", sound, e);
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            log::warn!("Failed to start audio thread: {}", e);
+        }
+
+        Self { tx }
+    }
+
+    pub fn play(&self, sound: MatchSound) {
+        let _ = self.tx.send(sound);
+    }
+}
+
+impl Default for SoundPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
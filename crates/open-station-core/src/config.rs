@@ -1,6 +1,11 @@
+use crate::hotkeys::HotkeyAction;
+use open_station_protocol::connection::Ports;
+use open_station_protocol::fms::FmsPorts;
+use open_station_protocol::types::{MatchInfo, MatchType, Mode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,8 +16,24 @@ pub struct Config {
     pub game_data: String,
     pub practice_timing: PracticeTiming,
     pub practice_audio: bool,
+    // Replaces the classic match built from practice_timing when set
+    #[serde(default)]
+    pub match_schedule: Option<MatchSchedule>,
     pub joystick_locks: HashMap<String, u8>, // UUID → slot
     pub window: WindowConfig,
+    // Older config files predate these settings
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub hotkeys: HotkeyConfig,
+    #[serde(default)]
+    pub match_info: MatchInfoConfig,
+    #[serde(default)]
+    pub fms: FmsConfig,
+    #[serde(default)]
+    pub server: ServerConfig,
+    #[serde(default)]
+    pub logging: DsLogConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +44,49 @@ pub struct PracticeTiming {
     pub teleop_secs: u32,
 }
 
+// The phases a practice match runs through, in order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MatchSchedule {
+    pub phases: Vec<SchedulePhase>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SchedulePhase {
+    pub label: String,
+    pub mode: ScheduleMode,
+    pub duration_secs: u32,
+    // Disabled phases are countdowns and pauses
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleMode {
+    Autonomous,
+    Teleop,
+    Test,
+}
+
+// Entered in the Setup tab, practice mode numbers its own matches
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MatchInfoConfig {
+    pub event_name: String,
+    pub match_type: MatchInfoType,
+    pub match_number: u16,
+    pub replay_number: u8,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchInfoType {
+    #[default]
+    None,
+    Practice,
+    Qualification,
+    Elimination,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WindowConfig {
     pub x: Option<i32>,
@@ -31,6 +95,58 @@ pub struct WindowConfig {
     pub height: u32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NetworkConfig {
+    // Skips team/mDNS/USB discovery, e.g. 127.0.0.1 for desktop simulation
+    pub target_address: Option<IpAddr>,
+    pub robot_udp_port: u16,
+    pub ds_udp_port: u16,
+    pub tcp_port: u16,
+}
+
+// Off unless this DS sits on a field, the ports are only bound when enabled
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FmsConfig {
+    pub enabled: bool,
+    pub ds_udp_port: u16,
+    pub fms_udp_port: u16,
+}
+
+// Local HTTP/WebSocket API for pit displays, off unless enabled. Control
+// endpoints answer only when a token is set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub enabled: bool,
+    pub bind_address: IpAddr,
+    pub port: u16,
+    pub token: Option<String>,
+}
+
+// .dslog/.dsevents session files, the oldest are deleted once there are more
+// than max_sessions
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DsLogConfig {
+    pub enabled: bool,
+    // Defaults to logs/ in the config directory
+    pub directory: Option<PathBuf>,
+    pub max_sessions: usize,
+}
+
+// Key names joined with `+` for chords, see hotkeys::parse_key
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HotkeyConfig {
+    pub estop: String,
+    pub enable: String,
+    pub disable: String,
+    pub a_stop: String,
+    pub rescan: String,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -40,8 +156,15 @@ impl Default for Config {
             game_data: String::new(),
             practice_timing: PracticeTiming::default(),
             practice_audio: true,
+            match_schedule: None,
             joystick_locks: HashMap::new(),
             window: WindowConfig::default(),
+            network: NetworkConfig::default(),
+            hotkeys: HotkeyConfig::default(),
+            match_info: MatchInfoConfig::default(),
+            fms: FmsConfig::default(),
+            server: ServerConfig::default(),
+            logging: DsLogConfig::default(),
         }
     }
 }
@@ -57,6 +180,73 @@ impl Default for PracticeTiming {
     }
 }
 
+impl SchedulePhase {
+    pub fn new(label: &str, mode: ScheduleMode, duration_secs: u32, enabled: bool) -> Self {
+        Self {
+            label: label.to_string(),
+            mode,
+            duration_secs,
+            enabled,
+        }
+    }
+}
+
+impl MatchSchedule {
+    // Countdown, autonomous, delay, teleop
+    pub fn classic(timing: &PracticeTiming) -> Self {
+        Self {
+            phases: vec![
+                SchedulePhase::new(
+                    "Countdown",
+                    ScheduleMode::Autonomous,
+                    timing.countdown_secs,
+                    false,
+                ),
+                SchedulePhase::new(
+                    "Autonomous",
+                    ScheduleMode::Autonomous,
+                    timing.auto_secs,
+                    true,
+                ),
+                SchedulePhase::new("Delay", ScheduleMode::Teleop, timing.delay_secs, false),
+                SchedulePhase::new("Teleop", ScheduleMode::Teleop, timing.teleop_secs, true),
+            ],
+        }
+    }
+}
+
+impl From<ScheduleMode> for Mode {
+    fn from(mode: ScheduleMode) -> Self {
+        match mode {
+            ScheduleMode::Autonomous => Mode::Autonomous,
+            ScheduleMode::Teleop => Mode::Teleop,
+            ScheduleMode::Test => Mode::Test,
+        }
+    }
+}
+
+impl From<MatchInfoType> for MatchType {
+    fn from(match_type: MatchInfoType) -> Self {
+        match match_type {
+            MatchInfoType::None => MatchType::None,
+            MatchInfoType::Practice => MatchType::Practice,
+            MatchInfoType::Qualification => MatchType::Qualification,
+            MatchInfoType::Elimination => MatchType::Elimination,
+        }
+    }
+}
+
+impl From<&MatchInfoConfig> for MatchInfo {
+    fn from(info: &MatchInfoConfig) -> Self {
+        MatchInfo {
+            event_name: info.event_name.clone(),
+            match_type: info.match_type.into(),
+            match_number: info.match_number,
+            replay_number: info.replay_number,
+        }
+    }
+}
+
 impl Default for WindowConfig {
     fn default() -> Self {
         Self {
@@ -68,7 +258,119 @@ impl Default for WindowConfig {
     }
 }
 
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        let ports = Ports::default();
+        Self {
+            target_address: None,
+            robot_udp_port: ports.robot_udp,
+            ds_udp_port: ports.ds_udp,
+            tcp_port: ports.tcp,
+        }
+    }
+}
+
+impl NetworkConfig {
+    pub fn ports(&self) -> Ports {
+        Ports {
+            robot_udp: self.robot_udp_port,
+            ds_udp: self.ds_udp_port,
+            tcp: self.tcp_port,
+        }
+    }
+}
+
+impl Default for FmsConfig {
+    fn default() -> Self {
+        let ports = FmsPorts::default();
+        Self {
+            enabled: false,
+            ds_udp_port: ports.ds_udp,
+            fms_udp_port: ports.fms_udp,
+        }
+    }
+}
+
+impl FmsConfig {
+    pub fn ports(&self) -> FmsPorts {
+        FmsPorts {
+            ds_udp: self.ds_udp_port,
+            fms_udp: self.fms_udp_port,
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: 5805,
+            token: None,
+        }
+    }
+}
+
+impl Default for DsLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            directory: None,
+            max_sessions: 250,
+        }
+    }
+}
+
+impl DsLogConfig {
+    pub fn directory(&self) -> PathBuf {
+        self.directory
+            .clone()
+            .unwrap_or_else(|| Config::config_dir().join("logs"))
+    }
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            estop: "Space".to_string(),
+            enable: "[+]+\\".to_string(),
+            disable: "Enter".to_string(),
+            a_stop: "Backspace".to_string(),
+            rescan: "F1".to_string(),
+        }
+    }
+}
+
+impl HotkeyConfig {
+    pub fn binding(&self, action: HotkeyAction) -> &str {
+        match action {
+            HotkeyAction::EStop => &self.estop,
+            HotkeyAction::Enable => &self.enable,
+            HotkeyAction::Disable => &self.disable,
+            HotkeyAction::AStop => &self.a_stop,
+            HotkeyAction::RescanJoysticks => &self.rescan,
+        }
+    }
+
+    pub fn set_binding(&mut self, action: HotkeyAction, binding: String) {
+        let slot = match action {
+            HotkeyAction::EStop => &mut self.estop,
+            HotkeyAction::Enable => &mut self.enable,
+            HotkeyAction::Disable => &mut self.disable,
+            HotkeyAction::AStop => &mut self.a_stop,
+            HotkeyAction::RescanJoysticks => &mut self.rescan,
+        };
+        *slot = binding;
+    }
+}
+
 impl Config {
+    pub fn match_schedule(&self) -> MatchSchedule {
+        self.match_schedule
+            .clone()
+            .unwrap_or_else(|| MatchSchedule::classic(&self.practice_timing))
+    }
+
     pub fn config_dir() -> PathBuf {
         let mut dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
         dir.push("open-station");
@@ -125,6 +427,8 @@ mod tests {
         assert!(config.practice_audio);
         assert!(!config.use_usb);
         assert_eq!(config.window.width, 1000);
+        assert_eq!(config.network.target_address, None);
+        assert_eq!(config.network.ports(), Ports::default());
     }
 
     #[test]
@@ -140,6 +444,15 @@ mod tests {
             ..Default::default()
         };
         config.joystick_locks.insert("uuid-123".to_string(), 0);
+        config.network.target_address = Some("127.0.0.1".parse().unwrap());
+        config.network.ds_udp_port = 5150;
+        config.hotkeys.enable = "Ctrl+Shift+E".to_string();
+        config.match_info = MatchInfoConfig {
+            event_name: "Week 0".to_string(),
+            match_type: MatchInfoType::Qualification,
+            match_number: 12,
+            replay_number: 1,
+        };
 
         config.save_to(&path).unwrap();
         let loaded = Config::load_from(&path);
@@ -148,6 +461,101 @@ mod tests {
         assert!(loaded.use_usb);
         assert_eq!(loaded.game_data, "LRL");
         assert_eq!(loaded.joystick_locks.get("uuid-123"), Some(&0));
+        assert_eq!(
+            loaded.network.target_address,
+            Some("127.0.0.1".parse().unwrap())
+        );
+        assert_eq!(loaded.network.ds_udp_port, 5150);
+        assert_eq!(loaded.network.tcp_port, 1740);
+        assert_eq!(loaded.hotkeys.enable, "Ctrl+Shift+E");
+        assert_eq!(loaded.hotkeys.estop, "Space");
+        assert_eq!(loaded.match_info, config.match_info);
+    }
+
+    #[test]
+    fn test_config_without_network_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut config = Config {
+            team_number: 254,
+            ..Default::default()
+        };
+        config.save_to(&path).unwrap();
+
+        // Strip the [network] table to mimic a config from an older release
+        let contents = fs::read_to_string(&path).unwrap();
+        let legacy = contents.split("[network]").next().unwrap().to_string();
+        fs::write(&path, legacy).unwrap();
+
+        config = Config::load_from(&path);
+        assert_eq!(config.team_number, 254);
+        assert_eq!(config.network.ports(), Ports::default());
+        assert_eq!(config.hotkeys, HotkeyConfig::default());
+        assert_eq!(config.fms, FmsConfig::default());
+        assert_eq!(config.server, ServerConfig::default());
+        assert_eq!(config.logging, DsLogConfig::default());
+    }
+
+    #[test]
+    fn test_partial_network_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let config = Config {
+            team_number: 254,
+            ..Default::default()
+        };
+        config.save_to(&path).unwrap();
+
+        // Only the address, as written by hand for a simulator
+        let contents = fs::read_to_string(&path).unwrap();
+        let (head, rest) = contents.split_once("[network]").unwrap();
+        let tail = rest.split_once("\n[").map(|(_, t)| t).unwrap_or_default();
+        fs::write(
+            &path,
+            format!("{head}[network]\ntarget_address = \"127.0.0.1\"\n\n[{tail}"),
+        )
+        .unwrap();
+
+        let config = Config::load_from(&path);
+        assert_eq!(config.team_number, 254);
+        assert_eq!(
+            config.network.target_address,
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+        assert_eq!(config.network.ports(), Ports::default());
+    }
+
+    #[test]
+    fn test_classic_schedule_is_default() {
+        let config = Config::default();
+        let schedule = config.match_schedule();
+        let labels: Vec<&str> = schedule.phases.iter().map(|p| p.label.as_str()).collect();
+        assert_eq!(labels, vec!["Countdown", "Autonomous", "Delay", "Teleop"]);
+        assert_eq!(schedule.phases[1].duration_secs, 15);
+        assert!(schedule.phases[1].enabled);
+        assert!(!schedule.phases[2].enabled);
+    }
+
+    #[test]
+    fn test_custom_schedule_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let schedule = MatchSchedule {
+            phases: vec![
+                SchedulePhase::new("Endgame", ScheduleMode::Teleop, 20, true),
+                SchedulePhase::new("Reset", ScheduleMode::Teleop, 10, false),
+                SchedulePhase::new("Checks", ScheduleMode::Test, 30, true),
+            ],
+        };
+        let config = Config {
+            match_schedule: Some(schedule.clone()),
+            ..Default::default()
+        };
+        config.save_to(&path).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("mode = \"test\""));
+        assert_eq!(Config::load_from(&path).match_schedule(), schedule);
     }
 
     #[test]
@@ -0,0 +1,770 @@
+use crate::config::DsLogConfig;
+use chrono::{DateTime, Local};
+use open_station_protocol::connection::LinkStats;
+use open_station_protocol::packet::incoming::{RioPacket, RioTag};
+use open_station_protocol::types::*;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, watch};
+
+// The NI driver station log pair, readable by the NI Log File Viewer and
+// AdvantageScope. Both files start with a version and a LabVIEW timestamp,
+// the .dslog then holds one fixed size record per 20ms and the .dsevents
+// holds timestamped text.
+pub const DSLOG_VERSION: i32 = 4;
+pub const RECORD_INTERVAL: Duration = Duration::from_millis(20);
+// Status record plus the power distribution header, without PD data
+pub const RECORD_LEN: usize = 14;
+// The robot's PD tag has the 16 channels of a CTRE PDP
+pub const PD_CHANNELS: usize = 16;
+// Type byte of a CTRE PDP block, also its length after the header
+const PD_TYPE_CTRE: u8 = 25;
+
+// Seconds from the LabVIEW epoch (1904-01-01 UTC) to the Unix epoch
+const LABVIEW_EPOCH_OFFSET: i64 = 2_082_844_800;
+// Same as the connection's own timeout
+const ROBOT_TIMEOUT: Duration = Duration::from_secs(1);
+// A robot that comes back within this stays in the same file, e.g. a
+// radio blip or a code restart
+const SESSION_GRACE: Duration = Duration::from_secs(10);
+const LOSS_WINDOW: usize = 50;
+const FLUSH_EVERY: u32 = 50;
+// Between attempts to open a new session after a write failed
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+// LabVIEW time: whole seconds since 1904 and the fraction of a second
+// as a 64 bit fixed point number
+pub fn labview_time(time: SystemTime) -> (i64, u64) {
+    let since = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let fraction = ((since.subsec_nanos() as u128) << 64) / 1_000_000_000;
+    (
+        since.as_secs() as i64 + LABVIEW_EPOCH_OFFSET,
+        fraction as u64,
+    )
+}
+
+pub fn encode_header(start: SystemTime) -> Vec<u8> {
+    let (secs, fraction) = labview_time(start);
+    let mut buf = Vec::with_capacity(20);
+    buf.extend_from_slice(&DSLOG_VERSION.to_be_bytes());
+    buf.extend_from_slice(&secs.to_be_bytes());
+    buf.extend_from_slice(&fraction.to_be_bytes());
+    buf
+}
+
+// One 20ms sample. Fractions are 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LogRecord {
+    pub trip_time_ms: f64,
+    pub packet_loss: f32,
+    pub voltage: f32,
+    pub rio_cpu: f32,
+    pub can_utilization: f32,
+    pub brownout: bool,
+    pub ds_enabled: bool,
+    pub ds_mode: Mode,
+    // What the robot reports, None while it is not connected
+    pub robot: Option<StatusFlags>,
+    // Amps, None until the robot has sent PD data
+    pub pd_currents: Option<[f32; PD_CHANNELS]>,
+}
+
+fn byte(value: f64) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+impl LogRecord {
+    pub fn encode(&self) -> Vec<u8> {
+        // Status bits are inverted, a set bit means the flag is off. The
+        // roboRIO's status packet has no watchdog flag, so that bit always
+        // reads as not tripped.
+        let robot_mode = |mode: Mode| self.robot.is_some_and(|r| r.mode == mode);
+        let flags = [
+            self.brownout,
+            false,
+            self.ds_enabled && self.ds_mode == Mode::Teleop,
+            self.ds_enabled && self.ds_mode == Mode::Autonomous,
+            !self.ds_enabled,
+            robot_mode(Mode::Teleop),
+            robot_mode(Mode::Autonomous),
+            self.robot.is_some_and(|r| !r.enabled),
+        ];
+        let status = flags
+            .iter()
+            .fold(0u8, |byte, &flag| (byte << 1) | u8::from(!flag));
+        let voltage = (self.voltage as f64 * 256.0).round().clamp(0.0, 65535.0) as u16;
+
+        let mut buf = vec![0u8; RECORD_LEN];
+        // Half milliseconds
+        buf[0] = byte(self.trip_time_ms * 2.0);
+        // 4% steps
+        buf[1] = byte(self.packet_loss as f64 * 25.0);
+        buf[2..4].copy_from_slice(&voltage.to_be_bytes());
+        // Half percent
+        buf[4] = byte(self.rio_cpu as f64 * 200.0);
+        buf[5] = status;
+        buf[6] = byte(self.can_utilization as f64 * 200.0);
+        // Wifi dB and bandwidth are radio statistics we never get
+        buf[7] = 0;
+        buf[8..10].copy_from_slice(&0u16.to_be_bytes());
+        // Power distribution header, only the last byte (the type) is read
+        match &self.pd_currents {
+            Some(currents) => {
+                buf[13] = PD_TYPE_CTRE;
+                buf.extend_from_slice(&encode_ctre_pd(currents));
+            }
+            None => buf[13] = 0,
+        }
+        buf
+    }
+}
+
+// CAN id, then each channel as 10 bits of eighth amps, six channels to each
+// 64 bit block, least significant bit first, then 3 bytes of resistance,
+// voltage and temperature. The robot sends neither the id nor the last three,
+// so they stay 0.
+fn encode_ctre_pd(currents: &[f32; PD_CHANNELS]) -> [u8; PD_TYPE_CTRE as usize] {
+    let mut buf = [0u8; PD_TYPE_CTRE as usize];
+    let bits = &mut buf[1..22];
+    for (channel, amps) in currents.iter().enumerate() {
+        let value = (*amps as f64 * 8.0).round().clamp(0.0, 1023.0) as u16;
+        let start = channel / 6 * 64 + channel % 6 * 10;
+        for bit in 0..10 {
+            if value & (1 << bit) != 0 {
+                let at = start + bit;
+                bits[at / 8] |= 1 << (at % 8);
+            }
+        }
+    }
+    buf
+}
+
+pub fn encode_event(time: SystemTime, text: &str) -> Vec<u8> {
+    let (secs, fraction) = labview_time(time);
+    let mut buf = Vec::with_capacity(20 + text.len());
+    buf.extend_from_slice(&secs.to_be_bytes());
+    buf.extend_from_slice(&fraction.to_be_bytes());
+    buf.extend_from_slice(&(text.len() as i32).to_be_bytes());
+    buf.extend_from_slice(text.as_bytes());
+    buf
+}
+
+// Tagged text as the NI driver station writes it, `elapsed` is seconds into
+// the session for messages without a robot timestamp
+pub fn event_text(message: &TcpMessage, elapsed: f64) -> Option<String> {
+    match message {
+        TcpMessage::Message(text) | TcpMessage::Stdout(text) => Some(format!(
+            "<TagVersion>1 <time> {elapsed:.3} <message> {text} "
+        )),
+        TcpMessage::ErrorReport {
+            timestamp,
+            error_code,
+            is_error,
+            details,
+            location,
+            call_stack,
+            ..
+        } => Some(format!(
+            "<TagVersion>1 <time> {timestamp:.3} <count> 1 <flags> {} <Code> {error_code} \
+             <details> {details} <location> {location} <stack> {call_stack} ",
+            u8::from(*is_error),
+        )),
+        TcpMessage::VersionInfo { name, version, .. } => Some(format!(
+            "<TagVersion>1 <time> {elapsed:.3} <message> {name} version {version} "
+        )),
+    }
+}
+
+// e.g. "2026_10_16 14_22_10 Fri Q12". The date leads so names sort in the
+// order the sessions started.
+pub fn session_name(start: DateTime<Local>, match_info: &MatchInfo) -> String {
+    let mut name = start.format("%Y_%m_%d %H_%M_%S %a").to_string();
+    let level = match match_info.match_type {
+        MatchType::None => return name,
+        MatchType::Practice => "P",
+        MatchType::Qualification => "Q",
+        MatchType::Elimination => "E",
+    };
+    name.push_str(&format!(" {level}{}", match_info.match_number));
+    if match_info.replay_number > 1 {
+        name.push_str(&format!("_{}", match_info.replay_number));
+    }
+    name
+}
+
+// Deletes the oldest session pairs until `keep` remain
+pub fn prune(dir: &Path, keep: usize) -> io::Result<()> {
+    let mut logs: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "dslog"))
+        .collect();
+    if logs.len() <= keep {
+        return Ok(());
+    }
+    logs.sort();
+    for path in &logs[..logs.len() - keep] {
+        std::fs::remove_file(path)?;
+        let _ = std::fs::remove_file(path.with_extension("dsevents"));
+    }
+    Ok(())
+}
+
+// An open .dslog/.dsevents pair
+pub struct LogSession {
+    name: String,
+    match_info: MatchInfo,
+    started: Instant,
+    dslog: BufWriter<File>,
+    dsevents: BufWriter<File>,
+    unflushed: u32,
+}
+
+impl LogSession {
+    pub fn create(
+        dir: &Path,
+        name: &str,
+        match_info: MatchInfo,
+        start: SystemTime,
+    ) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let mut dslog = BufWriter::new(File::create(dir.join(format!("{name}.dslog")))?);
+        let mut dsevents = BufWriter::new(File::create(dir.join(format!("{name}.dsevents")))?);
+        let header = encode_header(start);
+        dslog.write_all(&header)?;
+        dsevents.write_all(&header)?;
+        Ok(Self {
+            name: name.to_string(),
+            match_info,
+            started: Instant::now(),
+            dslog,
+            dsevents,
+            unflushed: 0,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn write_record(&mut self, record: &LogRecord) -> io::Result<()> {
+        self.dslog.write_all(&record.encode())?;
+        self.unflushed += 1;
+        // About once a second, so a crash loses little
+        if self.unflushed >= FLUSH_EVERY {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn write_event(&mut self, time: SystemTime, text: &str) -> io::Result<()> {
+        self.dsevents.write_all(&encode_event(time, text))
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.unflushed = 0;
+        self.dslog.flush()?;
+        self.dsevents.flush()
+    }
+}
+
+// What the driver station is asking for, the DS half of each record
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogContext {
+    pub enabled: bool,
+    pub mode: Mode,
+    pub match_info: MatchInfo,
+}
+
+// Turns robot packets and messages into sessions. A session starts when a
+// robot connects, ends once it has been gone for a while and starts over
+// in a new file when the match changes.
+pub struct DsLogger {
+    dir: PathBuf,
+    max_sessions: usize,
+    session: Option<LogSession>,
+    last_packet: Option<Instant>,
+    latest: Option<(RioPacket, LinkStats)>,
+    cpu: f32,
+    can: f32,
+    pd_currents: Option<[f32; PD_CHANNELS]>,
+    // No new session before this after a failed write
+    retry_at: Option<Instant>,
+    // Lost and received counts for each of the last LOSS_WINDOW records
+    loss: VecDeque<(u32, u32)>,
+    lost: u32,
+    received: u32,
+    last_lost_total: u32,
+}
+
+impl DsLogger {
+    pub fn new(config: &DsLogConfig) -> Self {
+        Self {
+            dir: config.directory(),
+            max_sessions: config.max_sessions,
+            session: None,
+            last_packet: None,
+            latest: None,
+            cpu: 0.0,
+            can: 0.0,
+            pd_currents: None,
+            retry_at: None,
+            loss: VecDeque::with_capacity(LOSS_WINDOW),
+            lost: 0,
+            received: 0,
+            last_lost_total: 0,
+        }
+    }
+
+    pub fn session(&self) -> Option<&LogSession> {
+        self.session.as_ref()
+    }
+
+    pub fn on_packet(&mut self, packet: RioPacket, stats: LinkStats, now: Instant) {
+        for tag in &packet.tags {
+            match tag {
+                RioTag::CpuUsage(cores) if !cores.is_empty() => {
+                    self.cpu = cores.iter().sum::<f32>() / cores.len() as f32 / 100.0;
+                }
+                RioTag::CanMetrics(can) => self.can = can.utilization / 100.0,
+                RioTag::PdpData(amps) => {
+                    let mut currents = [0.0; PD_CHANNELS];
+                    for (current, amps) in currents.iter_mut().zip(amps) {
+                        *current = *amps;
+                    }
+                    self.pd_currents = Some(currents);
+                }
+                _ => {}
+            }
+        }
+        // The counter starts over with each connection
+        self.lost += stats
+            .lost_packets
+            .checked_sub(self.last_lost_total)
+            .unwrap_or(stats.lost_packets);
+        self.last_lost_total = stats.lost_packets;
+        self.received += 1;
+        self.last_packet = Some(now);
+        self.latest = Some((packet, stats));
+    }
+
+    pub fn on_message(&mut self, message: &TcpMessage, time: SystemTime) -> io::Result<()> {
+        let Some(session) = &mut self.session else {
+            return Ok(());
+        };
+        let elapsed = session.started.elapsed().as_secs_f64();
+        match event_text(message, elapsed) {
+            Some(text) => session.write_event(time, &text),
+            None => Ok(()),
+        }
+    }
+
+    // Called every RECORD_INTERVAL, writes one record
+    pub fn tick(&mut self, context: &LogContext, now: Instant, time: SystemTime) -> io::Result<()> {
+        let connected = self
+            .last_packet
+            .is_some_and(|at| now.saturating_duration_since(at) < ROBOT_TIMEOUT);
+
+        if self
+            .session
+            .as_ref()
+            .is_some_and(|s| s.match_info != context.match_info)
+        {
+            self.close()?;
+        }
+        if self.session.is_none() && connected && self.retry_at.is_none_or(|at| now >= at) {
+            self.retry_at = None;
+            self.open(context, time)?;
+        }
+        if !connected
+            && self.last_packet.is_some_and(|at| {
+                now.saturating_duration_since(at) >= ROBOT_TIMEOUT + SESSION_GRACE
+            })
+        {
+            self.close()?;
+        }
+
+        let record = self.record(context, connected);
+        match &mut self.session {
+            Some(session) => session.write_record(&record),
+            None => Ok(()),
+        }
+    }
+
+    // Drops the session a write failed on. Driving goes on, and logging
+    // starts over in a new pair a little later.
+    pub fn fail(&mut self, error: io::Error, now: Instant) {
+        match self.session.take() {
+            Some(session) => log::error!(
+                "Driver station log {} failed, retrying: {}",
+                session.name(),
+                error
+            ),
+            None => log::error!("Driver station log failed, retrying: {}", error),
+        }
+        self.retry_at = Some(now + RETRY_DELAY);
+    }
+
+    pub fn close(&mut self) -> io::Result<()> {
+        match self.session.take() {
+            Some(mut session) => {
+                log::info!("Closed driver station log {}", session.name());
+                session.flush()
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn open(&mut self, context: &LogContext, time: SystemTime) -> io::Result<()> {
+        let name = session_name(DateTime::<Local>::from(time), &context.match_info);
+        let session = LogSession::create(&self.dir, &name, context.match_info.clone(), time)?;
+        log::info!("Logging to {}", self.dir.join(&name).display());
+        self.session = Some(session);
+        // The new pair counts too
+        prune(&self.dir, self.max_sessions.max(1))
+    }
+
+    fn record(&mut self, context: &LogContext, connected: bool) -> LogRecord {
+        if self.loss.len() == LOSS_WINDOW {
+            self.loss.pop_front();
+        }
+        self.loss.push_back((self.lost, self.received));
+        self.lost = 0;
+        self.received = 0;
+
+        let mut record = LogRecord {
+            ds_enabled: context.enabled,
+            ds_mode: context.mode,
+            packet_loss: 1.0,
+            ..Default::default()
+        };
+        let Some((packet, stats)) = self.latest.as_ref().filter(|_| connected) else {
+            return record;
+        };
+
+        let (lost, received) = self
+            .loss
+            .iter()
+            .fold((0, 0), |(l, r), &(lost, received)| (l + lost, r + received));
+        record.packet_loss = if lost + received == 0 {
+            0.0
+        } else {
+            lost as f32 / (lost + received) as f32
+        };
+        record.trip_time_ms = stats.trip_time_ms;
+        record.voltage = packet.voltage.volts;
+        record.rio_cpu = self.cpu;
+        record.can_utilization = self.can;
+        record.brownout = packet.status.brownout;
+        record.robot = Some(packet.status);
+        record.pd_currents = self.pd_currents;
+        record
+    }
+}
+
+// Runs until the packet stream closes with the driver station
+pub async fn run(
+    config: DsLogConfig,
+    mut packets: broadcast::Receiver<(RioPacket, LinkStats)>,
+    mut messages: broadcast::Receiver<TcpMessage>,
+    context: watch::Receiver<LogContext>,
+) {
+    let mut logger = DsLogger::new(&config);
+    let mut ticker = tokio::time::interval(RECORD_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        let result = tokio::select! {
+            _ = ticker.tick() => {
+                let context = context.borrow().clone();
+                logger.tick(&context, Instant::now(), SystemTime::now())
+            }
+
+            packet = packets.recv() => match packet {
+                Ok((packet, stats)) => {
+                    logger.on_packet(packet, stats, Instant::now());
+                    Ok(())
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => Ok(()),
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+
+            message = messages.recv() => match message {
+                Ok(message) => logger.on_message(&message, SystemTime::now()),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Driver station log skipped {} messages", skipped);
+                    Ok(())
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+
+        if let Err(e) = result {
+            logger.fail(e, Instant::now());
+        }
+    }
+
+    let _ = logger.close();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn packet(volts: f32, enabled: bool) -> RioPacket {
+        RioPacket {
+            sequence: 1,
+            status: StatusFlags {
+                estop: false,
+                code_initializing: false,
+                brownout: false,
+                enabled,
+                mode: Mode::Autonomous,
+            },
+            trace: 0,
+            voltage: BatteryVoltage { volts },
+            request_date: false,
+            tags: vec![RioTag::CpuUsage(vec![40.0, 60.0])],
+        }
+    }
+
+    fn stats(lost_packets: u32) -> LinkStats {
+        LinkStats {
+            trip_time_ms: 3.0,
+            lost_packets,
+            sent_count: 0,
+            received_count: 0,
+        }
+    }
+
+    fn logger(dir: &Path) -> DsLogger {
+        DsLogger::new(&DsLogConfig {
+            enabled: true,
+            directory: Some(dir.to_path_buf()),
+            max_sessions: 3,
+        })
+    }
+
+    fn files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_header_uses_labview_epoch() {
+        let header = encode_header(UNIX_EPOCH + Duration::from_millis(1500));
+        assert_eq!(header.len(), 20);
+        assert_eq!(header[..4], [0, 0, 0, 4]);
+        assert_eq!(header[4..12], (2_082_844_801i64).to_be_bytes());
+        assert_eq!(header[12..], (1u64 << 63).to_be_bytes());
+    }
+
+    #[test]
+    fn test_record_encoding() {
+        let record = LogRecord {
+            trip_time_ms: 2.5,
+            packet_loss: 0.08,
+            voltage: 12.5,
+            rio_cpu: 0.5,
+            can_utilization: 0.25,
+            brownout: false,
+            ds_enabled: true,
+            ds_mode: Mode::Autonomous,
+            robot: Some(packet(12.5, true).status),
+            pd_currents: None,
+        };
+        assert_eq!(
+            record.encode(),
+            [5, 2, 0x0C, 0x80, 100, 0b1110_1101, 50, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        // Nothing connected: disabled DS, no robot bits
+        assert_eq!(LogRecord::default().encode()[5], 0b1111_0111);
+    }
+
+    #[test]
+    fn test_ctre_pd_block() {
+        let mut currents = [0.0; PD_CHANNELS];
+        // 10 bits of 1s, then 1A on channel 1 and 2A on the 7th, which
+        // starts the second 64 bit block
+        currents[0] = 127.875;
+        currents[1] = 1.0;
+        currents[6] = 2.0;
+        let record = LogRecord {
+            pd_currents: Some(currents),
+            ..Default::default()
+        };
+        let bytes = record.encode();
+        assert_eq!(bytes.len(), RECORD_LEN + 25);
+        assert_eq!(bytes[10..14], [0, 0, 0, 25]);
+
+        let pd = &bytes[RECORD_LEN..];
+        // CAN id
+        assert_eq!(pd[0], 0);
+        // Channel 0 fills bits 0-9, channel 1 is 8 so sets bit 13
+        assert_eq!(pd[1..4], [0xFF, 0b0010_0011, 0]);
+        // Channel 6 is 16 at bit 64
+        assert_eq!(pd[9], 16);
+        assert_eq!(pd[22..], [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_events() {
+        let bytes = encode_event(UNIX_EPOCH, "hi");
+        assert_eq!(bytes[..8], LABVIEW_EPOCH_OFFSET.to_be_bytes());
+        assert_eq!(bytes[16..20], [0, 0, 0, 2]);
+        assert_eq!(&bytes[20..], b"hi");
+
+        let report = TcpMessage::ErrorReport {
+            timestamp: 12.5,
+            sequence: 1,
+            error_code: 44004,
+            is_error: true,
+            details: "Joystick 0 missing".into(),
+            location: "Robot.java:12".into(),
+            call_stack: String::new(),
+        };
+        assert_eq!(
+            event_text(&report, 0.0).unwrap(),
+            "<TagVersion>1 <time> 12.500 <count> 1 <flags> 1 <Code> 44004 \
+             <details> Joystick 0 missing <location> Robot.java:12 <stack>  "
+        );
+        assert_eq!(
+            event_text(&TcpMessage::Stdout("hello".into()), 1.25).unwrap(),
+            "<TagVersion>1 <time> 1.250 <message> hello "
+        );
+    }
+
+    #[test]
+    fn test_session_names() {
+        let start = Local.with_ymd_and_hms(2026, 10, 16, 14, 22, 10).unwrap();
+        let mut info = MatchInfo::default();
+        assert_eq!(session_name(start, &info), "2026_10_16 14_22_10 Fri");
+
+        info.match_type = MatchType::Qualification;
+        info.match_number = 12;
+        info.replay_number = 1;
+        assert_eq!(session_name(start, &info), "2026_10_16 14_22_10 Fri Q12");
+        info.replay_number = 2;
+        assert_eq!(session_name(start, &info), "2026_10_16 14_22_10 Fri Q12_2");
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_pairs() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["2026_01_01 a", "2026_01_02 b", "2026_01_03 c"] {
+            for ext in ["dslog", "dsevents"] {
+                File::create(dir.path().join(format!("{name}.{ext}"))).unwrap();
+            }
+        }
+        File::create(dir.path().join("notes.txt")).unwrap();
+
+        prune(dir.path(), 2).unwrap();
+        assert_eq!(
+            files(dir.path()),
+            [
+                "2026_01_02 b.dsevents",
+                "2026_01_02 b.dslog",
+                "2026_01_03 c.dsevents",
+                "2026_01_03 c.dslog",
+                "notes.txt",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_write_retries_with_new_session() {
+        let dir = tempfile::tempdir().unwrap();
+        // A file where the log directory should be
+        let logs = dir.path().join("logs");
+        File::create(&logs).unwrap();
+        let mut logger = logger(&logs);
+        let context = LogContext::default();
+        let mut now = Instant::now();
+        let time = SystemTime::now();
+
+        logger.on_packet(packet(12.0, false), stats(0), now);
+        let error = logger.tick(&context, now, time).unwrap_err();
+        logger.fail(error, now);
+        assert!(logger.session().is_none());
+
+        std::fs::remove_file(&logs).unwrap();
+        now += RECORD_INTERVAL;
+        logger.on_packet(packet(12.0, false), stats(0), now);
+        logger.tick(&context, now, time).unwrap();
+        assert!(logger.session().is_none());
+
+        now += RETRY_DELAY;
+        logger.on_packet(packet(12.0, false), stats(0), now);
+        logger.tick(&context, now, time).unwrap();
+        assert!(logger.session().is_some());
+        assert_eq!(files(&logs).len(), 2);
+    }
+
+    #[test]
+    fn test_session_follows_robot_and_match() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut logger = logger(dir.path());
+        let mut context = LogContext::default();
+        let start = Instant::now();
+        let time = SystemTime::now();
+
+        // Nothing is written until a robot shows up
+        logger.tick(&context, start, time).unwrap();
+        assert!(logger.session().is_none());
+
+        let mut now = start;
+        for lost in [0, 0, 1, 1] {
+            now += RECORD_INTERVAL;
+            logger.on_packet(packet(12.0, false), stats(lost), now);
+            logger.tick(&context, now, time).unwrap();
+        }
+        let first = logger.session().unwrap().name().to_string();
+        logger
+            .on_message(&TcpMessage::Stdout("robot up".into()), time)
+            .unwrap();
+
+        // A new match gets its own file
+        context.match_info = MatchInfo {
+            event_name: String::new(),
+            match_type: MatchType::Practice,
+            match_number: 1,
+            replay_number: 1,
+        };
+        now += RECORD_INTERVAL;
+        logger.on_packet(packet(12.0, true), stats(1), now);
+        logger.tick(&context, now, time).unwrap();
+        let second = logger.session().unwrap().name().to_string();
+        assert_eq!(second, format!("{first} P1"));
+
+        // Still open through a short drop out, closed after a long one
+        now += Duration::from_secs(5);
+        logger.tick(&context, now, time).unwrap();
+        assert!(logger.session().is_some());
+        now += SESSION_GRACE;
+        logger.tick(&context, now, time).unwrap();
+        assert!(logger.session().is_none());
+
+        let dslog = std::fs::read(dir.path().join(format!("{first}.dslog"))).unwrap();
+        assert_eq!(dslog.len(), 20 + 4 * RECORD_LEN);
+        let last = &dslog[20 + 3 * RECORD_LEN..];
+        // 1 lost out of 5 packets, rounded to 4% steps
+        assert_eq!(last[1], 5);
+        assert_eq!(last[2..4], [0x0C, 0x00]);
+        assert_eq!(last[4], 100);
+
+        let dsevents = std::fs::read(dir.path().join(format!("{first}.dsevents"))).unwrap();
+        assert!(String::from_utf8_lossy(&dsevents[20..]).contains("<message> robot up"));
+
+        // Records for the whole second session, the drop out included
+        let dslog = std::fs::read(dir.path().join(format!("{second}.dslog"))).unwrap();
+        assert_eq!(dslog.len(), 20 + 2 * RECORD_LEN);
+    }
+}
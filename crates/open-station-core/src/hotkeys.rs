@@ -1,9 +1,12 @@
+use crate::config::HotkeyConfig;
 use std::collections::HashSet;
+use std::fmt;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex,
 };
 use std::thread;
+use thiserror::Error;
 use tokio::sync::mpsc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,18 +18,328 @@ pub enum HotkeyAction {
     RescanJoysticks,
 }
 
+impl HotkeyAction {
+    // Checked in this order, so stopping wins whenever chords overlap
+    pub const ALL: [HotkeyAction; 5] = [
+        HotkeyAction::EStop,
+        HotkeyAction::Disable,
+        HotkeyAction::AStop,
+        HotkeyAction::RescanJoysticks,
+        HotkeyAction::Enable,
+    ];
+
+    // Matches the field names of the [hotkeys] config section
+    pub fn name(self) -> &'static str {
+        match self {
+            HotkeyAction::EStop => "estop",
+            HotkeyAction::Disable => "disable",
+            HotkeyAction::Enable => "enable",
+            HotkeyAction::AStop => "a_stop",
+            HotkeyAction::RescanJoysticks => "rescan",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<HotkeyAction> {
+        Self::ALL.into_iter().find(|a| a.name() == name)
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum HotkeyError {
+    #[error("unknown hotkey action {0:?}")]
+    UnknownAction(String),
+    #[error("no keys bound to {0}")]
+    Empty(&'static str),
+    #[error("unknown key {key:?} in the {action} binding")]
+    UnknownKey { action: &'static str, key: String },
+    #[error("{key} appears twice in the {action} binding")]
+    RepeatedKey { action: &'static str, key: String },
+    #[error("enable must be a chord of at least two keys")]
+    SingleKeyEnable,
+    #[error("estop must be a single key")]
+    ChordedEStop,
+    #[error("the {0} and {1} bindings overlap")]
+    Conflict(&'static str, &'static str),
+}
+
+// Canonical name first; later entries for the same key are accepted aliases
+const KEY_NAMES: &[(&str, rdev::Key)] = &[
+    ("Space", rdev::Key::Space),
+    ("Enter", rdev::Key::Return),
+    ("Return", rdev::Key::Return),
+    ("Backspace", rdev::Key::Backspace),
+    ("Tab", rdev::Key::Tab),
+    ("Escape", rdev::Key::Escape),
+    ("Esc", rdev::Key::Escape),
+    ("Delete", rdev::Key::Delete),
+    ("Insert", rdev::Key::Insert),
+    ("Home", rdev::Key::Home),
+    ("End", rdev::Key::End),
+    ("PageUp", rdev::Key::PageUp),
+    ("PageDown", rdev::Key::PageDown),
+    ("Up", rdev::Key::UpArrow),
+    ("Down", rdev::Key::DownArrow),
+    ("Left", rdev::Key::LeftArrow),
+    ("Right", rdev::Key::RightArrow),
+    ("Shift", rdev::Key::ShiftLeft),
+    ("RightShift", rdev::Key::ShiftRight),
+    ("Ctrl", rdev::Key::ControlLeft),
+    ("RightCtrl", rdev::Key::ControlRight),
+    ("Alt", rdev::Key::Alt),
+    ("AltGr", rdev::Key::AltGr),
+    ("Meta", rdev::Key::MetaLeft),
+    ("RightMeta", rdev::Key::MetaRight),
+    ("CapsLock", rdev::Key::CapsLock),
+    ("Pause", rdev::Key::Pause),
+    ("PrintScreen", rdev::Key::PrintScreen),
+    ("ScrollLock", rdev::Key::ScrollLock),
+    ("NumLock", rdev::Key::NumLock),
+    ("F1", rdev::Key::F1),
+    ("F2", rdev::Key::F2),
+    ("F3", rdev::Key::F3),
+    ("F4", rdev::Key::F4),
+    ("F5", rdev::Key::F5),
+    ("F6", rdev::Key::F6),
+    ("F7", rdev::Key::F7),
+    ("F8", rdev::Key::F8),
+    ("F9", rdev::Key::F9),
+    ("F10", rdev::Key::F10),
+    ("F11", rdev::Key::F11),
+    ("F12", rdev::Key::F12),
+    ("A", rdev::Key::KeyA),
+    ("B", rdev::Key::KeyB),
+    ("C", rdev::Key::KeyC),
+    ("D", rdev::Key::KeyD),
+    ("E", rdev::Key::KeyE),
+    ("F", rdev::Key::KeyF),
+    ("G", rdev::Key::KeyG),
+    ("H", rdev::Key::KeyH),
+    ("I", rdev::Key::KeyI),
+    ("J", rdev::Key::KeyJ),
+    ("K", rdev::Key::KeyK),
+    ("L", rdev::Key::KeyL),
+    ("M", rdev::Key::KeyM),
+    ("N", rdev::Key::KeyN),
+    ("O", rdev::Key::KeyO),
+    ("P", rdev::Key::KeyP),
+    ("Q", rdev::Key::KeyQ),
+    ("R", rdev::Key::KeyR),
+    ("S", rdev::Key::KeyS),
+    ("T", rdev::Key::KeyT),
+    ("U", rdev::Key::KeyU),
+    ("V", rdev::Key::KeyV),
+    ("W", rdev::Key::KeyW),
+    ("X", rdev::Key::KeyX),
+    ("Y", rdev::Key::KeyY),
+    ("Z", rdev::Key::KeyZ),
+    ("0", rdev::Key::Num0),
+    ("1", rdev::Key::Num1),
+    ("2", rdev::Key::Num2),
+    ("3", rdev::Key::Num3),
+    ("4", rdev::Key::Num4),
+    ("5", rdev::Key::Num5),
+    ("6", rdev::Key::Num6),
+    ("7", rdev::Key::Num7),
+    ("8", rdev::Key::Num8),
+    ("9", rdev::Key::Num9),
+    ("[", rdev::Key::LeftBracket),
+    ("]", rdev::Key::RightBracket),
+    ("\\", rdev::Key::BackSlash),
+    (";", rdev::Key::SemiColon),
+    ("'", rdev::Key::Quote),
+    (",", rdev::Key::Comma),
+    (".", rdev::Key::Dot),
+    ("/", rdev::Key::Slash),
+    ("-", rdev::Key::Minus),
+    ("=", rdev::Key::Equal),
+    ("`", rdev::Key::BackQuote),
+    ("IntlBackslash", rdev::Key::IntlBackslash),
+    ("Num0", rdev::Key::Kp0),
+    ("Num1", rdev::Key::Kp1),
+    ("Num2", rdev::Key::Kp2),
+    ("Num3", rdev::Key::Kp3),
+    ("Num4", rdev::Key::Kp4),
+    ("Num5", rdev::Key::Kp5),
+    ("Num6", rdev::Key::Kp6),
+    ("Num7", rdev::Key::Kp7),
+    ("Num8", rdev::Key::Kp8),
+    ("Num9", rdev::Key::Kp9),
+    ("NumEnter", rdev::Key::KpReturn),
+    ("NumPlus", rdev::Key::KpPlus),
+    ("NumMinus", rdev::Key::KpMinus),
+    ("NumMultiply", rdev::Key::KpMultiply),
+    ("NumDivide", rdev::Key::KpDivide),
+    ("NumDelete", rdev::Key::KpDelete),
+];
+
+pub fn parse_key(name: &str) -> Option<rdev::Key> {
+    KEY_NAMES
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+}
+
+pub fn key_name(key: rdev::Key) -> String {
+    KEY_NAMES
+        .iter()
+        .find(|(_, k)| *k == key)
+        .map(|(n, _)| n.to_string())
+        .unwrap_or_else(|| format!("{:?}", key))
+}
+
+fn key_order(key: &rdev::Key) -> usize {
+    KEY_NAMES
+        .iter()
+        .position(|(_, k)| k == key)
+        .unwrap_or(usize::MAX)
+}
+
+// One or more keys joined with `+`, e.g. `Space`, `Ctrl+E` or `[+]+\`.
+// All keys have to be held at once for the binding to fire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding(Vec<rdev::Key>);
+
+impl Binding {
+    pub fn parse(action: HotkeyAction, text: &str) -> Result<Binding, HotkeyError> {
+        if text.trim().is_empty() {
+            return Err(HotkeyError::Empty(action.name()));
+        }
+
+        let mut keys = Vec::new();
+        for name in text.split('+').map(str::trim) {
+            let key = parse_key(name).ok_or_else(|| HotkeyError::UnknownKey {
+                action: action.name(),
+                key: name.to_string(),
+            })?;
+            if keys.contains(&key) {
+                return Err(HotkeyError::RepeatedKey {
+                    action: action.name(),
+                    key: key_name(key),
+                });
+            }
+            keys.push(key);
+        }
+        Ok(Binding(keys))
+    }
+
+    pub fn from_pressed(pressed: &HashSet<rdev::Key>) -> Binding {
+        let mut keys: Vec<rdev::Key> = pressed.iter().copied().collect();
+        keys.sort_by_key(key_order);
+        Binding(keys)
+    }
+
+    pub fn keys(&self) -> &[rdev::Key] {
+        &self.0
+    }
+
+    fn is_subset_of(&self, other: &Binding) -> bool {
+        self.0.iter().all(|k| other.0.contains(k))
+    }
+
+    // Fires on the press that completes the chord
+    fn matches(&self, key: rdev::Key, pressed: &HashSet<rdev::Key>) -> bool {
+        self.0.contains(&key) && self.0.iter().all(|k| pressed.contains(k))
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = self.0.iter().map(|k| key_name(*k)).collect();
+        write!(f, "{}", names.join("+"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(HotkeyAction, Binding)>,
+}
+
+impl Keymap {
+    pub fn from_config(config: &HotkeyConfig) -> Result<Keymap, HotkeyError> {
+        let mut bindings = Vec::with_capacity(HotkeyAction::ALL.len());
+        for action in HotkeyAction::ALL {
+            bindings.push((action, Binding::parse(action, config.binding(action))?));
+        }
+
+        for (action, binding) in &bindings {
+            match action {
+                // A single stray key press must never enable the robot
+                HotkeyAction::Enable if binding.0.len() < 2 => {
+                    return Err(HotkeyError::SingleKeyEnable)
+                }
+                // E-stop has to work with one hand in a hurry
+                HotkeyAction::EStop if binding.0.len() > 1 => {
+                    return Err(HotkeyError::ChordedEStop)
+                }
+                _ => {}
+            }
+        }
+
+        // If one binding is contained in another, pressing the larger one
+        // would fire both
+        for (i, (a, a_binding)) in bindings.iter().enumerate() {
+            for (b, b_binding) in &bindings[i + 1..] {
+                if a_binding.is_subset_of(b_binding) || b_binding.is_subset_of(a_binding) {
+                    return Err(HotkeyError::Conflict(a.name(), b.name()));
+                }
+            }
+        }
+
+        Ok(Keymap { bindings })
+    }
+
+    pub fn binding(&self, action: HotkeyAction) -> &Binding {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, b)| b)
+            .expect("every action is bound")
+    }
+
+    // `pressed` already includes `key`. Keys outside the keymap are ignored so
+    // typing elsewhere never touches the robot.
+    pub fn action_for(&self, key: rdev::Key, pressed: &HashSet<rdev::Key>) -> Option<HotkeyAction> {
+        self.bindings
+            .iter()
+            .find(|(_, binding)| binding.matches(key, pressed))
+            .map(|(action, _)| *action)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::from_config(&HotkeyConfig::default()).expect("default hotkeys are valid")
+    }
+}
+
+// Reported instead of acting while the operator tries out their bindings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotkeyTest {
+    pub keys: Binding,
+    pub action: Option<HotkeyAction>,
+}
+
 pub struct HotkeyManager {
     tx: mpsc::UnboundedSender<HotkeyAction>,
     rx: mpsc::UnboundedReceiver<HotkeyAction>,
+    test_tx: mpsc::UnboundedSender<HotkeyTest>,
+    test_rx: mpsc::UnboundedReceiver<HotkeyTest>,
+    keymap: Arc<Mutex<Keymap>>,
+    testing: Arc<AtomicBool>,
     running: Arc<AtomicBool>,
 }
 
 impl HotkeyManager {
     pub fn new() -> Self {
         let (tx, rx) = mpsc::unbounded_channel();
+        let (test_tx, test_rx) = mpsc::unbounded_channel();
         Self {
             tx,
             rx,
+            test_tx,
+            test_rx,
+            keymap: Arc::new(Mutex::new(Keymap::default())),
+            testing: Arc::new(AtomicBool::new(false)),
             running: Arc::new(AtomicBool::new(false)),
         }
     }
@@ -38,45 +351,34 @@ impl HotkeyManager {
         self.running.store(true, Ordering::SeqCst);
 
         let tx = self.tx.clone();
+        let test_tx = self.test_tx.clone();
+        let keymap = self.keymap.clone();
+        let testing = self.testing.clone();
         let running = self.running.clone();
         let pressed_keys: Arc<Mutex<HashSet<rdev::Key>>> = Arc::new(Mutex::new(HashSet::new()));
         let keys = pressed_keys.clone();
 
         thread::spawn(move || {
-            let callback = move |event: rdev::Event| {
-                match event.event_type {
-                    rdev::EventType::KeyPress(key) => {
-                        let mut pressed = keys.lock().unwrap();
-                        pressed.insert(key);
-
-                        match key {
-                            rdev::Key::Space => {
-                                let _ = tx.send(HotkeyAction::EStop);
-                            }
-                            rdev::Key::LeftBracket
-                            | rdev::Key::RightBracket
-                            | rdev::Key::BackSlash => {
-                                // Check if all three enable keys are pressed
-                                if pressed.contains(&rdev::Key::LeftBracket)
-                                    && pressed.contains(&rdev::Key::RightBracket)
-                                    && pressed.contains(&rdev::Key::BackSlash)
-                                {
-                                    let _ = tx.send(HotkeyAction::Enable);
-                                } else {
-                                    let _ = tx.send(HotkeyAction::Disable);
-                                }
-                            }
-                            _ => {
-                                let _ = tx.send(HotkeyAction::Disable);
-                            }
-                        }
-                    }
-                    rdev::EventType::KeyRelease(key) => {
-                        let mut pressed = keys.lock().unwrap();
-                        pressed.remove(&key);
+            let callback = move |event: rdev::Event| match event.event_type {
+                rdev::EventType::KeyPress(key) => {
+                    let mut pressed = keys.lock().unwrap();
+                    pressed.insert(key);
+
+                    let action = keymap.lock().unwrap().action_for(key, &pressed);
+                    if testing.load(Ordering::SeqCst) {
+                        let _ = test_tx.send(HotkeyTest {
+                            keys: Binding::from_pressed(&pressed),
+                            action,
+                        });
+                    } else if let Some(action) = action {
+                        let _ = tx.send(action);
                     }
-                    _ => {}
                 }
+                rdev::EventType::KeyRelease(key) => {
+                    let mut pressed = keys.lock().unwrap();
+                    pressed.remove(&key);
+                }
+                _ => {}
             };
 
             if let Err(e) = rdev::listen(callback) {
@@ -86,6 +388,22 @@ impl HotkeyManager {
         });
     }
 
+    pub fn set_keymap(&self, keymap: Keymap) {
+        *self.keymap.lock().unwrap() = keymap;
+    }
+
+    pub fn keymap(&self) -> Keymap {
+        self.keymap.lock().unwrap().clone()
+    }
+
+    pub fn set_testing(&self, testing: bool) {
+        self.testing.store(testing, Ordering::SeqCst);
+    }
+
+    pub fn is_testing(&self) -> bool {
+        self.testing.load(Ordering::SeqCst)
+    }
+
     pub async fn next_action(&mut self) -> Option<HotkeyAction> {
         self.rx.recv().await
     }
@@ -94,6 +412,10 @@ impl HotkeyManager {
         self.rx.try_recv().ok()
     }
 
+    pub fn try_next_test(&mut self) -> Option<HotkeyTest> {
+        self.test_rx.try_recv().ok()
+    }
+
     pub fn stop(&mut self) {
         self.running.store(false, Ordering::SeqCst);
     }
@@ -119,15 +441,182 @@ mod tests {
         assert_ne!(HotkeyAction::EStop, HotkeyAction::Disable);
     }
 
+    fn press(keys: &[rdev::Key]) -> Option<HotkeyAction> {
+        let pressed: HashSet<rdev::Key> = keys.iter().copied().collect();
+        Keymap::default().action_for(*keys.last().unwrap(), &pressed)
+    }
+
+    #[test]
+    fn test_keymap() {
+        assert_eq!(press(&[rdev::Key::Space]), Some(HotkeyAction::EStop));
+        assert_eq!(press(&[rdev::Key::Return]), Some(HotkeyAction::Disable));
+        assert_eq!(press(&[rdev::Key::Backspace]), Some(HotkeyAction::AStop));
+        assert_eq!(press(&[rdev::Key::F1]), Some(HotkeyAction::RescanJoysticks));
+    }
+
+    #[test]
+    fn test_enable_needs_full_chord() {
+        assert_eq!(press(&[rdev::Key::LeftBracket]), None);
+        assert_eq!(
+            press(&[rdev::Key::LeftBracket, rdev::Key::RightBracket]),
+            None
+        );
+        assert_eq!(
+            press(&[
+                rdev::Key::LeftBracket,
+                rdev::Key::RightBracket,
+                rdev::Key::BackSlash
+            ]),
+            Some(HotkeyAction::Enable)
+        );
+    }
+
+    #[test]
+    fn test_unrelated_keys_ignored() {
+        assert_eq!(press(&[rdev::Key::KeyA]), None);
+        assert_eq!(press(&[rdev::Key::ShiftLeft, rdev::Key::KeyW]), None);
+    }
+
+    #[test]
+    fn test_estop_wins_inside_enable_chord() {
+        let pressed: HashSet<rdev::Key> = [
+            rdev::Key::LeftBracket,
+            rdev::Key::RightBracket,
+            rdev::Key::BackSlash,
+            rdev::Key::Space,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            Keymap::default().action_for(rdev::Key::Space, &pressed),
+            Some(HotkeyAction::EStop)
+        );
+    }
+
+    #[test]
+    fn test_parse_bindings() {
+        let binding = Binding::parse(HotkeyAction::Enable, "[+]+\\").unwrap();
+        assert_eq!(
+            binding.keys(),
+            &[
+                rdev::Key::LeftBracket,
+                rdev::Key::RightBracket,
+                rdev::Key::BackSlash
+            ]
+        );
+        assert_eq!(binding.to_string(), "[+]+\\");
+
+        let binding = Binding::parse(HotkeyAction::Disable, " ctrl + return ").unwrap();
+        assert_eq!(binding.to_string(), "Ctrl+Enter");
+    }
+
+    #[test]
+    fn test_parse_binding_errors() {
+        assert_eq!(
+            Binding::parse(HotkeyAction::Disable, ""),
+            Err(HotkeyError::Empty("disable"))
+        );
+        assert_eq!(
+            Binding::parse(HotkeyAction::Disable, "Ctrl+Hyper"),
+            Err(HotkeyError::UnknownKey {
+                action: "disable",
+                key: "Hyper".to_string()
+            })
+        );
+        assert_eq!(
+            Binding::parse(HotkeyAction::Enable, "A+a"),
+            Err(HotkeyError::RepeatedKey {
+                action: "enable",
+                key: "A".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsafe_keymaps() {
+        let config = HotkeyConfig {
+            enable: "E".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            Keymap::from_config(&config),
+            Err(HotkeyError::SingleKeyEnable)
+        );
+
+        let config = HotkeyConfig {
+            estop: "Ctrl+Space".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(Keymap::from_config(&config), Err(HotkeyError::ChordedEStop));
+    }
+
+    #[test]
+    fn test_rejects_conflicts() {
+        let config = HotkeyConfig {
+            disable: "Space".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            Keymap::from_config(&config),
+            Err(HotkeyError::Conflict("estop", "disable"))
+        );
+
+        // Holding the enable chord must not also disable
+        let config = HotkeyConfig {
+            enable: "Enter+E".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            Keymap::from_config(&config),
+            Err(HotkeyError::Conflict("disable", "enable"))
+        );
+    }
+
+    #[test]
+    fn test_custom_keymap() {
+        let config = HotkeyConfig {
+            estop: "Escape".to_string(),
+            enable: "Ctrl+Shift+E".to_string(),
+            ..Default::default()
+        };
+        let keymap = Keymap::from_config(&config).unwrap();
+        let pressed: HashSet<rdev::Key> = [
+            rdev::Key::ControlLeft,
+            rdev::Key::ShiftLeft,
+            rdev::Key::KeyE,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            keymap.action_for(rdev::Key::KeyE, &pressed),
+            Some(HotkeyAction::Enable)
+        );
+        assert_eq!(
+            keymap.action_for(rdev::Key::Space, &HashSet::from([rdev::Key::Space])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_action_names_round_trip() {
+        for action in HotkeyAction::ALL {
+            assert_eq!(HotkeyAction::from_name(action.name()), Some(action));
+        }
+        assert_eq!(HotkeyAction::from_name("launch"), None);
+    }
+
     #[test]
     fn test_manager_creation() {
         let manager = HotkeyManager::new();
         assert!(!manager.is_running());
+        assert!(!manager.is_testing());
+        assert_eq!(manager.keymap(), Keymap::default());
     }
 
     #[test]
     fn test_try_next_action_empty() {
         let mut manager = HotkeyManager::new();
         assert!(manager.try_next_action().is_none());
+        assert!(manager.try_next_test().is_none());
     }
 }
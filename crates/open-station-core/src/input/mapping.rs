@@ -1,4 +1,28 @@
 use gilrs::{Axis, Button, Gamepad};
+use open_station_protocol::types::{
+    JoystickType, AXIS_TYPE_THROTTLE, AXIS_TYPE_TWIST, AXIS_TYPE_X, AXIS_TYPE_Y, AXIS_TYPE_Z,
+};
+
+// Axis types reported for the standard layout: LX, LY, LT, RT, RX, RY
+pub const AXIS_TYPES: [u8; 6] = [
+    AXIS_TYPE_X,
+    AXIS_TYPE_Y,
+    AXIS_TYPE_Z,
+    AXIS_TYPE_Z,
+    AXIS_TYPE_TWIST,
+    AXIS_TYPE_THROTTLE,
+];
+pub const BUTTON_COUNT: u8 = 10;
+pub const POV_COUNT: u8 = 1;
+
+pub fn joystick_type(name: &str) -> JoystickType {
+    let name = name.to_lowercase();
+    if name.contains("xbox") || name.contains("xinput") {
+        JoystickType::XInputGamepad
+    } else {
+        JoystickType::HidGamepad
+    }
+}
 
 pub fn map_axis(axis: Axis) -> Option<usize> {
     match axis {
@@ -61,6 +85,22 @@ mod tests {
         assert_eq!(map_axis(Axis::RightStickY), Some(5));
     }
 
+    #[test]
+    fn test_joystick_type_detection() {
+        assert_eq!(
+            joystick_type("Xbox Wireless Controller"),
+            JoystickType::XInputGamepad
+        );
+        assert_eq!(
+            joystick_type("XInput Controller #1"),
+            JoystickType::XInputGamepad
+        );
+        assert_eq!(
+            joystick_type("Logitech Dual Action"),
+            JoystickType::HidGamepad
+        );
+    }
+
     #[test]
     fn test_button_mapping() {
         assert_eq!(map_button(Button::South), Some(0));
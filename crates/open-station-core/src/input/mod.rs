@@ -1,5 +1,5 @@
 use gilrs::{EventType, GamepadId, Gilrs};
-use open_station_protocol::types::JoystickData;
+use open_station_protocol::types::{JoystickData, JoystickDescriptor};
 use std::collections::HashMap;
 
 pub mod mapping;
@@ -16,6 +16,20 @@ pub struct JoystickInfo {
     pub pov_count: u8,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JoystickEvent {
+    Connected {
+        slot: u8,
+        uuid: String,
+        name: String,
+    },
+    Disconnected {
+        slot: u8,
+        uuid: String,
+        name: String,
+    },
+}
+
 #[derive(Debug)]
 struct JoystickSlot {
     uuid: String,
@@ -30,6 +44,7 @@ pub struct JoystickManager {
     gilrs: Gilrs,
     slots: Vec<Option<JoystickSlot>>,
     locks: HashMap<String, u8>,
+    events: Vec<JoystickEvent>,
 }
 
 impl JoystickManager {
@@ -39,6 +54,7 @@ impl JoystickManager {
             gilrs,
             slots: (0..6).map(|_| None).collect(),
             locks,
+            events: Vec::new(),
         };
         manager.scan_devices();
         manager
@@ -54,6 +70,11 @@ impl JoystickManager {
         }
     }
 
+    // Connect/disconnect events since the last call, in the order they happened
+    pub fn take_events(&mut self) -> Vec<JoystickEvent> {
+        std::mem::take(&mut self.events)
+    }
+
     pub fn get_joystick_data(&self) -> Vec<JoystickData> {
         self.slots
             .iter()
@@ -75,14 +96,30 @@ impl JoystickManager {
                     name: js.name.clone(),
                     locked: js.locked,
                     connected: js.connected,
-                    axis_count: 6, // standard FRC
-                    button_count: 10,
-                    pov_count: 1,
+                    axis_count: mapping::AXIS_TYPES.len() as u8, // standard FRC
+                    button_count: mapping::BUTTON_COUNT,
+                    pov_count: mapping::POV_COUNT,
                 })
             })
             .collect()
     }
 
+    pub fn get_joystick_descriptors(&self) -> Vec<JoystickDescriptor> {
+        self.slots
+            .iter()
+            .map(|slot| match slot {
+                Some(js) if js.connected => JoystickDescriptor {
+                    name: js.name.clone(),
+                    joystick_type: mapping::joystick_type(&js.name),
+                    axis_types: mapping::AXIS_TYPES.to_vec(),
+                    button_count: mapping::BUTTON_COUNT,
+                    pov_count: mapping::POV_COUNT,
+                },
+                _ => JoystickDescriptor::default(),
+            })
+            .collect()
+    }
+
     pub fn reorder(&mut self, order: Vec<String>) {
         let mut new_slots: Vec<Option<JoystickSlot>> = (0..6).map(|_| None).collect();
 
@@ -172,9 +209,16 @@ impl JoystickManager {
         let uuid = self.uuid_for_gamepad(id);
         let name = gamepad.name().to_string();
 
-        for slot in &mut self.slots {
+        for (i, slot) in self.slots.iter_mut().enumerate() {
             if let Some(js) = slot.as_mut() {
                 if js.uuid == uuid {
+                    if !js.connected {
+                        self.events.push(JoystickEvent::Connected {
+                            slot: i as u8,
+                            uuid,
+                            name,
+                        });
+                    }
                     js.connected = true;
                     js.gilrs_id = id;
                     return;
@@ -185,31 +229,46 @@ impl JoystickManager {
         if let Some(&preferred_slot) = self.locks.get(&uuid) {
             if let Some(slot) = self.slots.get_mut(preferred_slot as usize) {
                 *slot = Some(JoystickSlot {
-                    uuid,
-                    name,
+                    uuid: uuid.clone(),
+                    name: name.clone(),
                     gilrs_id: id,
                     locked: true,
                     connected: true,
                 });
+                self.events.push(JoystickEvent::Connected {
+                    slot: preferred_slot,
+                    uuid,
+                    name,
+                });
                 return;
             }
         }
 
         if let Some(empty_slot_idx) = self.find_empty_slot() {
             self.slots[empty_slot_idx] = Some(JoystickSlot {
-                uuid,
+                uuid: uuid.clone(),
                 name: name.clone(),
                 gilrs_id: id,
                 locked: false,
                 connected: true,
             });
+            self.events.push(JoystickEvent::Connected {
+                slot: empty_slot_idx as u8,
+                uuid,
+                name,
+            });
         }
     }
 
     fn on_device_disconnected(&mut self, id: GamepadId) {
-        for slot in &mut self.slots {
+        for (i, slot) in self.slots.iter_mut().enumerate() {
             if let Some(js) = slot.as_mut() {
-                if js.gilrs_id == id {
+                if js.gilrs_id == id && js.connected {
+                    self.events.push(JoystickEvent::Disconnected {
+                        slot: i as u8,
+                        uuid: js.uuid.clone(),
+                        name: js.name.clone(),
+                    });
                     if js.locked {
                         js.connected = false;
                     } else {
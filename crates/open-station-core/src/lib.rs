@@ -1,5 +1,10 @@
+#[cfg(feature = "audio")]
+pub mod audio;
 pub mod config;
+pub mod dslog;
 pub mod hotkeys;
 pub mod input;
 pub mod practice;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod state;
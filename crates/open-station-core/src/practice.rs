@@ -1,7 +1,11 @@
-use crate::config::PracticeTiming;
+use crate::config::{MatchSchedule, ScheduleMode, SchedulePhase};
+use open_station_protocol::clock::{Clock, SystemClock};
 use open_station_protocol::types::Mode;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+// What kind of period the match is in. Disabled phases count as Countdown
+// until the first enabled phase has run and as Delay after that.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PracticePhase {
     Idle,
@@ -9,9 +13,24 @@ pub enum PracticePhase {
     Autonomous,
     Delay,
     Teleop,
+    Test,
     Done,
 }
 
+// The field cues played during a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchSound {
+    StartAuto,
+    EndAuto,
+    StartTeleop,
+    Endgame,
+    EndMatch,
+    Abort,
+}
+
+// Teleop time left when the endgame warning sounds
+const ENDGAME_WARNING: Duration = Duration::from_secs(20);
+
 #[derive(Debug, Clone)]
 pub struct PracticeTick {
     pub phase: PracticePhase,
@@ -22,56 +41,171 @@ pub struct PracticeTick {
     pub mode: Option<Mode>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipTarget {
+    // Start of the next teleop period
+    Teleop,
+    // The endgame warning of the last teleop period
+    Endgame,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Idle,
+    Running(usize),
+    Done,
+}
+
 pub struct PracticeMode {
-    phase: PracticePhase,
-    timing: PracticeTiming,
+    schedule: MatchSchedule,
+    clock: Arc<dyn Clock>,
+    step: Step,
     phase_start: Option<Instant>,
+    paused_at: Option<Instant>,
+    // Robot state to apply on the next tick after pause/resume
+    pause_pending: bool,
+    resume_pending: bool,
     a_stopped: bool,
-    prev_phase: PracticePhase,
+    prev_step: Step,
+    endgame_warned: bool,
+    sounds: Vec<MatchSound>,
 }
 
 impl PracticeMode {
-    pub fn new(timing: PracticeTiming) -> Self {
+    pub fn new(schedule: MatchSchedule) -> Self {
+        Self::with_clock(schedule, Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(schedule: MatchSchedule, clock: Arc<dyn Clock>) -> Self {
         Self {
-            phase: PracticePhase::Idle,
-            timing,
+            schedule,
+            clock,
+            step: Step::Idle,
             phase_start: None,
+            paused_at: None,
+            pause_pending: false,
+            resume_pending: false,
             a_stopped: false,
-            prev_phase: PracticePhase::Idle,
+            prev_step: Step::Idle,
+            endgame_warned: false,
+            sounds: Vec::new(),
         }
     }
 
     pub fn start(&mut self) {
-        self.phase = PracticePhase::Countdown;
-        self.phase_start = Some(Instant::now());
+        self.step = if self.schedule.phases.is_empty() {
+            Step::Done
+        } else {
+            Step::Running(0)
+        };
+        self.phase_start = Some(self.clock.now());
         self.a_stopped = false;
-        self.prev_phase = PracticePhase::Idle;
+        self.prev_step = Step::Idle;
+        self.endgame_warned = false;
+        self.clear_pause();
     }
 
     pub fn stop(&mut self) {
-        self.phase = PracticePhase::Idle;
+        // Only a match cut short gets the abort cue, not the countdown
+        if let Step::Running(index) = self.step {
+            if self.started_match(index) {
+                self.sounds.push(MatchSound::Abort);
+            }
+        }
+        self.step = Step::Idle;
         self.phase_start = None;
         self.a_stopped = false;
+        self.clear_pause();
+    }
+
+    // Disables the robot and freezes the match clock
+    pub fn pause(&mut self) {
+        if self.is_running() && self.paused_at.is_none() {
+            self.paused_at = Some(self.clock.now());
+            self.pause_pending = true;
+            self.resume_pending = false;
+        }
+    }
+
+    // Continues with the time that was left when paused
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            let paused_for = self.clock.now().duration_since(paused_at);
+            self.phase_start = self.phase_start.map(|s| s + paused_for);
+            self.pause_pending = false;
+            self.resume_pending = true;
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    // Returns false when the schedule has no such period left
+    pub fn skip(&mut self, target: SkipTarget) -> bool {
+        let Step::Running(current) = self.step else {
+            return false;
+        };
+        let is_teleop = |s: &SchedulePhase| s.enabled && s.mode == ScheduleMode::Teleop;
+        let found = match target {
+            SkipTarget::Teleop => self
+                .schedule
+                .phases
+                .iter()
+                .enumerate()
+                .skip(current)
+                .find(|(_, s)| is_teleop(s)),
+            SkipTarget::Endgame => self
+                .schedule
+                .phases
+                .iter()
+                .enumerate()
+                .skip(current)
+                .rfind(|(_, s)| is_teleop(s)),
+        };
+        let Some((index, segment)) = found else {
+            return false;
+        };
+
+        let duration = Duration::from_secs(segment.duration_secs as u64);
+        let offset = match target {
+            SkipTarget::Teleop if index == current => return false,
+            SkipTarget::Teleop => Duration::ZERO,
+            SkipTarget::Endgame => duration.saturating_sub(ENDGAME_WARNING),
+        };
+        self.step = Step::Running(index);
+        self.phase_start = Some(self.match_now() - offset);
+        true
+    }
+
+    fn clear_pause(&mut self) {
+        self.paused_at = None;
+        self.pause_pending = false;
+        self.resume_pending = false;
+    }
+
+    // The match clock stands still while paused
+    fn match_now(&self) -> Instant {
+        self.paused_at.unwrap_or_else(|| self.clock.now())
     }
 
     pub fn a_stop(&mut self) {
-        if self.phase == PracticePhase::Autonomous {
+        if self.phase() == PracticePhase::Autonomous {
             self.a_stopped = true;
         }
     }
 
     pub fn tick(&mut self) -> PracticeTick {
-        let now = Instant::now();
+        let now = self.match_now();
         let elapsed = self
             .phase_start
             .map(|s| now.duration_since(s))
             .unwrap_or_default();
 
-        let phase_duration = self.phase_duration();
-
-        // Check if current phase has expired
-        if let Some(dur) = phase_duration {
-            if elapsed >= dur {
+        // Check if current phase has expired. A phase that was just entered
+        // gets one tick first, so even zero length phases are acted on.
+        if let Some(dur) = self.phase_duration() {
+            if elapsed >= dur && self.step == self.prev_step {
                 self.advance_phase(now);
             }
         }
@@ -85,47 +219,45 @@ impl PracticeMode {
             .map(|d| d.saturating_sub(elapsed))
             .unwrap_or_default();
 
-        let transitioning = self.phase != self.prev_phase;
-        let should_enable = transitioning
-            && matches!(
-                self.phase,
-                PracticePhase::Autonomous | PracticePhase::Teleop
-            )
-            && !self.a_stopped;
-        let should_disable = transitioning
-            && matches!(
-                self.phase,
-                PracticePhase::Delay | PracticePhase::Done | PracticePhase::Countdown
-            );
-
-        let mode = if transitioning {
-            match self.phase {
-                PracticePhase::Autonomous => Some(Mode::Autonomous),
-                PracticePhase::Teleop => Some(Mode::Teleop),
-                _ => None,
+        let transitioning = self.step != self.prev_step;
+        if transitioning {
+            // An A-Stop only lasts for the phase it was pressed in
+            self.a_stopped = false;
+            self.endgame_warned = false;
+            self.queue_transition_sounds();
+        }
+
+        let segment = self.segment().cloned();
+        let enabled_phase = segment.as_ref().is_some_and(|s| s.enabled);
+
+        // Phases that are endgame already, or shorter, get no warning
+        if let Some(seg) = segment.as_ref().filter(|s| s.enabled) {
+            if seg.mode == ScheduleMode::Teleop
+                && !self.endgame_warned
+                && Duration::from_secs(seg.duration_secs as u64) > ENDGAME_WARNING
+                && remaining <= ENDGAME_WARNING
+            {
+                self.endgame_warned = true;
+                self.sounds.push(MatchSound::Endgame);
             }
-        } else {
-            None
-        };
+        }
 
+        let paused = self.is_paused();
         let should_enable =
-            if self.phase == PracticePhase::Teleop && transitioning && self.a_stopped {
-                self.a_stopped = false;
-                true
-            } else {
-                should_enable
-            };
-
-        let should_disable = if self.a_stopped && self.phase == PracticePhase::Autonomous {
-            true
-        } else {
-            should_disable
-        };
+            enabled_phase && !paused && !self.a_stopped && (transitioning || self.resume_pending);
+        let should_disable = (transitioning && !enabled_phase && self.step != Step::Idle)
+            || (self.a_stopped && self.phase() == PracticePhase::Autonomous)
+            || self.pause_pending;
+        self.pause_pending = false;
+        self.resume_pending = false;
+        let mode = segment
+            .filter(|_| transitioning)
+            .map(|s| Mode::from(s.mode));
 
-        self.prev_phase = self.phase;
+        self.prev_step = self.step;
 
         PracticeTick {
-            phase: self.phase,
+            phase: self.phase(),
             elapsed,
             remaining,
             should_enable,
@@ -134,37 +266,109 @@ impl PracticeMode {
         }
     }
 
+    pub fn take_sounds(&mut self) -> Vec<MatchSound> {
+        std::mem::take(&mut self.sounds)
+    }
+
+    fn queue_transition_sounds(&mut self) {
+        if self.step == Step::Done {
+            self.sounds.push(MatchSound::EndMatch);
+            return;
+        }
+
+        let was_auto = match self.prev_step {
+            Step::Running(index) => self
+                .schedule
+                .phases
+                .get(index)
+                .is_some_and(|s| s.enabled && s.mode == ScheduleMode::Autonomous),
+            _ => false,
+        };
+        if was_auto {
+            self.sounds.push(MatchSound::EndAuto);
+        }
+
+        match self.segment() {
+            Some(s) if s.enabled && s.mode == ScheduleMode::Autonomous => {
+                self.sounds.push(MatchSound::StartAuto)
+            }
+            Some(s) if s.enabled && s.mode == ScheduleMode::Teleop => {
+                self.sounds.push(MatchSound::StartTeleop)
+            }
+            _ => {}
+        }
+    }
+
     pub fn phase(&self) -> PracticePhase {
-        self.phase
+        match self.step {
+            Step::Idle => PracticePhase::Idle,
+            Step::Done => PracticePhase::Done,
+            Step::Running(index) => match self.segment() {
+                Some(s) if s.enabled => match s.mode {
+                    ScheduleMode::Autonomous => PracticePhase::Autonomous,
+                    ScheduleMode::Teleop => PracticePhase::Teleop,
+                    ScheduleMode::Test => PracticePhase::Test,
+                },
+                _ if self.started_match(index) => PracticePhase::Delay,
+                _ => PracticePhase::Countdown,
+            },
+        }
+    }
+
+    // The schedule's name for the current phase
+    pub fn label(&self) -> String {
+        match self.segment() {
+            Some(s) => s.label.clone(),
+            None => format!("{:?}", self.phase()),
+        }
     }
 
     pub fn is_running(&self) -> bool {
-        self.phase != PracticePhase::Idle && self.phase != PracticePhase::Done
+        matches!(self.step, Step::Running(_))
+    }
+
+    // Takes effect from the next phase on
+    pub fn set_schedule(&mut self, schedule: MatchSchedule) {
+        self.schedule = schedule;
+    }
+
+    pub fn schedule(&self) -> &MatchSchedule {
+        &self.schedule
+    }
+
+    fn segment(&self) -> Option<&SchedulePhase> {
+        match self.step {
+            Step::Running(index) => self.schedule.phases.get(index),
+            _ => None,
+        }
     }
 
-    pub fn set_timing(&mut self, timing: PracticeTiming) {
-        self.timing = timing;
+    fn started_match(&self, index: usize) -> bool {
+        self.schedule
+            .phases
+            .iter()
+            .take(index + 1)
+            .any(|s| s.enabled)
     }
 
     fn phase_duration(&self) -> Option<Duration> {
-        match self.phase {
-            PracticePhase::Idle => None,
-            PracticePhase::Countdown => {
-                Some(Duration::from_secs(self.timing.countdown_secs as u64))
-            }
-            PracticePhase::Autonomous => Some(Duration::from_secs(self.timing.auto_secs as u64)),
-            PracticePhase::Delay => Some(Duration::from_secs(self.timing.delay_secs as u64)),
-            PracticePhase::Teleop => Some(Duration::from_secs(self.timing.teleop_secs as u64)),
-            PracticePhase::Done => None,
+        match self.step {
+            // A phase removed by a schedule change ends right away
+            Step::Running(_) => Some(
+                self.segment()
+                    .map(|s| Duration::from_secs(s.duration_secs as u64))
+                    .unwrap_or_default(),
+            ),
+            Step::Idle | Step::Done => None,
         }
     }
 
     fn advance_phase(&mut self, now: Instant) {
-        self.phase = match self.phase {
-            PracticePhase::Countdown => PracticePhase::Autonomous,
-            PracticePhase::Autonomous => PracticePhase::Delay,
-            PracticePhase::Delay => PracticePhase::Teleop,
-            PracticePhase::Teleop => PracticePhase::Done,
+        self.step = match self.step {
+            Step::Running(index) if index + 1 < self.schedule.phases.len() => {
+                Step::Running(index + 1)
+            }
+            Step::Running(_) => Step::Done,
             other => other, // Idle and Done don't advance
         };
         self.phase_start = Some(now);
@@ -174,6 +378,16 @@ impl PracticeMode {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::PracticeTiming;
+    use open_station_protocol::clock::MockClock;
+
+    fn classic(timing: PracticeTiming) -> PracticeMode {
+        PracticeMode::new(MatchSchedule::classic(&timing))
+    }
+
+    fn phase(label: &str, mode: ScheduleMode, duration_secs: u32, enabled: bool) -> SchedulePhase {
+        SchedulePhase::new(label, mode, duration_secs, enabled)
+    }
 
     fn fast_timing() -> PracticeTiming {
         PracticeTiming {
@@ -186,14 +400,14 @@ mod tests {
 
     #[test]
     fn test_initial_state() {
-        let pm = PracticeMode::new(PracticeTiming::default());
+        let pm = classic(PracticeTiming::default());
         assert_eq!(pm.phase(), PracticePhase::Idle);
         assert!(!pm.is_running());
     }
 
     #[test]
     fn test_start() {
-        let mut pm = PracticeMode::new(PracticeTiming::default());
+        let mut pm = classic(PracticeTiming::default());
         pm.start();
         assert_eq!(pm.phase(), PracticePhase::Countdown);
         assert!(pm.is_running());
@@ -201,7 +415,7 @@ mod tests {
 
     #[test]
     fn test_stop_resets_to_idle() {
-        let mut pm = PracticeMode::new(PracticeTiming::default());
+        let mut pm = classic(PracticeTiming::default());
         pm.start();
         pm.stop();
         assert_eq!(pm.phase(), PracticePhase::Idle);
@@ -211,7 +425,7 @@ mod tests {
     #[test]
     fn test_phase_transitions_with_zero_timing() {
         // With 0-second timing, phases should advance immediately on tick
-        let mut pm = PracticeMode::new(fast_timing());
+        let mut pm = classic(fast_timing());
         pm.start();
 
         // First tick advances from Countdown
@@ -234,7 +448,7 @@ mod tests {
 
     #[test]
     fn test_enable_on_auto_transition() {
-        let mut pm = PracticeMode::new(fast_timing());
+        let mut pm = classic(fast_timing());
         pm.start();
         // Tick through until we see should_enable with Auto mode
         let mut saw_auto_enable = false;
@@ -250,7 +464,7 @@ mod tests {
 
     #[test]
     fn test_done_is_not_running() {
-        let mut pm = PracticeMode::new(fast_timing());
+        let mut pm = classic(fast_timing());
         pm.start();
         for _ in 0..20 {
             pm.tick();
@@ -258,4 +472,371 @@ mod tests {
         assert_eq!(pm.phase(), PracticePhase::Done);
         assert!(!pm.is_running());
     }
+
+    fn run_to_done(pm: &mut PracticeMode) -> Vec<MatchSound> {
+        let mut sounds = Vec::new();
+        for _ in 0..20 {
+            pm.tick();
+            sounds.extend(pm.take_sounds());
+        }
+        sounds
+    }
+
+    #[test]
+    fn test_match_sound_sequence() {
+        let mut pm = classic(fast_timing());
+        pm.start();
+        assert_eq!(
+            run_to_done(&mut pm),
+            vec![
+                MatchSound::StartAuto,
+                MatchSound::EndAuto,
+                MatchSound::StartTeleop,
+                MatchSound::EndMatch,
+            ]
+        );
+        assert!(pm.take_sounds().is_empty());
+    }
+
+    #[test]
+    fn test_endgame_warning_plays_once() {
+        let (mut pm, clock) = mock_classic();
+        start_auto(&mut pm, &clock);
+        clock.advance(Duration::from_secs(15));
+        pm.tick();
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(pm.tick().phase, PracticePhase::Teleop);
+        assert_eq!(
+            pm.take_sounds(),
+            vec![
+                MatchSound::StartAuto,
+                MatchSound::EndAuto,
+                MatchSound::StartTeleop
+            ]
+        );
+
+        clock.advance(Duration::from_secs(114));
+        assert_eq!(pm.tick().remaining, Duration::from_secs(21));
+        assert!(pm.take_sounds().is_empty());
+
+        clock.advance(Duration::from_secs(1));
+        pm.tick();
+        clock.advance(Duration::from_secs(1));
+        pm.tick();
+        assert_eq!(pm.take_sounds(), vec![MatchSound::Endgame]);
+    }
+
+    #[test]
+    fn test_short_teleop_skips_endgame() {
+        let mut pm = classic(PracticeTiming {
+            teleop_secs: 10,
+            ..fast_timing()
+        });
+        pm.start();
+        for _ in 0..5 {
+            pm.tick();
+        }
+        assert_eq!(pm.phase(), PracticePhase::Teleop);
+        assert!(!pm.take_sounds().contains(&MatchSound::Endgame));
+    }
+
+    #[test]
+    fn test_abort_sound() {
+        let mut pm = classic(PracticeTiming {
+            countdown_secs: 0,
+            ..PracticeTiming::default()
+        });
+        pm.start();
+        pm.tick();
+        pm.tick();
+        assert_eq!(pm.take_sounds(), vec![MatchSound::StartAuto]);
+        pm.stop();
+        assert_eq!(pm.take_sounds(), vec![MatchSound::Abort]);
+
+        // Stopping during the countdown is not an abort
+        let mut pm = classic(PracticeTiming::default());
+        pm.start();
+        pm.tick();
+        pm.stop();
+        assert!(pm.take_sounds().is_empty());
+    }
+
+    #[test]
+    fn test_custom_schedule() {
+        // Two auto runs with a pause between them, then a test mode window
+        let mut pm = PracticeMode::new(MatchSchedule {
+            phases: vec![
+                phase("Run 1", ScheduleMode::Autonomous, 0, true),
+                phase("Reset", ScheduleMode::Autonomous, 0, false),
+                phase("Run 2", ScheduleMode::Autonomous, 0, true),
+                phase("Checks", ScheduleMode::Test, 0, true),
+            ],
+        });
+        pm.start();
+        assert_eq!(pm.label(), "Run 1");
+
+        let mut seen = Vec::new();
+        for _ in 0..10 {
+            let tick = pm.tick();
+            seen.push((pm.label(), tick.phase, tick.mode, tick.should_enable));
+            if tick.phase == PracticePhase::Done {
+                break;
+            }
+        }
+        assert_eq!(
+            seen,
+            vec![
+                (
+                    "Run 1".to_string(),
+                    PracticePhase::Autonomous,
+                    Some(Mode::Autonomous),
+                    true
+                ),
+                (
+                    "Reset".to_string(),
+                    PracticePhase::Delay,
+                    Some(Mode::Autonomous),
+                    false
+                ),
+                (
+                    "Run 2".to_string(),
+                    PracticePhase::Autonomous,
+                    Some(Mode::Autonomous),
+                    true
+                ),
+                (
+                    "Checks".to_string(),
+                    PracticePhase::Test,
+                    Some(Mode::Test),
+                    true
+                ),
+                ("Done".to_string(), PracticePhase::Done, None, false),
+            ]
+        );
+        assert_eq!(
+            pm.take_sounds(),
+            vec![
+                MatchSound::StartAuto,
+                MatchSound::EndAuto,
+                MatchSound::StartAuto,
+                MatchSound::EndAuto,
+                MatchSound::EndMatch,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_teleop_only_drill() {
+        let mut pm = PracticeMode::new(MatchSchedule {
+            phases: vec![phase("Drill", ScheduleMode::Teleop, 60, true)],
+        });
+        pm.start();
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Teleop);
+        assert_eq!(tick.mode, Some(Mode::Teleop));
+        assert!(tick.should_enable);
+        assert!(tick.remaining > Duration::from_secs(59));
+
+        // A-Stop only applies to autonomous
+        pm.a_stop();
+        assert!(!pm.tick().should_disable);
+    }
+
+    #[test]
+    fn test_empty_schedule_finishes_immediately() {
+        let mut pm = PracticeMode::new(MatchSchedule { phases: vec![] });
+        pm.start();
+        assert_eq!(pm.phase(), PracticePhase::Done);
+        assert!(!pm.is_running());
+    }
+
+    fn mock_classic() -> (PracticeMode, MockClock) {
+        let clock = MockClock::new();
+        let pm = PracticeMode::with_clock(
+            MatchSchedule::classic(&PracticeTiming::default()),
+            Arc::new(clock.clone()),
+        );
+        (pm, clock)
+    }
+
+    // Starts the match and runs through the 3 second countdown
+    fn start_auto(pm: &mut PracticeMode, clock: &MockClock) {
+        pm.start();
+        pm.tick();
+        clock.advance(Duration::from_secs(3));
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Autonomous);
+        assert!(tick.should_enable);
+    }
+
+    #[test]
+    fn test_pause_freezes_clock() {
+        let (mut pm, clock) = mock_classic();
+        start_auto(&mut pm, &clock);
+
+        clock.advance(Duration::from_secs(5));
+        pm.pause();
+        let tick = pm.tick();
+        assert!(tick.should_disable);
+        assert_eq!(tick.remaining, Duration::from_secs(10));
+
+        clock.advance(Duration::from_secs(60));
+        let tick = pm.tick();
+        assert!(pm.is_paused());
+        assert_eq!(tick.phase, PracticePhase::Autonomous);
+        assert_eq!(tick.remaining, Duration::from_secs(10));
+        assert!(!tick.should_enable && !tick.should_disable);
+
+        pm.resume();
+        let tick = pm.tick();
+        assert!(tick.should_enable);
+        assert_eq!(tick.remaining, Duration::from_secs(10));
+        assert!(!pm.tick().should_enable);
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(pm.tick().phase, PracticePhase::Delay);
+    }
+
+    #[test]
+    fn test_resume_keeps_a_stop() {
+        let (mut pm, clock) = mock_classic();
+        start_auto(&mut pm, &clock);
+        pm.a_stop();
+        assert!(pm.tick().should_disable);
+
+        pm.pause();
+        pm.tick();
+        pm.resume();
+        assert!(!pm.tick().should_enable);
+    }
+
+    #[test]
+    fn test_skip_to_teleop() {
+        let (mut pm, clock) = mock_classic();
+        start_auto(&mut pm, &clock);
+        pm.take_sounds();
+
+        clock.advance(Duration::from_secs(4));
+        assert!(pm.skip(SkipTarget::Teleop));
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Teleop);
+        assert_eq!(tick.mode, Some(Mode::Teleop));
+        assert!(tick.should_enable);
+        assert_eq!(tick.remaining, Duration::from_secs(135));
+        assert_eq!(
+            pm.take_sounds(),
+            vec![MatchSound::EndAuto, MatchSound::StartTeleop]
+        );
+
+        // Already there
+        assert!(!pm.skip(SkipTarget::Teleop));
+    }
+
+    #[test]
+    fn test_skip_to_endgame() {
+        let (mut pm, clock) = mock_classic();
+        start_auto(&mut pm, &clock);
+        pm.take_sounds();
+
+        assert!(pm.skip(SkipTarget::Endgame));
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Teleop);
+        assert_eq!(tick.remaining, Duration::from_secs(20));
+        assert_eq!(
+            pm.take_sounds(),
+            vec![
+                MatchSound::EndAuto,
+                MatchSound::StartTeleop,
+                MatchSound::Endgame
+            ]
+        );
+
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(pm.tick().phase, PracticePhase::Done);
+    }
+
+    #[test]
+    fn test_skip_while_paused() {
+        let (mut pm, clock) = mock_classic();
+        start_auto(&mut pm, &clock);
+        pm.pause();
+        pm.tick();
+
+        assert!(pm.skip(SkipTarget::Teleop));
+        clock.advance(Duration::from_secs(30));
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Teleop);
+        assert!(!tick.should_enable);
+        assert_eq!(tick.remaining, Duration::from_secs(135));
+
+        pm.resume();
+        assert!(pm.tick().should_enable);
+    }
+
+    #[test]
+    fn test_controls_need_a_running_match() {
+        let (mut pm, _clock) = mock_classic();
+        assert!(!pm.skip(SkipTarget::Teleop));
+        pm.pause();
+        assert!(!pm.is_paused());
+
+        pm.start();
+        pm.pause();
+        pm.stop();
+        assert!(!pm.is_paused());
+    }
+
+    #[test]
+    fn test_classic_phase_lengths() {
+        let (mut pm, clock) = mock_classic();
+        start_auto(&mut pm, &clock);
+
+        clock.advance(Duration::from_millis(14_999));
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Autonomous);
+        assert_eq!(tick.remaining, Duration::from_millis(1));
+        clock.advance(Duration::from_millis(1));
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Delay);
+        assert!(tick.should_disable);
+
+        clock.advance(Duration::from_secs(1));
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Teleop);
+        assert!(tick.should_enable);
+        assert_eq!(tick.remaining, Duration::from_secs(135));
+
+        clock.advance(Duration::from_millis(134_999));
+        assert_eq!(pm.tick().phase, PracticePhase::Teleop);
+        clock.advance(Duration::from_millis(1));
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Done);
+        assert!(tick.should_disable);
+        assert!(!pm.is_running());
+    }
+
+    #[test]
+    fn test_a_stop_reenables_in_teleop() {
+        let (mut pm, clock) = mock_classic();
+        start_auto(&mut pm, &clock);
+
+        clock.advance(Duration::from_secs(5));
+        pm.a_stop();
+        assert!(pm.tick().should_disable);
+        clock.advance(Duration::from_secs(5));
+        let tick = pm.tick();
+        assert!(tick.should_disable && !tick.should_enable);
+
+        // Auto keeps its full length after an A-Stop
+        clock.advance(Duration::from_secs(5));
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Delay);
+        assert!(!tick.should_enable);
+
+        clock.advance(Duration::from_secs(1));
+        let tick = pm.tick();
+        assert_eq!(tick.phase, PracticePhase::Teleop);
+        assert!(tick.should_enable);
+        assert!(!tick.should_disable);
+    }
 }
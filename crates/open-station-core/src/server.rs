@@ -0,0 +1,411 @@
+use crate::config::ServerConfig;
+use crate::state::{TelemetrySerialized, UiState};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use open_station_protocol::types::{RobotState, TcpMessage};
+use serde_json::{json, Value};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::time::{interval, Duration};
+
+// State and telemetry change every poll, clients get them at most this often
+const STREAM_INTERVAL: Duration = Duration::from_millis(100);
+
+// The only things a remote client may ask for. Enabling stays on the driving
+// laptop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerCommand {
+    Disable,
+    Estop,
+}
+
+// What the server reads from, AppState holds the other ends
+#[derive(Clone)]
+pub struct ServerSources {
+    pub ui_state: watch::Receiver<UiState>,
+    pub robot_state: watch::Receiver<RobotState>,
+    pub messages: broadcast::Sender<TcpMessage>,
+    pub commands: mpsc::UnboundedSender<ServerCommand>,
+}
+
+#[derive(Clone)]
+struct Shared {
+    sources: ServerSources,
+    token: Option<Arc<str>>,
+}
+
+pub fn router(token: Option<String>, sources: ServerSources) -> Router {
+    let shared = Shared {
+        sources,
+        token: token.filter(|t| !t.is_empty()).map(Arc::from),
+    };
+    Router::new()
+        .route("/api/state", get(get_state))
+        .route("/api/telemetry", get(get_telemetry))
+        .route("/api/ws", get(open_stream))
+        .route("/api/disable", post(disable))
+        .route("/api/estop", post(estop))
+        .with_state(shared)
+}
+
+pub async fn run(config: ServerConfig, sources: ServerSources) {
+    let addr = SocketAddr::new(config.bind_address, config.port);
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!("Failed to bind API server {}: {}", addr, e);
+            return;
+        }
+    };
+    if config.token.as_deref().unwrap_or_default().is_empty() {
+        log::info!("API server on http://{}, control disabled", addr);
+    } else {
+        log::info!("API server on http://{}", addr);
+    }
+
+    if let Err(e) = axum::serve(listener, router(config.token, sources)).await {
+        log::error!("API server stopped: {}", e);
+    }
+}
+
+async fn get_state(State(shared): State<Shared>) -> Json<UiState> {
+    Json(shared.sources.ui_state.borrow().clone())
+}
+
+async fn get_telemetry(State(shared): State<Shared>) -> Json<TelemetrySerialized> {
+    Json(TelemetrySerialized::from(
+        &shared.sources.robot_state.borrow().telemetry,
+    ))
+}
+
+async fn disable(State(shared): State<Shared>, headers: HeaderMap) -> StatusCode {
+    command(&shared, &headers, ServerCommand::Disable)
+}
+
+async fn estop(State(shared): State<Shared>, headers: HeaderMap) -> StatusCode {
+    command(&shared, &headers, ServerCommand::Estop)
+}
+
+fn command(shared: &Shared, headers: &HeaderMap, command: ServerCommand) -> StatusCode {
+    let Some(token) = &shared.token else {
+        return StatusCode::FORBIDDEN;
+    };
+    let given = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !given.is_some_and(|given| token_matches(given, token)) {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    log::info!("API request: {:?}", command);
+    match shared.sources.commands.send(command) {
+        Ok(()) => StatusCode::NO_CONTENT,
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+// Compares every byte so the time taken says nothing about the token
+fn token_matches(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+// Browsers send an Origin with WebSocket upgrades but apply no CORS to them,
+// so any page open on this laptop could otherwise read the robot's output
+async fn open_stream(
+    State(shared): State<Shared>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let origin = headers
+        .get(header::ORIGIN)
+        .map(|value| value.to_str().unwrap_or_default());
+    if !origin.is_none_or(origin_is_local) {
+        log::warn!("Refused API stream from origin {:?}", origin);
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    upgrade.on_upgrade(move |socket| stream(socket, shared.sources))
+}
+
+// e.g. http://localhost:5173, http://127.0.0.1 or http://[::1]:8080
+fn origin_is_local(origin: &str) -> bool {
+    let Some((_, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    let host = match authority.strip_prefix('[') {
+        Some(v6) => v6.split_once(']').map_or(v6, |(host, _)| host),
+        None => authority.split(':').next().unwrap_or_default(),
+    }
+    .to_ascii_lowercase();
+    host == "localhost"
+        || host.ends_with(".localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+// Same shapes the desktop frontend gets as events
+pub fn message_json(message: &TcpMessage) -> Value {
+    match message {
+        TcpMessage::Message(text) => json!({"type": "message", "text": text}),
+        TcpMessage::Stdout(text) => json!({"type": "stdout", "text": text}),
+        TcpMessage::ErrorReport {
+            details,
+            location,
+            is_error,
+            ..
+        } => json!({
+            "type": if *is_error { "error" } else { "warning" },
+            "details": details,
+            "location": location,
+        }),
+        TcpMessage::VersionInfo { name, version, .. } => {
+            json!({"type": "version", "name": name, "version": version})
+        }
+    }
+}
+
+async fn stream(mut socket: WebSocket, sources: ServerSources) {
+    let mut ui_state = sources.ui_state;
+    let mut robot_state = sources.robot_state;
+    let mut messages = sources.messages.subscribe();
+    let mut ticker = interval(STREAM_INTERVAL);
+    // New clients start with a full picture
+    ui_state.mark_changed();
+    robot_state.mark_changed();
+
+    loop {
+        let mut frames = Vec::new();
+        tokio::select! {
+            _ = ticker.tick() => {
+                if ui_state.has_changed().unwrap_or(false) {
+                    let state = ui_state.borrow_and_update().clone();
+                    frames.push(json!({"type": "state", "data": state}));
+                }
+                if robot_state.has_changed().unwrap_or(false) {
+                    let telemetry = TelemetrySerialized::from(&robot_state.borrow_and_update().telemetry);
+                    frames.push(json!({"type": "telemetry", "data": telemetry}));
+                }
+            }
+
+            message = messages.recv() => match message {
+                Ok(message) => frames.push(message_json(&message)),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("API client fell behind, skipped {} messages", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+
+            // Clients only listen, anything they send besides a close is ignored
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+
+        for frame in frames {
+            if socket
+                .send(Message::Text(frame.to_string().into()))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use open_station_protocol::types::{BatteryVoltage, StatusFlags, TelemetryData};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio::time::timeout;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+    struct Harness {
+        addr: SocketAddr,
+        ui_state: watch::Sender<UiState>,
+        _robot_state: watch::Sender<RobotState>,
+        messages: broadcast::Sender<TcpMessage>,
+        commands: mpsc::UnboundedReceiver<ServerCommand>,
+    }
+
+    async fn start(token: Option<&str>) -> Harness {
+        let (ui_state, ui_state_rx) = watch::channel(UiState {
+            team_number: 1234,
+            ..Default::default()
+        });
+        let (robot_state, robot_state_rx) = watch::channel(RobotState {
+            connected: false,
+            code_running: false,
+            voltage: BatteryVoltage { volts: 0.0 },
+            status: StatusFlags::from_byte(0),
+            telemetry: TelemetryData::default(),
+            sequence: 0,
+            trip_time_ms: 0.0,
+            lost_packets: 0,
+        });
+        let (messages, _) = broadcast::channel(16);
+        let (commands_tx, commands) = mpsc::unbounded_channel();
+        let sources = ServerSources {
+            ui_state: ui_state_rx,
+            robot_state: robot_state_rx,
+            messages: messages.clone(),
+            commands: commands_tx,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(token.map(String::from), sources);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        Harness {
+            addr,
+            ui_state,
+            _robot_state: robot_state,
+            messages,
+            commands,
+        }
+    }
+
+    // Bare HTTP/1.1 so the tests need no client library
+    async fn request(addr: SocketAddr, request_line: &str, token: Option<&str>) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let auth = token
+            .map(|t| format!("Authorization: Bearer {t}\r\n"))
+            .unwrap_or_default();
+        let request = format!(
+            "{request_line} HTTP/1.1\r\nHost: localhost\r\n{auth}Content-Length: 0\r\nConnection: close\r\n\r\n"
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = response
+            .split_once("\r\n\r\n")
+            .map(|(_, body)| body.to_string())
+            .unwrap_or_default();
+        (status, body)
+    }
+
+    async fn next_frame(ws: &mut WebSocketStream<MaybeTlsStream<TcpStream>>) -> Value {
+        let message = timeout(Duration::from_secs(5), ws.next())
+            .await
+            .expect("no frame from the server")
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(message.to_text().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rest_endpoints_are_read_only() {
+        let server = start(None).await;
+
+        let (status, body) = request(server.addr, "GET /api/state", None).await;
+        assert_eq!(status, 200);
+        let state: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(state["team_number"], 1234);
+        assert_eq!(state["enabled"], false);
+
+        let (status, body) = request(server.addr, "GET /api/telemetry", None).await;
+        assert_eq!(status, 200);
+        assert!(body.contains("\"can_utilization\""));
+
+        // No token configured, no control at all
+        let (status, _) = request(server.addr, "POST /api/estop", Some("")).await;
+        assert_eq!(status, 403);
+    }
+
+    #[tokio::test]
+    async fn test_control_needs_token_and_never_enables() {
+        let mut server = start(Some("pit-secret")).await;
+
+        let (status, _) = request(server.addr, "POST /api/estop", None).await;
+        assert_eq!(status, 401);
+        let (status, _) = request(server.addr, "POST /api/estop", Some("pit-secreT")).await;
+        assert_eq!(status, 401);
+        assert!(server.commands.try_recv().is_err());
+
+        let (status, _) = request(server.addr, "POST /api/estop", Some("pit-secret")).await;
+        assert_eq!(status, 204);
+        let (status, _) = request(server.addr, "POST /api/disable", Some("pit-secret")).await;
+        assert_eq!(status, 204);
+        assert_eq!(server.commands.try_recv(), Ok(ServerCommand::Estop));
+        assert_eq!(server.commands.try_recv(), Ok(ServerCommand::Disable));
+
+        let (status, _) = request(server.addr, "POST /api/enable", Some("pit-secret")).await;
+        assert_eq!(status, 404);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_refuses_foreign_origin() {
+        let server = start(None).await;
+        let url = format!("ws://{}/api/ws", server.addr);
+
+        let mut request = url.as_str().into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert("Origin", "https://example.com".parse().unwrap());
+        match tokio_tungstenite::connect_async(request).await {
+            Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+                assert_eq!(response.status(), 403)
+            }
+            other => panic!("foreign origin was not refused: {:?}", other.map(|_| ())),
+        }
+
+        let mut request = url.as_str().into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert("Origin", "http://localhost:5173".parse().unwrap());
+        let (mut ws, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+        next_frame(&mut ws).await;
+
+        assert!(origin_is_local("http://127.0.0.1:8080"));
+        assert!(origin_is_local("http://[::1]"));
+        assert!(origin_is_local("http://tauri.localhost"));
+        assert!(!origin_is_local("http://localhost.example.com"));
+        assert!(!origin_is_local("null"));
+    }
+
+    #[tokio::test]
+    async fn test_websocket_streams_state_and_messages() {
+        let server = start(None).await;
+        let url = format!("ws://{}/api/ws", server.addr);
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        let mut types = vec![
+            next_frame(&mut ws).await["type"].clone(),
+            next_frame(&mut ws).await["type"].clone(),
+        ];
+        types.sort_by_key(|t| t.to_string());
+        assert_eq!(types, vec![json!("state"), json!("telemetry")]);
+
+        server.ui_state.send_modify(|s| s.voltage = 12.5);
+        let frame = next_frame(&mut ws).await;
+        assert_eq!(frame["type"], "state");
+        assert_eq!(frame["data"]["voltage"], 12.5);
+
+        server
+            .messages
+            .send(TcpMessage::Stdout("hello".into()))
+            .unwrap();
+        assert_eq!(
+            next_frame(&mut ws).await,
+            json!({"type": "stdout", "text": "hello"})
+        );
+    }
+}
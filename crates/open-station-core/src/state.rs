@@ -1,11 +1,19 @@
-use crate::config::Config;
-use crate::hotkeys::HotkeyManager;
-use crate::input::JoystickManager;
-use crate::practice::PracticeMode;
+#[cfg(feature = "audio")]
+use crate::audio::SoundPlayer;
+use crate::config::{Config, MatchInfoConfig, MatchSchedule, NetworkConfig};
+use crate::dslog::{self, LogContext};
+use crate::hotkeys::{HotkeyAction, HotkeyError, HotkeyManager, HotkeyTest, Keymap};
+use crate::input::{JoystickEvent, JoystickManager};
+use crate::practice::{MatchSound, PracticeMode, PracticePhase, PracticeTick, SkipTarget};
+#[cfg(feature = "server")]
+use crate::server::{self, ServerCommand, ServerSources};
+use open_station_protocol::connection::ConnectionStatus;
 use open_station_protocol::driver_station::{DriverStation, DsReceiver};
+use open_station_protocol::packet::fms::FmsControl;
 use open_station_protocol::types::*;
 use serde::Serialize;
-use tokio::sync::watch;
+use tokio::runtime::Runtime;
+use tokio::sync::{broadcast, mpsc, watch};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct UiState {
@@ -20,13 +28,28 @@ pub struct UiState {
     // Joysticks
     pub joysticks: Vec<JoystickInfoSerialized>,
     pub any_joystick_connected: bool,
+    // Set after a joystick dropped out while enabled, until acknowledged
+    pub joystick_lockout: Option<String>,
+    // Hotkeys are reported here instead of acting while testing
+    pub hotkey_test_mode: bool,
+    pub hotkey_test: Option<HotkeyTestSerialized>,
     // Practice
     pub practice_phase: String,
     pub practice_elapsed_secs: f64,
     pub practice_remaining_secs: f64,
+    pub practice_paused: bool,
+    // As the robot sees it
+    pub match_name: String,
     // Connection
+    pub connection_state: String,
+    pub udp_connected: bool,
+    pub tcp_connected: bool,
     pub trip_time_ms: f64,
     pub lost_packets: u32,
+    // Set while a field has taken over enable, mode and alliance
+    pub fms_connected: bool,
+    // Telemetry
+    pub telemetry: TelemetrySerialized,
     // Meta
     pub team_number: u32,
     pub alliance_color: String,
@@ -48,6 +71,50 @@ pub struct JoystickInfoSerialized {
     pub povs: Vec<i16>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyTestSerialized {
+    pub keys: String,
+    pub action: Option<String>,
+}
+
+impl From<&HotkeyTest> for HotkeyTestSerialized {
+    fn from(t: &HotkeyTest) -> Self {
+        Self {
+            keys: t.keys.to_string(),
+            action: t.action.map(|a| a.name().to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TelemetrySerialized {
+    pub can_utilization: f32,
+    pub can_bus_off_count: u32,
+    pub can_tx_full_count: u32,
+    pub can_rx_error_count: u8,
+    pub can_tx_error_count: u8,
+    pub pdp_currents: Vec<f32>,
+    pub cpu_usage: Vec<f32>,
+    pub ram_usage: u32,
+    pub disk_free: u32,
+}
+
+impl From<&TelemetryData> for TelemetrySerialized {
+    fn from(t: &TelemetryData) -> Self {
+        Self {
+            can_utilization: t.can.utilization,
+            can_bus_off_count: t.can.bus_off_count,
+            can_tx_full_count: t.can.tx_full_count,
+            can_rx_error_count: t.can.rx_error_count,
+            can_tx_error_count: t.can.tx_error_count,
+            pdp_currents: t.pdp_currents.clone(),
+            cpu_usage: t.cpu_usage.clone(),
+            ram_usage: t.ram_usage,
+            disk_free: t.disk_free,
+        }
+    }
+}
+
 impl Default for UiState {
     fn default() -> Self {
         Self {
@@ -60,11 +127,21 @@ impl Default for UiState {
             mode: "Teleoperated".to_string(),
             joysticks: Vec::new(),
             any_joystick_connected: false,
+            joystick_lockout: None,
+            hotkey_test_mode: false,
+            hotkey_test: None,
             practice_phase: "Idle".to_string(),
             practice_elapsed_secs: 0.0,
             practice_remaining_secs: 0.0,
+            practice_paused: false,
+            match_name: String::new(),
+            connection_state: "Disconnected".to_string(),
+            udp_connected: false,
+            tcp_connected: false,
             trip_time_ms: 0.0,
             lost_packets: 0,
+            fms_connected: false,
+            telemetry: TelemetrySerialized::default(),
             team_number: 0,
             alliance_color: "Red".to_string(),
             alliance_station: 1,
@@ -74,59 +151,195 @@ impl Default for UiState {
 
 pub struct AppState {
     ds: DriverStation,
-    #[allow(dead_code)]
-    ds_rx: Option<DsReceiver>,
+    robot_state: watch::Receiver<RobotState>,
+    connection: watch::Receiver<ConnectionStatus>,
+    fms: watch::Receiver<Option<FmsControl>>,
+    fms_attached: bool,
+    // Only set when AppState was created outside of a Tokio runtime
+    runtime: Option<Runtime>,
     pub joysticks: JoystickManager,
     practice: PracticeMode,
-    #[allow(dead_code)]
+    practice_tick: Option<PracticeTick>,
+    // Number of the last practice match started this session
+    practice_match: u16,
+    #[cfg(feature = "audio")]
+    sound_player: Option<SoundPlayer>,
     hotkeys: HotkeyManager,
     config: Config,
 
     mode: Mode,
     alliance: Alliance,
     enabled: bool,
+    joystick_lockout: Option<String>,
+    last_hotkey_test: Option<HotkeyTest>,
 
     ui_state_tx: watch::Sender<UiState>,
     ui_state_rx: watch::Receiver<UiState>,
 
     #[allow(dead_code)]
-    stdout_tx: tokio::sync::mpsc::UnboundedSender<String>,
-    stdout_rx: Option<tokio::sync::mpsc::UnboundedReceiver<String>>,
+    stdout_tx: mpsc::UnboundedSender<String>,
+    stdout_rx: Option<mpsc::UnboundedReceiver<String>>,
 
-    #[allow(dead_code)]
-    message_tx: tokio::sync::mpsc::UnboundedSender<TcpMessage>,
-    message_rx: Option<tokio::sync::mpsc::UnboundedReceiver<TcpMessage>>,
+    message_tx: mpsc::UnboundedSender<TcpMessage>,
+    message_rx: Option<mpsc::UnboundedReceiver<TcpMessage>>,
+    // Stdout and messages for any number of listeners
+    message_broadcast: broadcast::Sender<TcpMessage>,
+    #[cfg(feature = "server")]
+    server_commands: mpsc::UnboundedReceiver<ServerCommand>,
+    log_context: watch::Sender<LogContext>,
+
+    sound_tx: mpsc::UnboundedSender<MatchSound>,
+    sound_rx: Option<mpsc::UnboundedReceiver<MatchSound>>,
 }
 
 impl AppState {
     pub fn new(config: Config) -> Self {
+        Self::build(config, true)
+    }
+
+    // Skips the global hotkey listener, which would otherwise fire E-Stop and
+    // Disable on keys typed into a terminal
+    pub fn new_headless(config: Config) -> Self {
+        Self::build(config, false)
+    }
+
+    fn build(config: Config, listen_for_hotkeys: bool) -> Self {
         let alliance = Alliance::new(AllianceColor::Red, 1);
-        let (ds, ds_rx) = DriverStation::new(config.team_number, alliance);
-        let joysticks = JoystickManager::new(config.joystick_locks.clone());
-        let practice = PracticeMode::new(config.practice_timing.clone());
+        let (mut ds, ds_rx) = DriverStation::new(config.team_number, alliance);
+        ds.set_usb_mode(config.use_usb);
+        ds.set_target_override(config.network.target_address);
+        ds.set_ports(config.network.ports());
+        if config.fms.enabled {
+            ds.set_fms_ports(Some(config.fms.ports()));
+        }
+        // Both are replayed on every TCP connect, so set them up front
+        ds.set_game_data(config.game_data.clone());
+        ds.set_match_info((&config.match_info).into());
+        let mut joysticks = JoystickManager::new(config.joystick_locks.clone());
+        // Devices present at startup are not news
+        joysticks.take_events();
+        let practice = PracticeMode::new(config.match_schedule());
         let mut hotkeys = HotkeyManager::new();
-        hotkeys.start();
+        match Keymap::from_config(&config.hotkeys) {
+            Ok(keymap) => hotkeys.set_keymap(keymap),
+            Err(e) => log::warn!("Invalid hotkey config, using defaults: {}", e),
+        }
+        if listen_for_hotkeys {
+            hotkeys.start();
+        }
 
         let (ui_state_tx, ui_state_rx) = watch::channel(UiState::default());
-        let (stdout_tx, stdout_rx) = tokio::sync::mpsc::unbounded_channel();
-        let (message_tx, message_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (stdout_tx, stdout_rx) = mpsc::unbounded_channel();
+        let (message_tx, message_rx) = mpsc::unbounded_channel();
+        let (sound_tx, sound_rx) = mpsc::unbounded_channel();
+        let (message_broadcast, _) = broadcast::channel(256);
+        let (log_context, log_context_rx) = watch::channel(LogContext::default());
+        #[cfg(feature = "server")]
+        let (server_tx, server_commands) = mpsc::unbounded_channel();
+
+        let runtime = match tokio::runtime::Handle::try_current() {
+            Ok(_) => None,
+            Err(_) => Some(
+                tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(2)
+                    .thread_name("open-station-ds")
+                    .enable_all()
+                    .build()
+                    .expect("Failed to build Tokio runtime"),
+            ),
+        };
+
+        let DsReceiver {
+            state: robot_state,
+            connection,
+            fms,
+            stdout: mut ds_stdout,
+            messages: mut ds_messages,
+            packets,
+        } = ds_rx;
+
+        {
+            let _guard = runtime.as_ref().map(|rt| rt.enter());
+
+            ds.start();
+
+            let tx = stdout_tx.clone();
+            let broadcast = message_broadcast.clone();
+            tokio::spawn(async move {
+                while let Some(line) = ds_stdout.recv().await {
+                    let _ = broadcast.send(TcpMessage::Stdout(line.clone()));
+                    if tx.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let tx = message_tx.clone();
+            let broadcast = message_broadcast.clone();
+            tokio::spawn(async move {
+                while let Some(msg) = ds_messages.recv().await {
+                    let _ = broadcast.send(msg.clone());
+                    if tx.send(msg).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            if config.logging.enabled {
+                tokio::spawn(dslog::run(
+                    config.logging.clone(),
+                    packets,
+                    message_broadcast.subscribe(),
+                    log_context_rx,
+                ));
+            }
+
+            #[cfg(feature = "server")]
+            if config.server.enabled {
+                tokio::spawn(server::run(
+                    config.server.clone(),
+                    ServerSources {
+                        ui_state: ui_state_rx.clone(),
+                        robot_state: robot_state.clone(),
+                        messages: message_broadcast.clone(),
+                        commands: server_tx,
+                    },
+                ));
+            }
+        }
 
         let app_state = Self {
             ds,
-            ds_rx: Some(ds_rx),
+            robot_state,
+            connection,
+            fms,
+            fms_attached: false,
+            runtime,
             joysticks,
             practice,
+            practice_tick: None,
+            practice_match: 0,
+            #[cfg(feature = "audio")]
+            sound_player: None,
             hotkeys,
             config,
             mode: Mode::Teleop,
             alliance,
             enabled: false,
+            joystick_lockout: None,
+            last_hotkey_test: None,
             ui_state_tx,
             ui_state_rx,
             stdout_tx,
             stdout_rx: Some(stdout_rx),
             message_tx,
             message_rx: Some(message_rx),
+            message_broadcast,
+            #[cfg(feature = "server")]
+            server_commands,
+            log_context,
+            sound_tx,
+            sound_rx: Some(sound_rx),
         };
 
         app_state.update_ui_state();
@@ -137,15 +350,46 @@ impl AppState {
         self.ui_state_rx.clone()
     }
 
-    pub fn take_stdout_rx(&mut self) -> Option<tokio::sync::mpsc::UnboundedReceiver<String>> {
+    pub fn take_stdout_rx(&mut self) -> Option<mpsc::UnboundedReceiver<String>> {
         self.stdout_rx.take()
     }
 
-    pub fn take_message_rx(&mut self) -> Option<tokio::sync::mpsc::UnboundedReceiver<TcpMessage>> {
+    pub fn take_message_rx(&mut self) -> Option<mpsc::UnboundedReceiver<TcpMessage>> {
         self.message_rx.take()
     }
 
+    // Robot stdout and messages, alongside the single consumer of the
+    // stdout and message receivers
+    pub fn subscribe_messages(&self) -> broadcast::Receiver<TcpMessage> {
+        self.message_broadcast.subscribe()
+    }
+
+    // Every match cue, whether or not practice audio is switched on
+    pub fn take_sound_rx(&mut self) -> Option<mpsc::UnboundedReceiver<MatchSound>> {
+        self.sound_rx.take()
+    }
+
+    pub fn robot_state(&self) -> RobotState {
+        self.robot_state.borrow().clone()
+    }
+
+    pub fn connection_status(&self) -> ConnectionStatus {
+        *self.connection.borrow()
+    }
+
     pub fn enable(&mut self) {
+        if let Some(reason) = &self.joystick_lockout {
+            log::warn!("Refusing to enable until acknowledged: {}", reason);
+            return;
+        }
+        if self.hotkeys.is_testing() {
+            log::warn!("Refusing to enable while testing hotkeys");
+            return;
+        }
+        if self.fms_attached {
+            log::warn!("Refusing to enable while the FMS is in control");
+            return;
+        }
         self.ds.enable();
         self.enabled = true;
         self.update_ui_state();
@@ -186,6 +430,22 @@ impl AppState {
         self.ds.set_game_data(data);
     }
 
+    // Practice matches report their own number until stopped
+    pub fn set_match_info(&mut self, info: MatchInfoConfig) {
+        self.config.match_info = info;
+        if !self.practice.is_running() {
+            self.ds.set_match_info((&self.config.match_info).into());
+        }
+        self.update_ui_state();
+    }
+
+    // The running link reconnects with the new address and ports
+    pub fn set_network(&mut self, network: NetworkConfig) {
+        self.ds.set_target_override(network.target_address);
+        self.ds.set_ports(network.ports());
+        self.config.network = network;
+    }
+
     pub fn set_usb_mode(&mut self, usb: bool) {
         self.config.use_usb = usb;
         self.ds.set_usb_mode(usb);
@@ -200,49 +460,285 @@ impl AppState {
     }
 
     pub fn start_practice(&mut self) {
+        if self.fms_attached {
+            log::warn!("Refusing to start practice while the FMS is in control");
+            return;
+        }
         self.practice.start();
+        self.practice_tick = None;
+        self.practice_match = self.practice_match.wrapping_add(1);
+        self.ds.set_match_info(MatchInfo {
+            event_name: self.config.match_info.event_name.clone(),
+            match_type: MatchType::Practice,
+            match_number: self.practice_match,
+            replay_number: 1,
+        });
+        self.tick_practice();
     }
 
     pub fn stop_practice(&mut self) {
         self.practice.stop();
+        self.practice_tick = None;
+        self.ds.set_match_time(None);
+        self.ds.set_match_info((&self.config.match_info).into());
+        self.emit_sounds();
         self.disable();
     }
 
+    pub fn pause_practice(&mut self) {
+        self.practice.pause();
+        self.tick_practice();
+        self.update_ui_state();
+    }
+
+    pub fn resume_practice(&mut self) {
+        self.practice.resume();
+        self.tick_practice();
+        self.update_ui_state();
+    }
+
+    // False when no match is running or the schedule has no such period left
+    pub fn skip_practice(&mut self, target: SkipTarget) -> bool {
+        if !self.practice.skip(target) {
+            return false;
+        }
+        self.tick_practice();
+        self.update_ui_state();
+        true
+    }
+
     pub fn a_stop(&mut self) {
         self.practice.a_stop();
         self.disable();
     }
 
+    pub fn set_practice_audio(&mut self, enabled: bool) {
+        self.config.practice_audio = enabled;
+    }
+
     pub fn set_practice_timing(&mut self, timing: crate::config::PracticeTiming) {
-        self.config.practice_timing = timing.clone();
-        self.practice.set_timing(timing);
+        self.config.practice_timing = timing;
+        self.practice.set_schedule(self.config.match_schedule());
+    }
+
+    // None goes back to the classic match built from the practice timing
+    pub fn set_match_schedule(&mut self, schedule: Option<MatchSchedule>) {
+        self.config.match_schedule = schedule;
+        self.practice.set_schedule(self.config.match_schedule());
     }
 
     pub fn reorder_joysticks(&mut self, order: Vec<String>) {
         self.joysticks.reorder(order);
+        self.push_joysticks();
         self.update_ui_state();
     }
 
     pub fn lock_joystick(&mut self, uuid: String, slot: u8) {
         self.joysticks.lock(&uuid, slot);
+        self.push_joysticks();
         self.update_ui_state();
     }
 
     pub fn unlock_joystick(&mut self, uuid: String) {
         self.joysticks.unlock(&uuid);
+        self.push_joysticks();
         self.update_ui_state();
     }
 
     pub fn rescan_joysticks(&mut self) {
         self.joysticks.rescan();
+        self.push_joysticks();
         self.update_ui_state();
     }
 
     pub fn poll(&mut self) {
+        while let Some(action) = self.hotkeys.try_next_action() {
+            self.dispatch_hotkey(action);
+        }
+        while let Some(test) = self.hotkeys.try_next_test() {
+            self.last_hotkey_test = Some(test);
+        }
+        #[cfg(feature = "server")]
+        while let Ok(command) = self.server_commands.try_recv() {
+            match command {
+                ServerCommand::Disable => self.disable(),
+                ServerCommand::Estop => self.estop(),
+            }
+        }
+        self.poll_fms();
+        self.tick_practice();
         self.joysticks.poll();
+        let events = self.joysticks.take_events();
+        self.handle_joystick_events(events);
+        self.push_joysticks();
         self.update_ui_state();
     }
 
+    // The field owns the match while attached. Practice stops and the local
+    // enable is dropped both ways so the robot never jumps back to a stale
+    // local state.
+    fn poll_fms(&mut self) {
+        let fms = *self.fms.borrow();
+        if fms.is_some() != self.fms_attached {
+            self.fms_attached = fms.is_some();
+            log::info!(
+                "FMS {}",
+                if self.fms_attached {
+                    "attached"
+                } else {
+                    "detached"
+                }
+            );
+            if self.practice.phase() != PracticePhase::Idle {
+                self.stop_practice();
+            }
+            self.disable();
+        }
+
+        let info = match fms {
+            Some(fms) => MatchInfo {
+                event_name: self.config.match_info.event_name.clone(),
+                match_type: fms.level,
+                match_number: fms.match_number,
+                replay_number: fms.replay_number,
+            },
+            None => (&self.config.match_info).into(),
+        };
+        if !self.practice.is_running() {
+            self.ds.set_match_info(info);
+        }
+    }
+
+    fn tick_practice(&mut self) {
+        if self.practice.phase() == PracticePhase::Idle {
+            return;
+        }
+
+        let tick = self.practice.tick();
+        // Disable before switching modes so the robot never sees the next
+        // mode enabled early
+        if tick.should_disable && self.enabled {
+            self.disable();
+        }
+        if let Some(mode) = tick.mode {
+            self.set_mode(mode);
+        }
+        if tick.should_enable {
+            self.enable();
+        }
+        let match_time = self
+            .practice
+            .is_running()
+            .then_some(tick.remaining.as_secs_f32());
+        self.ds.set_match_time(match_time);
+        self.practice_tick = Some(tick);
+        self.emit_sounds();
+    }
+
+    fn emit_sounds(&mut self) {
+        for sound in self.practice.take_sounds() {
+            log::debug!("Match sound: {:?}", sound);
+            #[cfg(feature = "audio")]
+            if self.config.practice_audio {
+                self.sound_player
+                    .get_or_insert_with(SoundPlayer::new)
+                    .play(sound);
+            }
+            let _ = self.sound_tx.send(sound);
+        }
+    }
+
+    fn dispatch_hotkey(&mut self, action: HotkeyAction) {
+        log::debug!("Hotkey: {:?}", action);
+        match action {
+            HotkeyAction::EStop => self.estop(),
+            HotkeyAction::Disable => self.disable(),
+            HotkeyAction::Enable => self.enable(),
+            HotkeyAction::AStop => self.a_stop(),
+            HotkeyAction::RescanJoysticks => self.rescan_joysticks(),
+        }
+    }
+
+    // Validates the whole keymap before applying it, so a rejected binding
+    // leaves the previous one active.
+    pub fn rebind_hotkey(
+        &mut self,
+        action: HotkeyAction,
+        binding: &str,
+    ) -> Result<(), HotkeyError> {
+        let mut hotkeys = self.config.hotkeys.clone();
+        hotkeys.set_binding(action, binding.to_string());
+        let keymap = Keymap::from_config(&hotkeys)?;
+
+        // Store the canonical spelling
+        hotkeys.set_binding(action, keymap.binding(action).to_string());
+        self.hotkeys.set_keymap(keymap);
+        self.config.hotkeys = hotkeys;
+        self.update_ui_state();
+        Ok(())
+    }
+
+    pub fn set_hotkey_test_mode(&mut self, testing: bool) {
+        if testing {
+            // Pressed keys are only reported, so the robot can't be stopped
+            // from the keyboard in the meantime
+            self.disable();
+        }
+        self.hotkeys.set_testing(testing);
+        self.last_hotkey_test = None;
+        self.update_ui_state();
+    }
+
+    pub fn acknowledge_joystick_loss(&mut self) {
+        if self.joystick_lockout.take().is_some() {
+            log::info!("Joystick loss acknowledged");
+            self.update_ui_state();
+        }
+    }
+
+    fn handle_joystick_events(&mut self, events: Vec<JoystickEvent>) {
+        for event in events {
+            match event {
+                JoystickEvent::Connected { slot, name, .. } => {
+                    log::info!("Joystick {} connected in slot {}", name, slot);
+                }
+                JoystickEvent::Disconnected { slot, name, .. } => {
+                    log::warn!("Joystick {} disconnected from slot {}", name, slot);
+                    if self.enabled {
+                        // The robot would otherwise keep acting on neutral input
+                        self.disable();
+                        let reason = format!(
+                            "Robot disabled: joystick {} ({}) disconnected while enabled",
+                            slot, name
+                        );
+                        self.report_warning(&reason);
+                        self.joystick_lockout = Some(reason);
+                    }
+                }
+            }
+        }
+    }
+
+    // Surfaces a DS-side problem next to robot messages, in the Messages tab
+    // as well as the logs and API streams
+    fn report_warning(&self, details: &str) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+        let message = TcpMessage::ErrorReport {
+            timestamp,
+            sequence: 0,
+            error_code: 0,
+            is_error: false,
+            details: details.to_string(),
+            location: "Driver Station".to_string(),
+            call_stack: String::new(),
+        };
+        let _ = self.message_broadcast.send(message.clone());
+        let _ = self.message_tx.send(message);
+    }
+
     pub fn launch_dashboard(&self) {
         if let Some(cmd) = &self.config.dashboard_command {
             let _ = std::process::Command::new("sh").arg("-c").arg(cmd).spawn();
@@ -281,46 +777,161 @@ impl AppState {
             })
             .collect();
 
-        let (alliance_color, alliance_station) = match self.alliance.color {
-            AllianceColor::Red => ("Red".to_string(), self.alliance.station),
-            AllianceColor::Blue => ("Blue".to_string(), self.alliance.station),
+        let (enabled, mode, alliance) = self.effective_control();
+        let (alliance_color, alliance_station) = match alliance.color {
+            AllianceColor::Red => ("Red".to_string(), alliance.station),
+            AllianceColor::Blue => ("Blue".to_string(), alliance.station),
         };
 
-        let practice_phase = format!("{:?}", self.practice.phase());
+        let practice_phase = self.practice.label();
+        let (practice_elapsed_secs, practice_remaining_secs) = self
+            .practice_tick
+            .as_ref()
+            .map(|t| (t.elapsed.as_secs_f64(), t.remaining.as_secs_f64()))
+            .unwrap_or_default();
 
-        UiState {
+        let mut ui = UiState {
             connected: false,
             code_running: false,
             voltage: 0.0,
             brownout: false,
             estopped: self.ds.is_estopped(),
-            enabled: self.enabled,
-            mode: format!("{}", self.mode),
+            enabled,
+            mode: format!("{}", mode),
             joysticks: joystick_info,
             any_joystick_connected: self.joysticks.any_connected(),
+            joystick_lockout: self.joystick_lockout.clone(),
+            hotkey_test_mode: self.hotkeys.is_testing(),
+            hotkey_test: self
+                .last_hotkey_test
+                .as_ref()
+                .map(HotkeyTestSerialized::from),
             practice_phase,
-            practice_elapsed_secs: 0.0,
-            practice_remaining_secs: 0.0,
+            practice_elapsed_secs,
+            practice_remaining_secs,
+            practice_paused: self.practice.is_paused(),
+            match_name: self.ds.match_info().name(),
+            connection_state: String::new(),
+            udp_connected: false,
+            tcp_connected: false,
             trip_time_ms: 0.0,
             lost_packets: 0,
+            fms_connected: self.fms_attached,
+            telemetry: TelemetrySerialized::default(),
             team_number: self.config.team_number,
             alliance_color,
             alliance_station,
+        };
+
+        apply_connection_status(&mut ui, &self.connection.borrow());
+        apply_robot_state(&mut ui, &self.robot_state.borrow());
+        ui
+    }
+
+    // Every slot is sampled, empty or disconnected ones as neutral data, so
+    // the robot keeps seeing stable slot indices and never a stale axis value.
+    fn push_joysticks(&mut self) {
+        let data = self.joysticks.get_joystick_data();
+        self.ds.set_joysticks(data);
+        // Only resent over TCP when a slot actually changed
+        self.ds
+            .set_joystick_descriptors(self.joysticks.get_joystick_descriptors());
+    }
+
+    // Under FMS the field decides, not the local controls
+    fn effective_control(&self) -> (bool, Mode, Alliance) {
+        match *self.fms.borrow() {
+            Some(fms) => (
+                fms.enabled && !fms.estop && !self.ds.is_estopped(),
+                fms.mode,
+                fms.alliance,
+            ),
+            None => (self.enabled, self.mode, self.alliance),
         }
     }
 
     fn update_ui_state(&self) {
         let _ = self.ui_state_tx.send(self.build_ui_state());
+
+        let (enabled, mode, _) = self.effective_control();
+        self.log_context.send_if_modified(|context| {
+            let next = LogContext {
+                enabled,
+                mode,
+                match_info: self.ds.match_info().clone(),
+            };
+            let changed = *context != next;
+            *context = next;
+            changed
+        });
+    }
+}
+
+impl Drop for AppState {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+fn apply_connection_status(ui: &mut UiState, status: &ConnectionStatus) {
+    ui.connection_state = status.state.to_string();
+    ui.udp_connected = status.udp;
+    ui.tcp_connected = status.tcp;
+}
+
+fn apply_robot_state(ui: &mut UiState, robot: &RobotState) {
+    ui.connected = robot.connected;
+    if !robot.connected {
+        return;
     }
+
+    ui.code_running = robot.code_running;
+    ui.voltage = robot.voltage.volts;
+    ui.brownout = robot.status.brownout;
+    ui.estopped |= robot.status.estop;
+    ui.trip_time_ms = robot.trip_time_ms;
+    ui.lost_packets = robot.lost_packets;
+    ui.telemetry = TelemetrySerialized::from(&robot.telemetry);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{DsLogConfig, MatchInfoType, PracticeTiming};
+    use open_station_rio_sim::sim::{self, SimConfig};
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    // Session logs would otherwise land in the real config directory
+    fn test_config() -> Config {
+        Config {
+            logging: DsLogConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
 
     #[test]
     fn test_new_app_state() {
-        let config = Config::default();
+        let config = test_config();
         let state = AppState::new(config);
         let ui = state.build_ui_state();
         assert_eq!(ui.team_number, 0);
@@ -331,9 +942,57 @@ mod tests {
         assert_eq!(ui.alliance_station, 1);
     }
 
+    #[test]
+    fn test_session_logs_stay_in_configured_dir() {
+        let runtime = Runtime::new().unwrap();
+        let _sim = runtime
+            .block_on(sim::start(SimConfig {
+                udp_port: 41410,
+                ds_port: 41411,
+                tcp_port: 41412,
+                code_start_delay: Duration::from_millis(100),
+                ..Default::default()
+            }))
+            .expect("simulator should bind loopback ports");
+
+        let dir = tempfile::tempdir().unwrap();
+        let default_logs = DsLogConfig::default().directory();
+        let before = files(&default_logs);
+        let mut state = AppState::new_headless(Config {
+            network: NetworkConfig {
+                target_address: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+                robot_udp_port: 41410,
+                ds_udp_port: 41411,
+                tcp_port: 41412,
+            },
+            logging: DsLogConfig {
+                enabled: true,
+                directory: Some(dir.path().join("logs")),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while files(&dir.path().join("logs")).is_empty() && Instant::now() < deadline {
+            state.poll();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let written = files(&dir.path().join("logs"));
+        assert!(written.iter().any(|name| name.ends_with(".dslog")));
+        assert!(written.iter().any(|name| name.ends_with(".dsevents")));
+        assert_eq!(files(&default_logs), before);
+    }
+
+    #[test]
+    fn test_headless_skips_hotkey_listener() {
+        let state = AppState::new_headless(test_config());
+        assert!(!state.hotkeys.is_running());
+    }
+
     #[test]
     fn test_set_team() {
-        let mut state = AppState::new(Config::default());
+        let mut state = AppState::new(test_config());
         state.set_team(1234);
         let ui = state.build_ui_state();
         assert_eq!(ui.team_number, 1234);
@@ -341,7 +1000,7 @@ mod tests {
 
     #[test]
     fn test_mode_switching() {
-        let mut state = AppState::new(Config::default());
+        let mut state = AppState::new(test_config());
         state.set_mode(Mode::Autonomous);
         let ui = state.build_ui_state();
         assert_eq!(ui.mode, "Autonomous");
@@ -349,16 +1008,416 @@ mod tests {
 
     #[test]
     fn test_enable_disable() {
-        let mut state = AppState::new(Config::default());
+        let mut state = AppState::new(test_config());
         state.enable();
         assert!(state.build_ui_state().enabled);
         state.disable();
         assert!(!state.build_ui_state().enabled);
     }
 
+    fn connected_robot() -> RobotState {
+        RobotState {
+            connected: true,
+            code_running: true,
+            voltage: BatteryVoltage { volts: 12.25 },
+            status: StatusFlags::from_byte(0b1000_1000),
+            telemetry: TelemetryData {
+                can: CanMetrics {
+                    utilization: 42.0,
+                    ..Default::default()
+                },
+                cpu_usage: vec![25.0, 50.0],
+                ..Default::default()
+            },
+            sequence: 7,
+            trip_time_ms: 4.5,
+            lost_packets: 3,
+        }
+    }
+
+    #[test]
+    fn test_apply_robot_state() {
+        let mut ui = UiState::default();
+        apply_robot_state(&mut ui, &connected_robot());
+        assert!(ui.connected);
+        assert!(ui.code_running);
+        assert!((ui.voltage - 12.25).abs() < 0.01);
+        assert!(ui.brownout);
+        assert!(ui.estopped);
+        assert_eq!(ui.trip_time_ms, 4.5);
+        assert_eq!(ui.lost_packets, 3);
+        assert_eq!(ui.telemetry.can_utilization, 42.0);
+        assert_eq!(ui.telemetry.cpu_usage, vec![25.0, 50.0]);
+    }
+
+    #[test]
+    fn test_apply_disconnected_robot_state() {
+        let mut robot = connected_robot();
+        robot.connected = false;
+        let mut ui = UiState::default();
+        apply_robot_state(&mut ui, &robot);
+        assert!(!ui.connected);
+        assert!(!ui.code_running);
+        assert_eq!(ui.voltage, 0.0);
+        assert!(!ui.estopped);
+    }
+
+    #[test]
+    fn test_apply_connection_status() {
+        use open_station_protocol::connection::ConnectionState;
+
+        let mut ui = UiState::default();
+        apply_connection_status(
+            &mut ui,
+            &ConnectionStatus {
+                state: ConnectionState::UdpConnected,
+                udp: true,
+                tcp: false,
+                code_running: false,
+                target: None,
+            },
+        );
+        assert_eq!(ui.connection_state, "UDP Connected");
+        assert!(ui.udp_connected);
+        assert!(!ui.tcp_connected);
+    }
+
+    #[test]
+    fn test_hotkey_dispatch() {
+        let mut state = AppState::new(test_config());
+        state.dispatch_hotkey(HotkeyAction::Enable);
+        assert!(state.build_ui_state().enabled);
+        state.dispatch_hotkey(HotkeyAction::Disable);
+        assert!(!state.build_ui_state().enabled);
+
+        state.dispatch_hotkey(HotkeyAction::Enable);
+        state.dispatch_hotkey(HotkeyAction::EStop);
+        let ui = state.build_ui_state();
+        assert!(!ui.enabled);
+        assert!(ui.estopped);
+    }
+
+    #[test]
+    fn test_rebind_hotkey() {
+        let mut state = AppState::new(test_config());
+        state
+            .rebind_hotkey(HotkeyAction::Enable, "ctrl+shift+e")
+            .unwrap();
+        assert_eq!(state.config().hotkeys.enable, "Ctrl+Shift+E");
+
+        let err = state.rebind_hotkey(HotkeyAction::Enable, "E").unwrap_err();
+        assert_eq!(err, HotkeyError::SingleKeyEnable);
+        assert_eq!(state.config().hotkeys.enable, "Ctrl+Shift+E");
+        assert_eq!(
+            state
+                .hotkeys
+                .keymap()
+                .binding(HotkeyAction::Enable)
+                .to_string(),
+            "Ctrl+Shift+E"
+        );
+    }
+
+    #[test]
+    fn test_hotkey_test_mode_blocks_enable() {
+        let mut state = AppState::new(test_config());
+        state.enable();
+        state.set_hotkey_test_mode(true);
+        let ui = state.build_ui_state();
+        assert!(ui.hotkey_test_mode);
+        assert!(!ui.enabled);
+
+        state.enable();
+        assert!(!state.build_ui_state().enabled);
+
+        state.set_hotkey_test_mode(false);
+        state.enable();
+        assert!(state.build_ui_state().enabled);
+    }
+
+    fn practice_config(auto_secs: u32) -> Config {
+        Config {
+            practice_timing: PracticeTiming {
+                countdown_secs: 0,
+                auto_secs,
+                delay_secs: 0,
+                teleop_secs: 60,
+            },
+            ..test_config()
+        }
+    }
+
+    #[test]
+    fn test_fms_takes_over_match() {
+        let mut state = AppState::new(practice_config(60));
+        let (fms_tx, fms_rx) = watch::channel(None);
+        state.fms = fms_rx;
+        state.start_practice();
+        state.poll();
+        assert!(state.build_ui_state().enabled);
+        assert!(state.log_context.borrow().enabled);
+
+        fms_tx.send_replace(Some(FmsControl {
+            sequence: 0,
+            estop: false,
+            enabled: false,
+            mode: Mode::Teleop,
+            alliance: Alliance::new(AllianceColor::Blue, 1),
+            level: MatchType::Qualification,
+            match_number: 31,
+            replay_number: 1,
+            remaining_secs: 0,
+        }));
+        state.poll();
+        let ui = state.build_ui_state();
+        assert!(ui.fms_connected);
+        assert!(!ui.enabled);
+        assert_eq!(ui.practice_phase, "Idle");
+        assert_eq!(ui.match_name, "Qualification 31");
+        // Logs record what the field asked for
+        let context = state.log_context.borrow().clone();
+        assert!(!context.enabled);
+        assert_eq!(context.match_info.match_number, 31);
+
+        state.enable();
+        state.start_practice();
+        let ui = state.build_ui_state();
+        assert!(!ui.enabled);
+        assert_eq!(ui.practice_phase, "Idle");
+
+        fms_tx.send_replace(None);
+        state.poll();
+        let ui = state.build_ui_state();
+        assert!(!ui.fms_connected);
+        assert!(!ui.enabled);
+        assert_eq!(ui.match_name, "");
+    }
+
+    #[test]
+    fn test_ui_follows_fms_control() {
+        let mut state = AppState::new(test_config());
+        let (fms_tx, fms_rx) = watch::channel(None);
+        state.fms = fms_rx;
+        state.set_mode(Mode::Teleop);
+
+        fms_tx.send_replace(Some(FmsControl {
+            sequence: 0,
+            estop: false,
+            enabled: true,
+            mode: Mode::Autonomous,
+            alliance: Alliance::new(AllianceColor::Blue, 2),
+            level: MatchType::Qualification,
+            match_number: 12,
+            replay_number: 1,
+            remaining_secs: 15,
+        }));
+        state.poll();
+        let ui = state.build_ui_state();
+        assert!(ui.enabled);
+        assert_eq!(ui.mode, "Autonomous");
+        assert_eq!(ui.alliance_color, "Blue");
+        assert_eq!(ui.alliance_station, 2);
+
+        state.estop();
+        assert!(!state.build_ui_state().enabled);
+    }
+
+    #[test]
+    fn test_saved_game_data_applied_at_startup() {
+        let state = AppState::new(Config {
+            game_data: "LRL".to_string(),
+            ..test_config()
+        });
+        assert_eq!(state.ds.game_data(), "LRL");
+    }
+
+    #[test]
+    fn test_practice_numbers_its_matches() {
+        let mut config = practice_config(60);
+        config.match_info = MatchInfoConfig {
+            event_name: "Week 0".to_string(),
+            match_type: MatchInfoType::Qualification,
+            match_number: 12,
+            replay_number: 1,
+        };
+        let mut state = AppState::new(config);
+        assert_eq!(state.build_ui_state().match_name, "Week 0 Qualification 12");
+
+        state.start_practice();
+        assert_eq!(state.build_ui_state().match_name, "Week 0 Practice 1");
+        // Manual entry waits for the practice match to end
+        state.set_match_info(MatchInfoConfig {
+            event_name: "Week 0".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(state.ds.match_info().match_type, MatchType::Practice);
+
+        state.stop_practice();
+        assert_eq!(state.build_ui_state().match_name, "Week 0");
+        state.start_practice();
+        assert_eq!(state.ds.match_info().match_number, 2);
+    }
+
+    #[test]
+    fn test_practice_drives_robot() {
+        let mut state = AppState::new(practice_config(60));
+        state.start_practice();
+        state.poll();
+
+        let ui = state.build_ui_state();
+        assert_eq!(ui.practice_phase, "Autonomous");
+        assert_eq!(ui.mode, "Autonomous");
+        assert!(ui.enabled);
+        assert!(ui.practice_remaining_secs > 59.0);
+        assert!(state.ds.match_time().unwrap() > 59.0);
+
+        state.a_stop();
+        state.poll();
+        assert!(!state.build_ui_state().enabled);
+
+        state.stop_practice();
+        let ui = state.build_ui_state();
+        assert_eq!(ui.practice_phase, "Idle");
+        assert!(!ui.enabled);
+        assert_eq!(ui.practice_remaining_secs, 0.0);
+        assert_eq!(state.ds.match_time(), None);
+    }
+
+    #[test]
+    fn test_practice_reaches_teleop() {
+        let mut state = AppState::new(practice_config(0));
+        state.start_practice();
+        for _ in 0..5 {
+            state.poll();
+        }
+
+        let ui = state.build_ui_state();
+        assert_eq!(ui.practice_phase, "Teleop");
+        assert_eq!(ui.mode, "Teleoperated");
+        assert!(ui.enabled);
+    }
+
+    #[test]
+    fn test_practice_pause_and_skip() {
+        let mut state = AppState::new(practice_config(60));
+        state.start_practice();
+        state.poll();
+        assert!(state.build_ui_state().enabled);
+
+        state.pause_practice();
+        let ui = state.build_ui_state();
+        assert!(ui.practice_paused);
+        assert!(!ui.enabled);
+
+        assert!(state.skip_practice(SkipTarget::Teleop));
+        let ui = state.build_ui_state();
+        assert_eq!(ui.practice_phase, "Teleop");
+        // Already there
+        assert!(!state.skip_practice(SkipTarget::Teleop));
+        assert_eq!(ui.mode, "Teleoperated");
+        assert!(!ui.enabled);
+
+        state.resume_practice();
+        let ui = state.build_ui_state();
+        assert!(!ui.practice_paused);
+        assert!(ui.enabled);
+    }
+
+    #[test]
+    fn test_practice_emits_sounds() {
+        let mut state = AppState::new(practice_config(0));
+        let mut sounds = state.take_sound_rx().unwrap();
+        state.start_practice();
+        for _ in 0..5 {
+            state.poll();
+        }
+        state.stop_practice();
+
+        let mut received = Vec::new();
+        while let Ok(sound) = sounds.try_recv() {
+            received.push(sound);
+        }
+        assert_eq!(
+            received,
+            vec![
+                MatchSound::StartAuto,
+                MatchSound::EndAuto,
+                MatchSound::StartTeleop,
+                MatchSound::Abort,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_practice_respects_joystick_lockout() {
+        let mut state = AppState::new(practice_config(60));
+        state.joystick_lockout = Some("lost".to_string());
+        state.start_practice();
+        state.poll();
+
+        let ui = state.build_ui_state();
+        assert_eq!(ui.practice_phase, "Autonomous");
+        assert!(!ui.enabled);
+    }
+
+    fn disconnect_event() -> JoystickEvent {
+        JoystickEvent::Disconnected {
+            slot: 1,
+            uuid: "uuid-1".to_string(),
+            name: "Xbox Controller".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_joystick_loss_disables_and_locks_out() {
+        let mut state = AppState::new(test_config());
+        let mut messages = state.take_message_rx().unwrap();
+        let mut subscribed = state.subscribe_messages();
+        state.enable();
+
+        state.handle_joystick_events(vec![disconnect_event()]);
+        let ui = state.build_ui_state();
+        assert!(!ui.enabled);
+        assert!(ui.joystick_lockout.unwrap().contains("Xbox Controller"));
+        // Logs and API streams see it too
+        for message in [messages.try_recv().unwrap(), subscribed.try_recv().unwrap()] {
+            match message {
+                TcpMessage::ErrorReport { details, .. } => assert_eq!(
+                    details,
+                    "Robot disabled: joystick 1 (Xbox Controller) disconnected while enabled"
+                ),
+                other => panic!("expected ErrorReport, got {other:?}"),
+            }
+        }
+
+        // Re-enable is refused until the operator acknowledges
+        state.enable();
+        assert!(!state.build_ui_state().enabled);
+        state.acknowledge_joystick_loss();
+        state.enable();
+        assert!(state.build_ui_state().enabled);
+    }
+
+    #[test]
+    fn test_joystick_loss_while_disabled_is_ignored() {
+        let mut state = AppState::new(test_config());
+        state.handle_joystick_events(vec![disconnect_event()]);
+        assert!(state.build_ui_state().joystick_lockout.is_none());
+        state.enable();
+        assert!(state.build_ui_state().enabled);
+    }
+
+    #[test]
+    fn test_poll_samples_every_slot() {
+        let mut state = AppState::new(test_config());
+        state.poll();
+        assert_eq!(state.joysticks.get_joystick_data().len(), 6);
+        assert_eq!(state.joysticks.get_joystick_descriptors().len(), 6);
+    }
+
     #[test]
     fn test_estop() {
-        let mut state = AppState::new(Config::default());
+        let mut state = AppState::new(test_config());
         state.enable();
         state.estop();
         let ui = state.build_ui_state();
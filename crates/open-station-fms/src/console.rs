@@ -0,0 +1,197 @@
+use open_station_protocol::fms::FmsPorts;
+use open_station_protocol::types::*;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ConsoleError {
+    #[error("unknown station {0:?}, expected red1 to blue3")]
+    InvalidStation(String),
+    #[error("expected STATION=TEAM@HOST[:PORT], got {0:?}")]
+    InvalidAssignment(String),
+    #[error("invalid team number {0:?}")]
+    InvalidTeam(String),
+    #[error("invalid address {0:?}")]
+    InvalidAddress(String),
+    #[error("unknown level {0:?}, expected practice, qualification or elimination")]
+    InvalidLevel(String),
+    #[error("invalid match number {0:?}")]
+    InvalidMatchNumber(String),
+    #[error("unknown command {0:?}, try help")]
+    UnknownCommand(String),
+}
+
+// A team's driver station at one of the six stations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StationAssignment {
+    pub alliance: Alliance,
+    pub team: u16,
+    pub ds_addr: SocketAddr,
+}
+
+// `red1=1234@10.12.34.5`, the port defaults to the standard DS field port
+impl FromStr for StationAssignment {
+    type Err = ConsoleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ConsoleError::InvalidAssignment(s.to_string());
+        let (station, rest) = s.split_once('=').ok_or_else(invalid)?;
+        let (team, host) = rest.split_once('@').ok_or_else(invalid)?;
+
+        let alliance = parse_station(station)?;
+        let team = team
+            .trim()
+            .parse()
+            .map_err(|_| ConsoleError::InvalidTeam(team.to_string()))?;
+        let host = host.trim();
+        let ds_addr = match host.parse::<SocketAddr>() {
+            Ok(addr) => addr,
+            Err(_) => host
+                .parse::<IpAddr>()
+                .map(|ip| SocketAddr::new(ip, FmsPorts::default().ds_udp))
+                .map_err(|_| ConsoleError::InvalidAddress(host.to_string()))?,
+        };
+
+        Ok(StationAssignment {
+            alliance,
+            team,
+            ds_addr,
+        })
+    }
+}
+
+pub fn parse_station(s: &str) -> Result<Alliance, ConsoleError> {
+    let lower = s.trim().to_ascii_lowercase();
+    let (color, station) = if let Some(station) = lower.strip_prefix("red") {
+        (AllianceColor::Red, station)
+    } else if let Some(station) = lower.strip_prefix("blue") {
+        (AllianceColor::Blue, station)
+    } else {
+        return Err(ConsoleError::InvalidStation(s.to_string()));
+    };
+    match station.parse::<u8>() {
+        Ok(n @ 1..=3) => Ok(Alliance::new(color, n)),
+        _ => Err(ConsoleError::InvalidStation(s.to_string())),
+    }
+}
+
+pub fn parse_level(s: &str) -> Result<MatchType, ConsoleError> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "practice" => Ok(MatchType::Practice),
+        "qualification" | "qual" => Ok(MatchType::Qualification),
+        "elimination" | "elim" => Ok(MatchType::Elimination),
+        _ => Err(ConsoleError::InvalidLevel(s.to_string())),
+    }
+}
+
+// One line typed at the field console
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Start,
+    Abort,
+    Enable,
+    Disable,
+    Estop,
+    Assign(StationAssignment),
+    Remove(Alliance),
+    Level(MatchType),
+    Match(u16),
+    Status,
+    Help,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = ConsoleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match s.trim().split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (s.trim(), ""),
+        };
+
+        match name {
+            "start" => Ok(Command::Start),
+            "abort" => Ok(Command::Abort),
+            "enable" => Ok(Command::Enable),
+            "disable" => Ok(Command::Disable),
+            "estop" => Ok(Command::Estop),
+            "assign" => Ok(Command::Assign(arg.parse()?)),
+            "remove" => Ok(Command::Remove(parse_station(arg)?)),
+            "level" => Ok(Command::Level(parse_level(arg)?)),
+            "match" => arg
+                .parse()
+                .map(Command::Match)
+                .map_err(|_| ConsoleError::InvalidMatchNumber(arg.to_string())),
+            "status" => Ok(Command::Status),
+            "help" => Ok(Command::Help),
+            "quit" | "exit" => Ok(Command::Quit),
+            _ => Err(ConsoleError::UnknownCommand(s.trim().to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_parse_assignment() {
+        let assignment: StationAssignment = "blue2=1234@10.12.34.5".parse().unwrap();
+        assert_eq!(assignment.alliance, Alliance::new(AllianceColor::Blue, 2));
+        assert_eq!(assignment.team, 1234);
+        assert_eq!(
+            assignment.ds_addr,
+            SocketAddr::from((Ipv4Addr::new(10, 12, 34, 5), 1120))
+        );
+
+        let assignment: StationAssignment = "Red3=254@127.0.0.1:41120".parse().unwrap();
+        assert_eq!(assignment.alliance, Alliance::new(AllianceColor::Red, 3));
+        assert_eq!(assignment.ds_addr.port(), 41120);
+    }
+
+    #[test]
+    fn test_parse_assignment_errors() {
+        assert_eq!(
+            "red4=1@10.0.0.1".parse::<StationAssignment>(),
+            Err(ConsoleError::InvalidStation("red4".into()))
+        );
+        assert_eq!(
+            "red1=abc@10.0.0.1".parse::<StationAssignment>(),
+            Err(ConsoleError::InvalidTeam("abc".into()))
+        );
+        assert_eq!(
+            "red1=1@robot".parse::<StationAssignment>(),
+            Err(ConsoleError::InvalidAddress("robot".into()))
+        );
+        assert!(matches!(
+            "red1 1234".parse::<StationAssignment>(),
+            Err(ConsoleError::InvalidAssignment(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!("start".parse(), Ok(Command::Start));
+        assert_eq!(" estop ".parse(), Ok(Command::Estop));
+        assert_eq!(
+            "remove blue1".parse(),
+            Ok(Command::Remove(Alliance::new(AllianceColor::Blue, 1)))
+        );
+        assert_eq!(
+            "level qual".parse(),
+            Ok(Command::Level(MatchType::Qualification))
+        );
+        assert_eq!("match 12".parse(), Ok(Command::Match(12)));
+        assert!(matches!(
+            "assign red1=1234@10.12.34.5".parse(),
+            Ok(Command::Assign(_))
+        ));
+        assert_eq!(
+            "launch".parse::<Command>(),
+            Err(ConsoleError::UnknownCommand("launch".into()))
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Instant;
+use tokio::net::UdpSocket;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{interval, timeout, Duration};
+
+use open_station_protocol::fms::FmsPorts;
+use open_station_protocol::packet::fms::{self, DsStatus, FmsControl};
+use open_station_protocol::types::*;
+
+use crate::packet;
+
+// A station's DS link is down after this long without a status reply
+const DS_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct FieldConfig {
+    pub bind_addr: IpAddr,
+    // Where driver stations send their status
+    pub fms_port: u16,
+    pub send_interval: Duration,
+}
+
+impl Default for FieldConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            fms_port: FmsPorts::default().fms_udp,
+            send_interval: Duration::from_millis(250),
+        }
+    }
+}
+
+// What the field tells every station, only the alliance differs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldControl {
+    pub estop: bool,
+    pub enabled: bool,
+    pub mode: Mode,
+    pub level: MatchType,
+    pub match_number: u16,
+    pub replay_number: u8,
+    pub remaining_secs: u16,
+}
+
+impl Default for FieldControl {
+    fn default() -> Self {
+        Self {
+            estop: false,
+            enabled: false,
+            mode: Mode::Autonomous,
+            level: MatchType::Practice,
+            match_number: 1,
+            replay_number: 1,
+            remaining_secs: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StationObserved {
+    pub alliance: Alliance,
+    // Team assigned to the station, the DS reports its own in each status
+    pub team: u16,
+    pub ds_addr: SocketAddr,
+    // Latest status reply from the DS
+    pub status: Option<DsStatus>,
+    pub replies: u32,
+    pub ds_linked: bool,
+    last_reply: Option<Instant>,
+}
+
+impl StationObserved {
+    pub fn new(alliance: Alliance, team: u16, ds_addr: SocketAddr) -> Self {
+        Self {
+            alliance,
+            team,
+            ds_addr,
+            status: None,
+            replies: 0,
+            ds_linked: false,
+            last_reply: None,
+        }
+    }
+
+    pub fn robot_linked(&self) -> bool {
+        self.ds_linked && self.status.is_some_and(|s| s.robot_comms)
+    }
+}
+
+// Everything the field has sent and heard back
+#[derive(Debug, Clone, Default)]
+pub struct Observed {
+    pub packets_sent: u32,
+    pub stations: Vec<StationObserved>,
+}
+
+impl Observed {
+    pub fn station(&self, alliance: Alliance) -> Option<&StationObserved> {
+        self.stations.iter().find(|s| s.alliance == alliance)
+    }
+}
+
+enum Command {
+    AddStation(Alliance, u16, SocketAddr),
+    RemoveStation(Alliance),
+    SetControl(FieldControl),
+}
+
+pub struct FieldHandle {
+    commands: mpsc::UnboundedSender<Command>,
+    observed: watch::Receiver<Observed>,
+    addr: SocketAddr,
+}
+
+impl FieldHandle {
+    // Starts sending field control to the DS listening at ds_addr. A station
+    // that already exists is reassigned.
+    pub fn add_station(&self, alliance: Alliance, team: u16, ds_addr: SocketAddr) {
+        let _ = self
+            .commands
+            .send(Command::AddStation(alliance, team, ds_addr));
+    }
+
+    // The DS detaches once it stops hearing from the field
+    pub fn remove_station(&self, alliance: Alliance) {
+        let _ = self.commands.send(Command::RemoveStation(alliance));
+    }
+
+    pub fn set_control(&self, control: FieldControl) {
+        let _ = self.commands.send(Command::SetControl(control));
+    }
+
+    pub fn observed(&self) -> Observed {
+        self.observed.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Observed> {
+        self.observed.clone()
+    }
+
+    pub async fn wait_for(
+        &self,
+        limit: Duration,
+        predicate: impl FnMut(&Observed) -> bool,
+    ) -> Option<Observed> {
+        let mut rx = self.observed.clone();
+        let result = match timeout(limit, rx.wait_for(predicate)).await {
+            Ok(Ok(observed)) => Some(observed.clone()),
+            _ => None,
+        };
+        result
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+pub async fn start(config: FieldConfig) -> io::Result<FieldHandle> {
+    let socket = UdpSocket::bind(SocketAddr::new(config.bind_addr, config.fms_port)).await?;
+    let addr = socket.local_addr()?;
+    log::info!("Field listening for driver stations on UDP {}", addr);
+
+    let (command_tx, command_rx) = mpsc::unbounded_channel();
+    let (observed_tx, observed_rx) = watch::channel(Observed::default());
+
+    let field = Field {
+        control: FieldControl::default(),
+        sequence: 0,
+        observed: Observed::default(),
+        observed_tx,
+    };
+    tokio::spawn(field.run(socket, config.send_interval, command_rx));
+
+    Ok(FieldHandle {
+        commands: command_tx,
+        observed: observed_rx,
+        addr,
+    })
+}
+
+struct Field {
+    control: FieldControl,
+    sequence: u16,
+    observed: Observed,
+    observed_tx: watch::Sender<Observed>,
+}
+
+impl Field {
+    async fn run(
+        mut self,
+        socket: UdpSocket,
+        send_interval: Duration,
+        mut command_rx: mpsc::UnboundedReceiver<Command>,
+    ) {
+        let mut ticker = interval(send_interval);
+        let mut buf = vec![0u8; 256];
+        let mut commands_open = true;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    for station in &self.observed.stations {
+                        let packet = packet::build_fms_packet(&self.packet_for(station.alliance));
+                        match socket.send_to(&packet, station.ds_addr).await {
+                            Ok(_) => self.observed.packets_sent += 1,
+                            Err(e) => log::warn!("UDP send to {} failed: {}", station.ds_addr, e),
+                        }
+                    }
+                    self.sequence = self.sequence.wrapping_add(1);
+                    self.expire_links(Instant::now());
+                }
+
+                result = socket.recv_from(&mut buf) => {
+                    match result {
+                        Ok((len, from)) => self.handle_status(&buf[..len], from),
+                        Err(e) => log::warn!("UDP receive error: {}", e),
+                    }
+                }
+
+                command = command_rx.recv(), if commands_open => {
+                    match command {
+                        Some(command) => self.handle_command(command),
+                        // Keep the field running after the handle is dropped
+                        None => commands_open = false,
+                    }
+                }
+            }
+
+            self.observed_tx.send_replace(self.observed.clone());
+        }
+    }
+
+    fn packet_for(&self, alliance: Alliance) -> FmsControl {
+        FmsControl {
+            sequence: self.sequence,
+            estop: self.control.estop,
+            enabled: self.control.enabled,
+            mode: self.control.mode,
+            alliance,
+            level: self.control.level,
+            match_number: self.control.match_number,
+            replay_number: self.control.replay_number,
+            remaining_secs: self.control.remaining_secs,
+        }
+    }
+
+    fn handle_status(&mut self, data: &[u8], from: SocketAddr) {
+        let status = match fms::parse_ds_status(data) {
+            Ok(status) => status,
+            Err(e) => {
+                log::warn!("Failed to parse DS status from {}: {}", from, e);
+                return;
+            }
+        };
+        // Replies come from the port the DS listens on
+        match self
+            .observed
+            .stations
+            .iter_mut()
+            .find(|s| s.ds_addr == from)
+        {
+            Some(station) => {
+                if !station.ds_linked {
+                    log::info!("{} DS linked", describe(station.alliance));
+                }
+                if status.team != station.team {
+                    log::warn!(
+                        "{} is assigned team {} but its DS reports team {}",
+                        describe(station.alliance),
+                        station.team,
+                        status.team
+                    );
+                }
+                station.status = Some(status);
+                station.replies += 1;
+                station.ds_linked = true;
+                station.last_reply = Some(Instant::now());
+            }
+            None => log::debug!("Ignoring status from unknown DS {}", from),
+        }
+    }
+
+    fn handle_command(&mut self, command: Command) {
+        match command {
+            Command::AddStation(alliance, team, ds_addr) => {
+                self.observed.stations.retain(|s| s.alliance != alliance);
+                self.observed
+                    .stations
+                    .push(StationObserved::new(alliance, team, ds_addr));
+            }
+            Command::RemoveStation(alliance) => {
+                self.observed.stations.retain(|s| s.alliance != alliance);
+            }
+            Command::SetControl(control) => self.control = control,
+        }
+    }
+    fn expire_links(&mut self, now: Instant) {
+        for station in &mut self.observed.stations {
+            let stale = station
+                .last_reply
+                .is_none_or(|at| now.saturating_duration_since(at) >= DS_TIMEOUT);
+            if station.ds_linked && stale {
+                log::warn!("{} DS link lost", describe(station.alliance));
+                station.ds_linked = false;
+            }
+        }
+    }
+}
+
+pub fn describe(alliance: Alliance) -> String {
+    format!("{:?} {}", alliance.color, alliance.station)
+}
@@ -0,0 +1,5 @@
+pub mod console;
+pub mod field;
+pub mod match_log;
+pub mod packet;
+pub mod schedule;
@@ -0,0 +1,310 @@
+use open_station_fms::console::{self, Command, StationAssignment};
+use open_station_fms::field::{self, FieldConfig, FieldControl, FieldHandle, Observed};
+use open_station_fms::match_log::MatchLog;
+use open_station_fms::schedule::{MatchPhase, MatchRunner, MatchTiming};
+use open_station_protocol::types::*;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+const USAGE: &str = "\
+Usage: open-station-fms [OPTIONS]
+
+Options:
+  --bind ADDR            Address to listen on (default 0.0.0.0)
+  --fms-port PORT        UDP port driver stations reply to (default 1160)
+  --station ASSIGNMENT   Team at a station, e.g. red1=1234@10.12.34.5, add
+                         :PORT if the DS does not listen on 1120. Repeat
+                         for each station.
+  --level LEVEL          practice, qualification or elimination (default practice)
+  --match N              First match number (default 1)
+  --countdown SECS       Disabled countdown before autonomous (default 5)
+  --auto SECS            Autonomous length (default 15)
+  --pause SECS           Disabled pause before teleop (default 3)
+  --teleop SECS          Teleop length (default 135)
+  --log FILE             Append a summary of each match (default fms-matches.log)
+  -h, --help             Show this help";
+
+const COMMANDS: &str = "\
+Commands:
+  start                        Start the next match
+  abort                        End the match early, the next start replays it
+  enable, disable              Enable or disable every station outside a match
+  estop                        E-Stop every station until the next start
+  assign STATION=TEAM@HOST     Put a team's driver station at a station
+  remove STATION               Take a station off the field
+  level LEVEL                  Set the tournament level
+  match N                      Jump to a match number
+  status                       Show every station
+  quit                         Disable every station and exit";
+
+struct Args {
+    config: FieldConfig,
+    stations: Vec<StationAssignment>,
+    level: MatchType,
+    match_number: u16,
+    timing: MatchTiming,
+    log_path: PathBuf,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        config: FieldConfig {
+            bind_addr: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            ..Default::default()
+        },
+        stations: Vec::new(),
+        level: MatchType::Practice,
+        match_number: 1,
+        timing: MatchTiming::default(),
+        log_path: PathBuf::from("fms-matches.log"),
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "-h" || arg == "--help" {
+            return Err(String::new());
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {arg}"))?;
+        let invalid = || format!("invalid value for {arg}: {value}");
+        let secs = || {
+            value
+                .parse::<f32>()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f32(secs).ok())
+                .ok_or_else(invalid)
+        };
+
+        match arg.as_str() {
+            "--bind" => args.config.bind_addr = value.parse::<IpAddr>().map_err(|_| invalid())?,
+            "--fms-port" => args.config.fms_port = value.parse().map_err(|_| invalid())?,
+            "--station" => {
+                let station: StationAssignment = value.parse().map_err(|e| format!("{e}"))?;
+                args.stations.retain(|s| s.alliance != station.alliance);
+                args.stations.push(station);
+            }
+            "--level" => args.level = console::parse_level(&value).map_err(|e| e.to_string())?,
+            "--match" => args.match_number = value.parse().map_err(|_| invalid())?,
+            "--countdown" => args.timing.countdown = secs()?,
+            "--auto" => args.timing.autonomous = secs()?,
+            "--pause" => args.timing.pause = secs()?,
+            "--teleop" => args.timing.teleop = secs()?,
+            "--log" => args.log_path = PathBuf::from(value),
+            _ => return Err(format!("unknown option {arg}")),
+        }
+    }
+
+    Ok(args)
+}
+
+fn describe_phase(runner: &MatchRunner) -> String {
+    let name = MatchInfo {
+        event_name: String::new(),
+        match_type: runner.level(),
+        match_number: runner.match_number(),
+        replay_number: runner.replay_number(),
+    }
+    .name();
+    match runner.phase() {
+        MatchPhase::Idle | MatchPhase::Done => format!("{name} | {:?}", runner.phase()),
+        phase => format!("{name} | {phase:?} {}s", runner.control().remaining_secs),
+    }
+}
+
+// Every station, empty ones included
+fn status_table(runner: &MatchRunner, observed: &Observed) -> String {
+    let mut out = describe_phase(runner);
+    for byte in 0..6 {
+        let alliance = Alliance::from_byte(byte).unwrap();
+        let name = field::describe(alliance);
+        let Some(station) = observed.station(alliance) else {
+            let _ = write!(out, "\n  {name:<6}     -  empty");
+            continue;
+        };
+
+        let up = |linked: bool| if linked { "up" } else { "down" };
+        let _ = write!(
+            out,
+            "\n  {name:<6} {:>5}  DS {:<4}  robot {:<4}",
+            station.team,
+            up(station.ds_linked),
+            up(station.robot_linked()),
+        );
+        if let Some(status) = station.status.filter(|_| station.ds_linked) {
+            let _ = write!(
+                out,
+                "  {:.2}V  {} {}",
+                status.voltage.volts,
+                if status.estop {
+                    "E-STOPPED"
+                } else if status.enabled {
+                    "enabled"
+                } else {
+                    "disabled"
+                },
+                status.mode,
+            );
+            if status.team != station.team {
+                let _ = write!(out, "  (DS reports team {})", status.team);
+            }
+        }
+    }
+    out
+}
+
+fn assign(field: &FieldHandle, station: &StationAssignment) {
+    field.add_station(station.alliance, station.team, station.ds_addr);
+    println!(
+        "{} is team {} at {}",
+        field::describe(station.alliance),
+        station.team,
+        station.ds_addr
+    );
+}
+
+fn append_log(path: &Path, summary: &str) {
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(summary.as_bytes()));
+    if let Err(e) = result {
+        eprintln!("error: failed to write match log {}: {e}", path.display());
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) if e.is_empty() => {
+            println!("{USAGE}\n\n{COMMANDS}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let field = match field::start(args.config).await {
+        Ok(field) => field,
+        Err(e) => {
+            eprintln!("error: failed to start field: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for station in &args.stations {
+        assign(&field, station);
+    }
+
+    let mut runner = MatchRunner::new(args.timing, args.level, args.match_number);
+    let mut match_log: Option<MatchLog> = None;
+    let mut robot_links: Vec<(Alliance, bool)> = Vec::new();
+    let mut ticker = tokio::time::interval(Duration::from_millis(100));
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdin_open = true;
+    println!("{COMMANDS}");
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+
+            line = lines.next_line(), if stdin_open => {
+                let line = match line {
+                    Ok(Some(line)) => line,
+                    // Keep running the field without a console
+                    _ => {
+                        stdin_open = false;
+                        continue;
+                    }
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match line.parse::<Command>() {
+                    Ok(Command::Start) => {
+                        runner.start();
+                        if runner.is_running() {
+                            let observed = field.observed();
+                            match_log = Some(MatchLog::new(
+                                runner.level(),
+                                runner.match_number(),
+                                runner.replay_number(),
+                                &observed,
+                            ));
+                            println!("{}", status_table(&runner, &observed));
+                        }
+                    }
+                    Ok(Command::Abort) => runner.abort(),
+                    Ok(Command::Enable) => runner.set_enabled(true),
+                    Ok(Command::Disable) => runner.set_enabled(false),
+                    Ok(Command::Estop) => runner.estop(),
+                    Ok(Command::Assign(station)) => assign(&field, &station),
+                    Ok(Command::Remove(alliance)) => field.remove_station(alliance),
+                    Ok(Command::Level(level)) => runner.set_level(level),
+                    Ok(Command::Match(number)) => runner.set_match_number(number),
+                    Ok(Command::Status) => println!("{}", status_table(&runner, &field.observed())),
+                    Ok(Command::Help) => println!("{COMMANDS}"),
+                    Ok(Command::Quit) => break,
+                    Err(e) => println!("{e}"),
+                }
+            }
+        }
+
+        if runner.tick().is_some() {
+            println!("{}", describe_phase(&runner));
+        }
+
+        let observed = field.observed();
+        robot_links.retain(|(alliance, _)| observed.station(*alliance).is_some());
+        for station in &observed.stations {
+            let linked = station.robot_linked();
+            match robot_links.iter_mut().find(|(a, _)| *a == station.alliance) {
+                Some((_, was)) if *was == linked => {}
+                Some((_, was)) => {
+                    *was = linked;
+                    println!(
+                        "{} ({}) robot link {}",
+                        field::describe(station.alliance),
+                        station.team,
+                        if linked { "up" } else { "lost" }
+                    );
+                }
+                None => robot_links.push((station.alliance, linked)),
+            }
+        }
+
+        if let Some(log) = &mut match_log {
+            log.record(&observed);
+            if let Some(result) = runner.result().filter(|_| !runner.is_running()) {
+                let summary = log.summary(result);
+                print!("{summary}");
+                append_log(&args.log_path, &summary);
+                match_log = None;
+            }
+        }
+
+        field.set_control(runner.control());
+    }
+
+    // Leave every station disabled for a couple of packets before exiting
+    runner.abort();
+    if let Some(log) = match_log {
+        let summary = log.summary(runner.result().unwrap());
+        print!("{summary}");
+        append_log(&args.log_path, &summary);
+    }
+    field.set_control(FieldControl::default());
+    tokio::time::sleep(Duration::from_millis(600)).await;
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,174 @@
+use chrono::{DateTime, Local};
+use open_station_protocol::types::*;
+use std::fmt::Write;
+
+use crate::field::{self, Observed, StationObserved};
+use crate::schedule::MatchResult;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StationLog {
+    pub alliance: Alliance,
+    pub team: u16,
+    pub ds_linked: bool,
+    pub robot_linked: bool,
+    // Times a link that was up went down during the match
+    pub ds_drops: u32,
+    pub robot_drops: u32,
+    pub min_voltage: Option<f32>,
+    pub estopped: bool,
+}
+
+impl StationLog {
+    fn new(station: &StationObserved) -> Self {
+        let mut log = Self {
+            alliance: station.alliance,
+            team: station.team,
+            ds_linked: false,
+            robot_linked: false,
+            ds_drops: 0,
+            robot_drops: 0,
+            min_voltage: None,
+            estopped: false,
+        };
+        log.record(station);
+        log
+    }
+
+    fn record(&mut self, station: &StationObserved) {
+        let robot_linked = station.robot_linked();
+        if self.ds_linked && !station.ds_linked {
+            self.ds_drops += 1;
+        }
+        if self.robot_linked && !robot_linked {
+            self.robot_drops += 1;
+        }
+        self.ds_linked = station.ds_linked;
+        self.robot_linked = robot_linked;
+
+        if let Some(status) = station.status.filter(|_| robot_linked) {
+            let volts = status.voltage.volts;
+            self.min_voltage = Some(self.min_voltage.map_or(volts, |min| min.min(volts)));
+            self.estopped |= status.estop;
+        }
+    }
+}
+
+// Link history of every station over one match
+#[derive(Debug, Clone)]
+pub struct MatchLog {
+    pub level: MatchType,
+    pub match_number: u16,
+    pub replay_number: u8,
+    pub started: DateTime<Local>,
+    pub stations: Vec<StationLog>,
+}
+
+impl MatchLog {
+    pub fn new(
+        level: MatchType,
+        match_number: u16,
+        replay_number: u8,
+        observed: &Observed,
+    ) -> Self {
+        let mut stations: Vec<StationLog> = observed.stations.iter().map(StationLog::new).collect();
+        stations.sort_by_key(|s| s.alliance.to_byte());
+        Self {
+            level,
+            match_number,
+            replay_number,
+            started: Local::now(),
+            stations,
+        }
+    }
+
+    // Stations assigned mid-match are not tracked
+    pub fn record(&mut self, observed: &Observed) {
+        for log in &mut self.stations {
+            if let Some(station) = observed.station(log.alliance) {
+                log.record(station);
+            }
+        }
+    }
+
+    pub fn name(&self) -> String {
+        MatchInfo {
+            event_name: String::new(),
+            match_type: self.level,
+            match_number: self.match_number,
+            replay_number: self.replay_number,
+        }
+        .name()
+    }
+
+    pub fn summary(&self, result: MatchResult) -> String {
+        let mut out = format!(
+            "{} {} {}\n",
+            self.started.format("%Y-%m-%d %H:%M:%S"),
+            self.name(),
+            match result {
+                MatchResult::Completed => "completed",
+                MatchResult::Aborted => "aborted",
+            }
+        );
+        for station in &self.stations {
+            let _ = writeln!(
+                out,
+                "  {:<6} {:>5}  DS drops {}  robot drops {}  min {}{}",
+                field::describe(station.alliance),
+                station.team,
+                station.ds_drops,
+                station.robot_drops,
+                station
+                    .min_voltage
+                    .map_or("-".to_string(), |v| format!("{v:.2}V")),
+                if station.estopped { "  E-STOPPED" } else { "" },
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use open_station_protocol::packet::fms::DsStatus;
+
+    fn observed(ds_linked: bool, robot_comms: bool, volts: f32) -> Observed {
+        let mut station = StationObserved::new(
+            Alliance::new(AllianceColor::Blue, 1),
+            254,
+            "127.0.0.1:1120".parse().unwrap(),
+        );
+        station.ds_linked = ds_linked;
+        station.status = Some(DsStatus {
+            sequence: 0,
+            estop: false,
+            robot_comms,
+            enabled: true,
+            mode: Mode::Teleop,
+            team: 254,
+            voltage: BatteryVoltage { volts },
+        });
+        Observed {
+            packets_sent: 0,
+            stations: vec![station],
+        }
+    }
+
+    #[test]
+    fn test_match_log_counts_drops() {
+        let mut log = MatchLog::new(MatchType::Qualification, 7, 2, &observed(true, true, 12.4));
+        log.record(&observed(true, false, 0.0));
+        log.record(&observed(true, true, 11.1));
+        log.record(&observed(false, true, 11.0));
+
+        let station = &log.stations[0];
+        assert_eq!(station.ds_drops, 1);
+        assert_eq!(station.robot_drops, 2);
+        assert_eq!(station.min_voltage, Some(11.1));
+
+        let summary = log.summary(MatchResult::Completed);
+        assert!(summary.contains("Qualification 7 Replay 2 completed"));
+        assert!(summary.contains("Blue 1   254  DS drops 1  robot drops 2  min 11.10V"));
+    }
+}
@@ -0,0 +1,61 @@
+use chrono::{Datelike, Timelike, Utc};
+use open_station_protocol::packet::fms::{FmsControl, FMS_CONTROL_LEN};
+use open_station_protocol::types::*;
+
+pub fn build_fms_packet(control: &FmsControl) -> Vec<u8> {
+    let flags = ControlFlags {
+        estop: control.estop,
+        fms_connected: false,
+        enabled: control.enabled,
+        mode: control.mode,
+    };
+
+    let mut buf = Vec::with_capacity(FMS_CONTROL_LEN);
+    buf.extend_from_slice(&control.sequence.to_be_bytes());
+    buf.push(0x00);
+    buf.push(flags.to_byte());
+    buf.push(0x00);
+    buf.push(control.alliance.to_byte());
+    buf.push(control.level.to_byte());
+    buf.extend_from_slice(&control.match_number.to_be_bytes());
+    buf.push(control.replay_number);
+    append_field_time(&mut buf);
+    buf.extend_from_slice(&control.remaining_secs.to_be_bytes());
+    buf
+}
+
+// Same layout as the DS date tag payload
+fn append_field_time(buf: &mut Vec<u8>) {
+    let now = Utc::now();
+    buf.extend_from_slice(&(now.nanosecond() / 1000).to_be_bytes());
+    buf.push(now.second() as u8);
+    buf.push(now.minute() as u8);
+    buf.push(now.hour() as u8);
+    buf.push(now.day() as u8);
+    buf.push(now.month0() as u8);
+    buf.push((now.year() - 1900) as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use open_station_protocol::packet::fms::parse_fms_packet;
+
+    #[test]
+    fn test_fms_packet_round_trip() {
+        let control = FmsControl {
+            sequence: 300,
+            estop: false,
+            enabled: true,
+            mode: Mode::Autonomous,
+            alliance: Alliance::new(AllianceColor::Red, 3),
+            level: MatchType::Elimination,
+            match_number: 9,
+            replay_number: 2,
+            remaining_secs: 14,
+        };
+        let data = build_fms_packet(&control);
+        assert_eq!(data.len(), FMS_CONTROL_LEN);
+        assert_eq!(parse_fms_packet(&data).unwrap(), control);
+    }
+}
@@ -0,0 +1,363 @@
+use open_station_protocol::clock::{Clock, SystemClock};
+use open_station_protocol::types::*;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::field::FieldControl;
+
+// Period lengths of a field match, a zero length period is skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchTiming {
+    pub countdown: Duration,
+    pub autonomous: Duration,
+    pub pause: Duration,
+    pub teleop: Duration,
+}
+
+impl Default for MatchTiming {
+    fn default() -> Self {
+        Self {
+            countdown: Duration::from_secs(5),
+            autonomous: Duration::from_secs(15),
+            pause: Duration::from_secs(3),
+            teleop: Duration::from_secs(135),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchPhase {
+    Idle,
+    Countdown,
+    Autonomous,
+    Pause,
+    Teleop,
+    Done,
+}
+
+impl MatchPhase {
+    fn enabled(self) -> bool {
+        matches!(self, MatchPhase::Autonomous | MatchPhase::Teleop)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchResult {
+    Completed,
+    Aborted,
+}
+
+// Runs one match at a time for every station on the field. A completed match
+// moves on to the next number, an aborted one is replayed.
+pub struct MatchRunner {
+    timing: MatchTiming,
+    clock: Arc<dyn Clock>,
+    level: MatchType,
+    match_number: u16,
+    replay_number: u8,
+    phase: MatchPhase,
+    step: usize,
+    phase_start: Instant,
+    result: Option<MatchResult>,
+    // Enable outside a match, for checking robots between matches
+    manual_enabled: bool,
+    estopped: bool,
+}
+
+impl MatchRunner {
+    pub fn new(timing: MatchTiming, level: MatchType, match_number: u16) -> Self {
+        Self::with_clock(timing, level, match_number, Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(
+        timing: MatchTiming,
+        level: MatchType,
+        match_number: u16,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let phase_start = clock.now();
+        Self {
+            timing,
+            clock,
+            level,
+            match_number,
+            replay_number: 1,
+            phase: MatchPhase::Idle,
+            step: 0,
+            phase_start,
+            result: None,
+            manual_enabled: false,
+            estopped: false,
+        }
+    }
+
+    pub fn phase(&self) -> MatchPhase {
+        self.phase
+    }
+
+    pub fn is_running(&self) -> bool {
+        !matches!(self.phase, MatchPhase::Idle | MatchPhase::Done)
+    }
+
+    pub fn result(&self) -> Option<MatchResult> {
+        self.result
+    }
+
+    pub fn level(&self) -> MatchType {
+        self.level
+    }
+
+    pub fn match_number(&self) -> u16 {
+        self.match_number
+    }
+
+    pub fn replay_number(&self) -> u8 {
+        self.replay_number
+    }
+
+    // Starts the next match, clearing a field E-Stop
+    pub fn start(&mut self) {
+        if self.is_running() {
+            return;
+        }
+        match self.result.take() {
+            Some(MatchResult::Completed) => {
+                self.match_number = self.match_number.saturating_add(1);
+                self.replay_number = 1;
+            }
+            Some(MatchResult::Aborted) => {
+                self.replay_number = self.replay_number.saturating_add(1);
+            }
+            None => {}
+        }
+        self.manual_enabled = false;
+        self.estopped = false;
+        self.step = 0;
+        self.phase_start = self.clock.now();
+        self.phase = self.periods().first().map_or(MatchPhase::Done, |p| p.0);
+        if self.phase == MatchPhase::Done {
+            self.result = Some(MatchResult::Completed);
+        }
+    }
+
+    pub fn abort(&mut self) {
+        if self.is_running() {
+            self.phase = MatchPhase::Done;
+            self.result = Some(MatchResult::Aborted);
+        }
+    }
+
+    // Disables every station until the next match starts
+    pub fn estop(&mut self) {
+        self.abort();
+        self.manual_enabled = false;
+        self.estopped = true;
+    }
+
+    // Enables or disables every station together. Ignored during a match.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !self.is_running() && !self.estopped {
+            self.manual_enabled = enabled;
+        }
+    }
+
+    pub fn set_level(&mut self, level: MatchType) {
+        if !self.is_running() {
+            self.level = level;
+        }
+    }
+
+    // Jumps to a match number, starting at its first play
+    pub fn set_match_number(&mut self, match_number: u16) {
+        if !self.is_running() {
+            self.match_number = match_number;
+            self.replay_number = 1;
+            self.result = None;
+        }
+    }
+
+    // Advances through periods that have run out, returns the new phase when
+    // it changed
+    pub fn tick(&mut self) -> Option<MatchPhase> {
+        if !self.is_running() {
+            return None;
+        }
+
+        let now = self.clock.now();
+        let periods = self.periods();
+        let before = self.phase;
+        while self.is_running() {
+            let length = periods[self.step].1;
+            if now.saturating_duration_since(self.phase_start) < length {
+                break;
+            }
+            self.phase_start += length;
+            self.step += 1;
+            match periods.get(self.step) {
+                Some(&(phase, _)) => self.phase = phase,
+                None => {
+                    self.phase = MatchPhase::Done;
+                    self.result = Some(MatchResult::Completed);
+                }
+            }
+        }
+
+        (self.phase != before).then_some(self.phase)
+    }
+
+    // Time left in the current period
+    pub fn remaining(&self) -> Duration {
+        if !self.is_running() {
+            return Duration::ZERO;
+        }
+        let elapsed = self.clock.now().saturating_duration_since(self.phase_start);
+        self.periods()[self.step].1.saturating_sub(elapsed)
+    }
+
+    pub fn control(&self) -> FieldControl {
+        let mode = match self.phase {
+            MatchPhase::Countdown | MatchPhase::Autonomous => Mode::Autonomous,
+            _ => Mode::Teleop,
+        };
+        let enabled = if self.is_running() {
+            self.phase.enabled()
+        } else {
+            self.manual_enabled
+        };
+        FieldControl {
+            estop: self.estopped,
+            enabled: enabled && !self.estopped,
+            mode,
+            level: self.level,
+            match_number: self.match_number,
+            replay_number: self.replay_number,
+            // Whole seconds, rounded up so a period never reads 0 early
+            remaining_secs: self.remaining().as_secs_f32().ceil() as u16,
+        }
+    }
+
+    fn periods(&self) -> Vec<(MatchPhase, Duration)> {
+        [
+            (MatchPhase::Countdown, self.timing.countdown),
+            (MatchPhase::Autonomous, self.timing.autonomous),
+            (MatchPhase::Pause, self.timing.pause),
+            (MatchPhase::Teleop, self.timing.teleop),
+        ]
+        .into_iter()
+        .filter(|(_, length)| !length.is_zero())
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use open_station_protocol::clock::MockClock;
+
+    fn runner(clock: &MockClock) -> MatchRunner {
+        MatchRunner::with_clock(
+            MatchTiming::default(),
+            MatchType::Qualification,
+            4,
+            Arc::new(clock.clone()),
+        )
+    }
+
+    #[test]
+    fn test_match_runs_countdown_auto_pause_teleop() {
+        let clock = MockClock::new();
+        let mut runner = runner(&clock);
+        assert!(!runner.control().enabled);
+
+        runner.start();
+        assert_eq!(runner.phase(), MatchPhase::Countdown);
+        let control = runner.control();
+        assert!(!control.enabled);
+        assert_eq!(control.mode, Mode::Autonomous);
+        assert_eq!(control.remaining_secs, 5);
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(runner.tick(), Some(MatchPhase::Autonomous));
+        assert!(runner.control().enabled);
+        assert_eq!(runner.control().remaining_secs, 15);
+
+        clock.advance(Duration::from_millis(14_500));
+        assert_eq!(runner.tick(), None);
+        assert_eq!(runner.control().remaining_secs, 1);
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(runner.tick(), Some(MatchPhase::Pause));
+        let control = runner.control();
+        assert!(!control.enabled);
+        assert_eq!(control.mode, Mode::Teleop);
+
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(runner.tick(), Some(MatchPhase::Teleop));
+        assert!(runner.control().enabled);
+
+        clock.advance(Duration::from_secs(135));
+        assert_eq!(runner.tick(), Some(MatchPhase::Done));
+        assert!(!runner.control().enabled);
+        assert_eq!(runner.result(), Some(MatchResult::Completed));
+
+        // The next start is the next match
+        runner.start();
+        assert_eq!(runner.match_number(), 5);
+        assert_eq!(runner.replay_number(), 1);
+    }
+
+    #[test]
+    fn test_long_tick_gap_skips_periods() {
+        let clock = MockClock::new();
+        let mut runner = runner(&clock);
+        runner.start();
+
+        clock.advance(Duration::from_secs(25));
+        assert_eq!(runner.tick(), Some(MatchPhase::Teleop));
+        assert_eq!(runner.control().remaining_secs, 133);
+    }
+
+    #[test]
+    fn test_aborted_match_is_replayed() {
+        let clock = MockClock::new();
+        let mut runner = runner(&clock);
+        runner.start();
+        clock.advance(Duration::from_secs(8));
+        runner.tick();
+        assert!(runner.control().enabled);
+
+        runner.abort();
+        assert_eq!(runner.phase(), MatchPhase::Done);
+        assert_eq!(runner.result(), Some(MatchResult::Aborted));
+        assert!(!runner.control().enabled);
+
+        runner.start();
+        assert_eq!(runner.match_number(), 4);
+        assert_eq!(runner.replay_number(), 2);
+    }
+
+    #[test]
+    fn test_manual_enable_and_estop() {
+        let clock = MockClock::new();
+        let mut runner = runner(&clock);
+        runner.set_enabled(true);
+        let control = runner.control();
+        assert!(control.enabled);
+        assert_eq!(control.mode, Mode::Teleop);
+
+        // Ignored while a match runs
+        runner.start();
+        runner.set_enabled(true);
+        assert!(!runner.control().enabled);
+
+        runner.estop();
+        let control = runner.control();
+        assert!(control.estop);
+        assert!(!control.enabled);
+        runner.set_enabled(true);
+        assert!(!runner.control().enabled);
+
+        runner.start();
+        assert!(!runner.control().estop);
+    }
+}
@@ -0,0 +1,196 @@
+use open_station_fms::field::{self, FieldConfig, FieldControl, FieldHandle};
+use open_station_fms::packet::build_fms_packet;
+use open_station_protocol::connection::Ports;
+use open_station_protocol::driver_station::{DriverStation, DsReceiver};
+use open_station_protocol::fms::FmsPorts;
+use open_station_protocol::packet::fms::FmsControl;
+use open_station_protocol::types::*;
+use open_station_rio_sim::sim::{self, SimConfig, SimHandle};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+const WAIT: Duration = Duration::from_secs(5);
+
+// Simulated robot, driver station and field on loopback, five ports from base
+async fn start_field(base_port: u16) -> (SimHandle, DriverStation, DsReceiver, FieldHandle) {
+    let ports = Ports {
+        robot_udp: base_port,
+        ds_udp: base_port + 1,
+        tcp: base_port + 2,
+    };
+    let fms_ports = FmsPorts {
+        ds_udp: base_port + 3,
+        fms_udp: base_port + 4,
+    };
+    let sim = sim::start(SimConfig {
+        udp_port: ports.robot_udp,
+        ds_port: ports.ds_udp,
+        tcp_port: ports.tcp,
+        code_start_delay: Duration::from_millis(100),
+        ..Default::default()
+    })
+    .await
+    .expect("simulator should bind loopback ports");
+
+    let (mut ds, rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+    ds.set_target_override(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    ds.set_ports(ports);
+    ds.set_fms_ports(Some(fms_ports));
+    ds.start();
+
+    let field = field::start(FieldConfig {
+        fms_port: fms_ports.fms_udp,
+        send_interval: Duration::from_millis(50),
+        ..Default::default()
+    })
+    .await
+    .expect("field should bind a loopback port");
+
+    (sim, ds, rx, field)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_field_takes_over_and_hands_back() {
+    let (sim, mut ds, mut rx, field) = start_field(41300).await;
+    timeout(WAIT, rx.state.wait_for(|s| s.code_running))
+        .await
+        .expect("robot never came up")
+        .unwrap();
+
+    // Locally enabled before the field shows up
+    ds.enable();
+    sim.wait_for(WAIT, |o| o.control.enabled).await.unwrap();
+
+    let blue2 = Alliance::new(AllianceColor::Blue, 2);
+    field.add_station(blue2, 1234, SocketAddr::from((Ipv4Addr::LOCALHOST, 41303)));
+    timeout(WAIT, rx.fms.wait_for(|f| f.is_some()))
+        .await
+        .expect("DS never attached to the field")
+        .unwrap();
+    let observed = sim
+        .wait_for(WAIT, |o| o.control.fms_connected)
+        .await
+        .unwrap();
+    assert!(
+        !observed.control.enabled,
+        "local enable leaked to the field"
+    );
+
+    field.set_control(FieldControl {
+        enabled: true,
+        mode: Mode::Autonomous,
+        level: MatchType::Qualification,
+        match_number: 4,
+        remaining_secs: 15,
+        ..Default::default()
+    });
+    let observed = sim
+        .wait_for(WAIT, |o| o.control.enabled && o.match_time == Some(15.0))
+        .await
+        .expect("field enable never reached the robot");
+    assert_eq!(observed.control.mode, Mode::Autonomous);
+    assert_eq!(observed.alliance, Some(blue2));
+
+    let observed = field
+        .wait_for(WAIT, |o| {
+            o.station(blue2)
+                .and_then(|s| s.status)
+                .is_some_and(|s| s.robot_comms && s.enabled)
+        })
+        .await
+        .expect("field never heard the DS status");
+    let station = observed.station(blue2).unwrap();
+    assert!(station.ds_linked && station.robot_linked());
+    let status = station.status.unwrap();
+    assert_eq!(status.team, 1234);
+    assert_eq!(status.mode, Mode::Autonomous);
+
+    // Pulling the station hands control back, disabled
+    field.remove_station(blue2);
+    let observed = sim
+        .wait_for(WAIT, |o| !o.control.fms_connected)
+        .await
+        .expect("DS never detached from the field");
+    assert!(!observed.control.enabled);
+    sim.wait_for(WAIT, |o| {
+        o.alliance == Some(Alliance::new(AllianceColor::Red, 1)) && o.match_time.is_none()
+    })
+    .await
+    .expect("local alliance and match time were not restored");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_local_estop_beats_field_enable() {
+    let (sim, mut ds, mut rx, field) = start_field(41310).await;
+    timeout(WAIT, rx.state.wait_for(|s| s.code_running))
+        .await
+        .expect("robot never came up")
+        .unwrap();
+
+    field.add_station(
+        Alliance::new(AllianceColor::Red, 3),
+        1234,
+        SocketAddr::from((Ipv4Addr::LOCALHOST, 41313)),
+    );
+    field.set_control(FieldControl {
+        enabled: true,
+        mode: Mode::Teleop,
+        ..Default::default()
+    });
+    sim.wait_for(WAIT, |o| o.control.fms_connected && o.control.enabled)
+        .await
+        .expect("field enable never reached the robot");
+
+    ds.estop();
+    let observed = sim
+        .wait_for(WAIT, |o| o.control.estop)
+        .await
+        .expect("E-Stop never reached the robot");
+    assert!(!observed.control.enabled);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_other_senders_ignored_while_attached() {
+    let (sim, _ds, mut rx, field) = start_field(41320).await;
+    timeout(WAIT, rx.state.wait_for(|s| s.code_running))
+        .await
+        .expect("robot never came up")
+        .unwrap();
+
+    let red1 = Alliance::new(AllianceColor::Red, 1);
+    let ds_addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 41323));
+    field.add_station(red1, 1234, ds_addr);
+    sim.wait_for(WAIT, |o| o.control.fms_connected)
+        .await
+        .expect("DS never attached to the field");
+
+    // A second sender on another loopback address tries to enable the robot
+    let rogue = UdpSocket::bind((Ipv4Addr::new(127, 0, 0, 2), 0))
+        .await
+        .expect("should bind a second loopback address");
+    let packet = build_fms_packet(&FmsControl {
+        sequence: 0,
+        estop: false,
+        enabled: true,
+        mode: Mode::Teleop,
+        alliance: red1,
+        level: MatchType::Qualification,
+        match_number: 1,
+        replay_number: 1,
+        remaining_secs: 150,
+    });
+    let spoofing = tokio::spawn(async move {
+        loop {
+            let _ = rogue.send_to(&packet, ds_addr).await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    });
+
+    let enabled = sim
+        .wait_for(Duration::from_secs(1), |o| o.control.enabled)
+        .await;
+    spoofing.abort();
+    assert!(enabled.is_none(), "a second sender enabled the robot");
+}
@@ -1,35 +1,209 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpStream, UdpSocket};
-use tokio::sync::mpsc;
-use tokio::time::{interval, timeout, Duration, Instant};
+use tokio::sync::{mpsc, watch};
+use tokio::time::error::Elapsed;
+use tokio::time::{interval, timeout, Duration};
 
+use crate::clock::{Clock, SystemClock};
+use crate::discovery::{self, Resolver};
+use crate::fms;
+use crate::packet::fms::FmsControl;
 use crate::packet::tcp::TcpFrameReader;
 use crate::packet::{incoming, outgoing, tcp};
 use crate::types::*;
 
+// Trip time is averaged over the last second of replies
+const TRIP_TIME_WINDOW: usize = 50;
+// Send timestamps older than this many packets can no longer be matched
+const MAX_PENDING_SENDS: usize = 100;
+// UDP is considered lost after this long without a reply
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(1);
+// Reconnect delays double from the first up to the cap
+const BACKOFF_START: Duration = Duration::from_millis(100);
+const BACKOFF_MAX: Duration = Duration::from_secs(2);
+// Wait before retrying a refused TCP connection
+const TCP_RETRY_DELAY: Duration = Duration::from_secs(2);
+const TCP_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Control flags, requests, joysticks, alliance and remaining match time,
+// resent in every packet until the next update
+pub type ControlUpdate = (
+    ControlFlags,
+    RequestFlags,
+    Vec<JoystickData>,
+    Alliance,
+    Option<f32>,
+);
+
+// What the robot learns over TCP rather than in every UDP packet. The
+// connection manager keeps the latest of each and replays all of it every
+// time the TCP stream comes up.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TcpSession {
+    pub joystick_descriptors: Vec<JoystickDescriptor>,
+    pub match_info: MatchInfo,
+    pub game_data: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionUpdate {
+    JoystickDescriptors(Vec<JoystickDescriptor>),
+    MatchInfo(MatchInfo),
+    GameData(String),
+}
+
+impl TcpSession {
+    // Records the update and returns the frames that carry it
+    pub fn apply(&mut self, update: SessionUpdate) -> Vec<u8> {
+        match update {
+            SessionUpdate::JoystickDescriptors(descriptors) => {
+                self.joystick_descriptors = descriptors;
+                tcp::build_joystick_descriptor_frames(&self.joystick_descriptors)
+            }
+            SessionUpdate::MatchInfo(info) => {
+                self.match_info = info;
+                tcp::build_match_info(&self.match_info)
+            }
+            SessionUpdate::GameData(data) => {
+                self.game_data = data;
+                tcp::build_game_data_frame(&self.game_data)
+            }
+        }
+    }
+
+    // Everything at once for a fresh connection. Robot code only learns
+    // about joysticks from descriptors, so those go first.
+    pub fn frames(&self) -> Vec<u8> {
+        let mut frames = tcp::build_joystick_descriptor_frames(&self.joystick_descriptors);
+        frames.extend(tcp::build_match_info(&self.match_info));
+        frames.extend(tcp::build_game_data_frame(&self.game_data));
+        frames
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LinkStats {
+    pub trip_time_ms: f64,
+    pub lost_packets: u32,
+    pub sent_count: u32,
+    pub received_count: u32,
+}
+
+// Standard FRC ports, overridable to talk to simulators or to run several
+// driver stations on one machine.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ports {
+    pub robot_udp: u16,
+    pub ds_udp: u16,
+    pub tcp: u16,
+}
+
+impl Default for Ports {
+    fn default() -> Self {
+        Self {
+            robot_udp: 1110,
+            ds_udp: 1150,
+            tcp: 1740,
+        }
+    }
+}
+
+// Where the link goes, changeable while running. A change drops the sockets
+// and resolves the robot again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LinkSettings {
+    pub team: u32,
+    pub use_usb: bool,
+    pub target_override: Option<IpAddr>,
+    pub ports: Ports,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ConnectionState {
+    #[default]
     Disconnected,
     Resolving,
-    Connected,
+    UdpConnected,
+    TcpConnected,
     CodeRunning,
 }
 
+impl ConnectionState {
+    pub fn is_connected(self) -> bool {
+        !matches!(
+            self,
+            ConnectionState::Disconnected | ConnectionState::Resolving
+        )
+    }
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectionState::Disconnected => write!(f, "Disconnected"),
+            ConnectionState::Resolving => write!(f, "Resolving"),
+            ConnectionState::UdpConnected => write!(f, "UDP Connected"),
+            ConnectionState::TcpConnected => write!(f, "TCP Connected"),
+            ConnectionState::CodeRunning => write!(f, "Code Running"),
+        }
+    }
+}
+
+// UDP and TCP are tracked separately so the UI can show which half of the
+// link is down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConnectionStatus {
+    pub state: ConnectionState,
+    pub udp: bool,
+    pub tcp: bool,
+    pub code_running: bool,
+    pub target: Option<SocketAddr>,
+}
+
+impl ConnectionStatus {
+    fn derive_state(&self) -> ConnectionState {
+        if !self.udp {
+            ConnectionState::Disconnected
+        } else if self.code_running {
+            ConnectionState::CodeRunning
+        } else if self.tcp {
+            ConnectionState::TcpConnected
+        } else {
+            ConnectionState::UdpConnected
+        }
+    }
+}
+
 pub struct ConnectionManager {
     team: u32,
     use_usb: bool,
-    state: ConnectionState,
+    target_override: Option<IpAddr>,
+    ports: Ports,
+    resolver: Resolver,
+    clock: Arc<dyn Clock>,
+    status: ConnectionStatus,
+    status_tx: watch::Sender<ConnectionStatus>,
+    fms: watch::Receiver<Option<FmsControl>>,
+    settings: Option<watch::Receiver<LinkSettings>>,
     target_addr: Option<SocketAddr>,
-    #[allow(dead_code)]
     sequence: u16,
-    #[allow(dead_code)]
     last_received: Option<Instant>,
-    trip_times: Vec<f64>,
+    last_rio_sequence: Option<u16>,
+    date_requested: bool,
+    // Requests not yet carried by a sent packet
+    pending_request: RequestFlags,
+    timezone: String,
+    pending_sends: VecDeque<(u16, Instant)>,
+    trip_times: VecDeque<f64>,
     lost_packets: u32,
-    #[allow(dead_code)]
     sent_count: u32,
-    #[allow(dead_code)]
     received_count: u32,
 }
 
@@ -38,11 +212,23 @@ impl ConnectionManager {
         Self {
             team,
             use_usb: false,
-            state: ConnectionState::Disconnected,
+            target_override: None,
+            ports: Ports::default(),
+            resolver: Resolver::new(),
+            clock: Arc::new(SystemClock),
+            status: ConnectionStatus::default(),
+            status_tx: watch::channel(ConnectionStatus::default()).0,
+            fms: watch::channel(None).1,
+            settings: None,
             target_addr: None,
             sequence: 0,
             last_received: None,
-            trip_times: Vec::new(),
+            last_rio_sequence: None,
+            date_requested: false,
+            pending_request: RequestFlags::default(),
+            timezone: String::new(),
+            pending_sends: VecDeque::with_capacity(MAX_PENDING_SENDS),
+            trip_times: VecDeque::with_capacity(TRIP_TIME_WINDOW),
             lost_packets: 0,
             sent_count: 0,
             received_count: 0,
@@ -52,8 +238,8 @@ impl ConnectionManager {
     pub fn set_team(&mut self, team: u32) {
         if self.team != team {
             self.team = team;
-            self.state = ConnectionState::Disconnected;
             self.target_addr = None;
+            self.set_link(false, false, false);
         }
     }
 
@@ -61,8 +247,94 @@ impl ConnectionManager {
         self.use_usb = usb;
     }
 
+    pub fn set_target_override(&mut self, addr: Option<IpAddr>) {
+        self.target_override = addr;
+    }
+
+    pub fn set_ports(&mut self, ports: Ports) {
+        self.ports = ports;
+    }
+
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    // Follows the team, addressing and ports from a DriverStation. run()
+    // reconnects whenever they change.
+    pub fn set_settings(&mut self, settings: watch::Receiver<LinkSettings>) {
+        self.apply_settings(*settings.borrow());
+        self.settings = Some(settings);
+    }
+
+    fn apply_settings(&mut self, settings: LinkSettings) {
+        self.set_team(settings.team);
+        self.set_usb_mode(settings.use_usb);
+        self.set_target_override(settings.target_override);
+        self.set_ports(settings.ports);
+    }
+
+    pub fn ports(&self) -> Ports {
+        self.ports
+    }
+
+    // Field control from an FmsLink, applied to every packet while attached
+    pub fn set_fms(&mut self, fms: watch::Receiver<Option<FmsControl>>) {
+        self.fms = fms;
+    }
+
+    pub fn set_status_sender(&mut self, tx: watch::Sender<ConnectionStatus>) {
+        tx.send_replace(self.status);
+        self.status_tx = tx;
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status_tx.subscribe()
+    }
+
     pub fn state(&self) -> ConnectionState {
-        self.state
+        self.status.state
+    }
+
+    pub fn status(&self) -> ConnectionStatus {
+        self.status
+    }
+
+    fn publish(&mut self, status: ConnectionStatus) {
+        if status != self.status {
+            if status.state != self.status.state {
+                log::info!(
+                    "Connection state: {} -> {}",
+                    self.status.state,
+                    status.state
+                );
+            }
+            self.status = status;
+            self.status_tx.send_replace(status);
+        }
+    }
+
+    fn set_link(&mut self, udp: bool, tcp: bool, code_running: bool) {
+        let mut status = ConnectionStatus {
+            udp,
+            tcp,
+            code_running: udp && code_running,
+            target: self.target_addr,
+            ..self.status
+        };
+        status.state = status.derive_state();
+        self.publish(status);
+    }
+
+    fn set_udp(&mut self, udp: bool, code_running: bool) {
+        self.set_link(udp, self.status.tcp, code_running);
+    }
+
+    fn set_tcp(&mut self, tcp: bool) {
+        self.set_link(self.status.udp, tcp, self.status.code_running);
+    }
+
+    fn udp_timed_out(&self, since: Instant, now: Instant) -> bool {
+        now.duration_since(self.last_received.unwrap_or(since)) > RECEIVE_TIMEOUT
     }
 
     pub fn trip_time_ms(&self) -> f64 {
@@ -77,86 +349,153 @@ impl ConnectionManager {
         self.lost_packets
     }
 
+    pub fn link_stats(&self) -> LinkStats {
+        LinkStats {
+            trip_time_ms: self.trip_time_ms(),
+            lost_packets: self.lost_packets,
+            sent_count: self.sent_count,
+            received_count: self.received_count,
+        }
+    }
+
+    fn reset_link_stats(&mut self) {
+        self.sequence = 0;
+        self.last_received = None;
+        self.last_rio_sequence = None;
+        self.date_requested = false;
+        self.pending_sends.clear();
+        self.trip_times.clear();
+        self.lost_packets = 0;
+        self.sent_count = 0;
+        self.received_count = 0;
+    }
+
+    fn record_sent(&mut self, sequence: u16, at: Instant) {
+        if self.pending_sends.len() >= MAX_PENDING_SENDS {
+            self.pending_sends.pop_front();
+        }
+        self.pending_sends.push_back((sequence, at));
+        self.sent_count = self.sent_count.wrapping_add(1);
+    }
+
+    fn record_received(&mut self, sequence: u16, at: Instant) {
+        self.received_count = self.received_count.wrapping_add(1);
+        self.last_received = Some(at);
+
+        // The roboRIO echoes the sequence number of the last packet it got,
+        // so a forward jump of more than one means packets were dropped.
+        if let Some(last) = self.last_rio_sequence {
+            let gap = sequence.wrapping_sub(last);
+            if gap == 0 || gap >= 0x8000 {
+                // Duplicate or reordered reply
+                return;
+            }
+            self.lost_packets = self.lost_packets.saturating_add(gap as u32 - 1);
+        }
+        self.last_rio_sequence = Some(sequence);
+
+        if let Some(pos) = self.pending_sends.iter().position(|(s, _)| *s == sequence) {
+            let (_, sent_at) = self.pending_sends[pos];
+            // Anything sent before the matched packet will never be answered
+            self.pending_sends.drain(..=pos);
+
+            if self.trip_times.len() >= TRIP_TIME_WINDOW {
+                self.trip_times.pop_front();
+            }
+            self.trip_times
+                .push_back(at.duration_since(sent_at).as_secs_f64() * 1000.0);
+        }
+    }
+
     pub fn team_to_ip(team: u32) -> IpAddr {
-        let te = (team / 100) as u8;
-        let am = (team % 100) as u8;
-        IpAddr::V4(Ipv4Addr::new(10, te, am, 2))
+        discovery::team_to_ip(team)
     }
 
-    pub async fn resolve_address(&mut self) -> SocketAddr {
-        self.state = ConnectionState::Resolving;
+    // The DS clears a reboot or restart request right after queueing it, so
+    // a newer control update before the next tick would drop it. Requests
+    // stay latched until a packet carrying them has been sent.
+    pub(crate) fn latch_request(&mut self, request: RequestFlags) {
+        self.pending_request.reboot_roborio |= request.reboot_roborio;
+        self.pending_request.restart_code |= request.restart_code;
+    }
 
-        // Try USB mode first if enabled
-        if self.use_usb {
-            let usb_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(172, 22, 11, 2)), 1110);
-            log::info!("Using USB address: {}", usb_addr);
-            self.target_addr = Some(usb_addr);
-            return usb_addr;
+    // The next control packet, with the field's control applied when one is
+    // attached
+    pub(crate) fn control_packet(
+        &self,
+        latest: &ControlUpdate,
+        fms_control: Option<&FmsControl>,
+    ) -> Vec<u8> {
+        let (control, _, joysticks, alliance, match_time) = latest;
+        let (control, alliance, match_time) =
+            fms::apply_fms(*control, *alliance, *match_time, fms_control);
+        self.build_packet(
+            &control,
+            &self.pending_request,
+            &alliance,
+            joysticks,
+            match_time,
+        )
+    }
+
+    fn build_packet(
+        &self,
+        control: &ControlFlags,
+        request: &RequestFlags,
+        alliance: &Alliance,
+        joysticks: &[JoystickData],
+        match_time: Option<f32>,
+    ) -> Vec<u8> {
+        let mut packet =
+            outgoing::build_ds_packet(self.sequence, control, request, alliance, joysticks);
+
+        if let Some(remaining) = match_time {
+            outgoing::append_countdown_tag(&mut packet, remaining);
+        }
+
+        // Keep answering until the roboRIO clears request_date
+        if self.date_requested {
+            outgoing::append_datetime_tag(&mut packet);
+            outgoing::append_timezone_tag(&mut packet, &self.timezone);
         }
 
-        // Try mDNS resolution
-        let mdns_hostname = format!("roboRIO-{}-FRC.local", self.team);
-        log::info!("Attempting mDNS lookup for {}", mdns_hostname);
+        packet
+    }
+
+    pub async fn resolve_address(&mut self) -> SocketAddr {
+        self.publish(ConnectionStatus {
+            state: ConnectionState::Resolving,
+            ..ConnectionStatus::default()
+        });
 
-        if let Some(addr) = self.try_mdns_lookup(&mdns_hostname).await {
-            log::info!("Resolved via mDNS: {}", addr);
+        // An explicit address (e.g. a simulator on loopback) skips discovery
+        if let Some(ip) = self.target_override {
+            let addr = SocketAddr::new(ip, self.ports.robot_udp);
+            log::info!("Using configured address: {}", addr);
             self.target_addr = Some(addr);
             return addr;
         }
 
-        // Fallback to static IP
-        let static_ip = Self::team_to_ip(self.team);
-        let static_addr = SocketAddr::new(static_ip, 1110);
-        log::info!("Using static IP fallback: {}", static_addr);
-        self.target_addr = Some(static_addr);
-        static_addr
-    }
-
-    async fn try_mdns_lookup(&self, _hostname: &str) -> Option<SocketAddr> {
-        // Try mDNS resolution with a 2-second timeout
-        let mdns_result = timeout(Duration::from_secs(2), async {
-            // Create mDNS service discovery
-            let mdns = mdns_sd::ServiceDaemon::new().ok()?;
-
-            // Browse for the roboRIO service
-            let service_type = "_ni._tcp.local.";
-            let receiver = mdns.browse(service_type).ok()?;
-
-            // Wait for service events with timeout
-            let browse_timeout = Duration::from_secs(2);
-            let start = Instant::now();
-
-            while start.elapsed() < browse_timeout {
-                if let Ok(Ok(mdns_sd::ServiceEvent::ServiceResolved(info))) =
-                    timeout(Duration::from_millis(100), receiver.recv_async()).await
-                {
-                    // Check if this is the roboRIO we're looking for
-                    if info.get_fullname().contains(&self.team.to_string()) {
-                        if let Some(addr) = info.get_addresses().iter().next() {
-                            return Some(SocketAddr::new(*addr, 1110));
-                        }
-                    }
-                }
-            }
-
-            None::<SocketAddr>
-        })
-        .await;
+        // USB mode pins the address instead of racing it
+        if self.use_usb {
+            let usb_addr = SocketAddr::new(discovery::USB_ADDRESS, self.ports.robot_udp);
+            log::info!("Using USB address: {}", usb_addr);
+            self.target_addr = Some(usb_addr);
+            return usb_addr;
+        }
 
-        mdns_result.ok().flatten()
+        let ip = self.resolver.resolve(self.team, self.ports.tcp).await;
+        let addr = SocketAddr::new(ip, self.ports.robot_udp);
+        self.target_addr = Some(addr);
+        addr
     }
 
     pub async fn run(
         &mut self,
-        mut control_rx: mpsc::UnboundedReceiver<(
-            ControlFlags,
-            RequestFlags,
-            Vec<JoystickData>,
-            Alliance,
-        )>,
-        packet_tx: mpsc::UnboundedSender<incoming::RioPacket>,
+        mut control_rx: mpsc::UnboundedReceiver<ControlUpdate>,
+        packet_tx: mpsc::UnboundedSender<(incoming::RioPacket, LinkStats)>,
         tcp_message_tx: mpsc::UnboundedSender<TcpMessage>,
-        mut tcp_outbound_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+        mut session_rx: mpsc::UnboundedReceiver<SessionUpdate>,
     ) {
         let mut reconnect_attempts = 0u32;
 
@@ -166,39 +505,57 @@ impl ConnectionManager {
             RequestFlags::default(),
             Vec::new(),
             Alliance::new(AllianceColor::Red, 1),
+            None,
         );
+        let mut session = TcpSession::default();
+        let mut fms_attached = false;
+        let mut settings = self.settings.clone();
 
         loop {
-            // Resolve address
-            let target = self.resolve_address().await;
+            if let Some(settings) = settings.as_mut() {
+                let latest = *settings.borrow_and_update();
+                self.apply_settings(latest);
+            }
+
+            // Resolve address, starting over if the team changes meanwhile
+            let target = tokio::select! {
+                target = self.resolve_address() => target,
+                _ = settings_changed(&mut settings) => {
+                    reconnect_attempts = 0;
+                    continue;
+                }
+            };
 
             // Bind UDP socket for receiving
-            let udp_socket = match UdpSocket::bind("0.0.0.0:1150").await {
+            let listen_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), self.ports.ds_udp);
+            let udp_socket = match UdpSocket::bind(listen_addr).await {
                 Ok(sock) => sock,
                 Err(e) => {
                     log::error!("Failed to bind UDP socket: {}", e);
-                    self.backoff_delay(reconnect_attempts).await;
-                    reconnect_attempts += 1;
+                    self.backoff_delay(&mut reconnect_attempts, &mut settings)
+                        .await;
                     continue;
                 }
             };
 
-            log::info!("UDP socket bound to 0.0.0.0:1150");
+            log::info!("UDP socket bound to {}", listen_addr);
 
             // Spawn UDP send task
             let send_socket = match UdpSocket::bind("0.0.0.0:0").await {
                 Ok(sock) => sock,
                 Err(e) => {
                     log::error!("Failed to bind send socket: {}", e);
-                    self.backoff_delay(reconnect_attempts).await;
-                    reconnect_attempts += 1;
+                    self.backoff_delay(&mut reconnect_attempts, &mut settings)
+                        .await;
                     continue;
                 }
             };
 
             let mut ticker = interval(Duration::from_millis(20));
-            let mut sequence = 0u16;
-            let receive_timeout = Duration::from_secs(1);
+            self.reset_link_stats();
+            self.set_link(false, false, false);
+            self.timezone = outgoing::system_timezone();
+            let started = self.clock.now();
             let mut buf = vec![0u8; 2048];
 
             // TCP connection state
@@ -206,50 +563,67 @@ impl ConnectionManager {
                 SocketAddr::V4(addr) => IpAddr::V4(*addr.ip()),
                 SocketAddr::V6(addr) => IpAddr::V6(*addr.ip()),
             };
-            let tcp_target = SocketAddr::new(target_ip, 1740);
+            let tcp_target = SocketAddr::new(target_ip, self.ports.tcp);
 
             // Try to establish TCP connection (non-blocking, optional)
             let mut tcp_stream: Option<TcpStream> = None;
             let mut tcp_reader = TcpFrameReader::new();
             let mut tcp_read_buf = vec![0u8; 4096];
-            let mut tcp_connect_attempt = Box::pin(timeout(
-                Duration::from_secs(3),
-                TcpStream::connect(tcp_target),
-            ));
+            let mut tcp_connect_attempt =
+                connect_tcp(self.clock.as_ref(), tcp_target, Duration::ZERO);
 
             // Main UDP send/receive loop
             let mut connection_active = true;
+            let mut reconnect_now = false;
 
             while connection_active {
                 tokio::select! {
                     _ = ticker.tick() => {
-                        // Send control packet
-                        let (control, request, joysticks, alliance) = &latest_control;
-                        let packet = outgoing::build_ds_packet(
-                            sequence,
-                            control,
-                            request,
-                            alliance,
-                            joysticks,
-                        );
-
-                        if let Err(e) = send_socket.send_to(&packet, target).await {
-                            log::warn!("UDP send error: {}", e);
+                        if self.udp_timed_out(started, self.clock.now()) {
+                            log::warn!("No UDP packet received for 1 second, disconnecting");
+                            connection_active = false;
+                            continue;
+                        }
+
+                        // Send control packet. The DS drops its own enable when
+                        // the field comes or goes, this covers the gap until
+                        // its next update.
+                        let fms_control = *self.fms.borrow();
+                        if fms_control.is_some() != fms_attached {
+                            fms_attached = fms_control.is_some();
+                            latest_control.0.enabled = false;
+                        }
+                        let sequence = self.sequence;
+                        let packet = self.control_packet(&latest_control, fms_control.as_ref());
+
+                        match send_socket.send_to(&packet, target).await {
+                            Ok(_) => {
+                                self.record_sent(sequence, self.clock.now());
+                                self.pending_request = RequestFlags::default();
+                            }
+                            Err(e) => log::warn!("UDP send error: {}", e),
                         }
 
-                        sequence = sequence.wrapping_add(1);
+                        self.sequence = sequence.wrapping_add(1);
                     }
 
                     Some(new_state) = control_rx.recv() => {
+                        self.latch_request(new_state.1);
                         latest_control = new_state;
                     }
 
-                    result = timeout(receive_timeout, udp_socket.recv_from(&mut buf)) => {
+                    result = udp_socket.recv_from(&mut buf) => {
                         match result {
-                            Ok(Ok((len, _addr))) => {
+                            Ok((len, _addr)) => {
                                 match incoming::parse_rio_packet(&buf[..len]) {
                                     Ok(rio_packet) => {
-                                        if packet_tx.send(rio_packet).is_err() {
+                                        self.record_received(rio_packet.sequence, self.clock.now());
+                                        self.set_udp(true, !rio_packet.status.code_initializing);
+                                        if rio_packet.request_date && !self.date_requested {
+                                            log::info!("roboRIO requested date and time");
+                                        }
+                                        self.date_requested = rio_packet.request_date;
+                                        if packet_tx.send((rio_packet, self.link_stats())).is_err() {
                                             log::warn!("Failed to send parsed packet");
                                         }
                                     }
@@ -258,34 +632,36 @@ impl ConnectionManager {
                                     }
                                 }
                             }
-                            Ok(Err(e)) => {
+                            Err(e) => {
                                 log::warn!("UDP receive error: {}", e);
                             }
-                            Err(_) => {
-                                // Timeout - no packet received
-                                log::warn!("No UDP packet received for 1 second, disconnecting");
-                                connection_active = false;
-                            }
                         }
                     }
 
                     // TCP connection attempt
                     result = &mut tcp_connect_attempt, if tcp_stream.is_none() => {
                         match result {
-                            Ok(Ok(stream)) => {
+                            Ok(Ok(mut stream)) => {
                                 log::info!("TCP connected to {}", tcp_target);
-                                tcp_stream = Some(stream);
+                                match stream.write_all(&session.frames()).await {
+                                    Ok(()) => {
+                                        tcp_stream = Some(stream);
+                                        self.set_tcp(true);
+                                    }
+                                    Err(e) => {
+                                        log::warn!("TCP write error: {}", e);
+                                        tcp_connect_attempt = connect_tcp(self.clock.as_ref(), tcp_target, Duration::ZERO);
+                                    }
+                                }
                             }
                             Ok(Err(e)) => {
                                 log::warn!("TCP connection failed: {}", e);
-                                // Retry connection after a delay
-                                tokio::time::sleep(Duration::from_secs(2)).await;
-                                tcp_connect_attempt = Box::pin(timeout(Duration::from_secs(3), TcpStream::connect(tcp_target)));
+                                tcp_connect_attempt = connect_tcp(self.clock.as_ref(), tcp_target, TCP_RETRY_DELAY);
                             }
                             Err(_) => {
                                 log::warn!("TCP connection timed out");
                                 // Retry connection
-                                tcp_connect_attempt = Box::pin(timeout(Duration::from_secs(3), TcpStream::connect(tcp_target)));
+                                tcp_connect_attempt = connect_tcp(self.clock.as_ref(), tcp_target, Duration::ZERO);
                             }
                         }
                     }
@@ -301,7 +677,8 @@ impl ConnectionManager {
                             Ok(0) => {
                                 log::info!("TCP connection closed by remote");
                                 tcp_stream = None;
-                                tcp_connect_attempt = Box::pin(timeout(Duration::from_secs(3), TcpStream::connect(tcp_target)));
+                                self.set_tcp(false);
+                                tcp_connect_attempt = connect_tcp(self.clock.as_ref(), tcp_target, Duration::ZERO);
                             }
                             Ok(n) => {
                                 tcp_reader.feed(&tcp_read_buf[..n]);
@@ -316,42 +693,116 @@ impl ConnectionManager {
                             Err(e) => {
                                 log::warn!("TCP read error: {}", e);
                                 tcp_stream = None;
-                                tcp_connect_attempt = Box::pin(timeout(Duration::from_secs(3), TcpStream::connect(tcp_target)));
+                                self.set_tcp(false);
+                                tcp_connect_attempt = connect_tcp(self.clock.as_ref(), tcp_target, Duration::ZERO);
                             }
                         }
                     }
 
-                    Some(frame) = tcp_outbound_rx.recv() => {
+                    Some(update) = session_rx.recv() => {
+                        let frames = session.apply(update);
                         if let Some(stream) = tcp_stream.as_mut() {
-                            if let Err(e) = stream.write_all(&frame).await {
+                            if let Err(e) = stream.write_all(&frames).await {
                                 log::warn!("TCP write error: {}", e);
                                 tcp_stream = None;
-                                tcp_connect_attempt = Box::pin(timeout(Duration::from_secs(3), TcpStream::connect(tcp_target)));
+                                self.set_tcp(false);
+                                tcp_connect_attempt = connect_tcp(self.clock.as_ref(), tcp_target, Duration::ZERO);
                             }
                         }
                     }
+
+                    _ = settings_changed(&mut settings) => {
+                        log::info!("Link settings changed, reconnecting");
+                        connection_active = false;
+                        reconnect_now = true;
+                    }
                 }
             }
 
-            // Connection lost, update state and retry
-            self.state = ConnectionState::Disconnected;
+            // Connection lost, update state and retry. Joystick values are
+            // dropped so a reconnect never replays pre-disconnect input.
+            self.set_link(false, false, false);
+            latest_control.2.clear();
+            if reconnect_now {
+                reconnect_attempts = 0;
+                continue;
+            }
             log::info!("Connection lost, will retry after backoff");
 
-            self.backoff_delay(reconnect_attempts).await;
-            reconnect_attempts += 1;
+            self.backoff_delay(&mut reconnect_attempts, &mut settings)
+                .await;
         }
     }
 
-    async fn backoff_delay(&self, attempt: u32) {
-        let delay_ms = std::cmp::min(100 * 2u64.pow(attempt), 2000);
-        log::debug!("Backing off for {}ms (attempt {})", delay_ms, attempt);
-        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+    // A settings change cuts the wait short and restarts the backoff
+    async fn backoff_delay(
+        &self,
+        attempt: &mut u32,
+        settings: &mut Option<watch::Receiver<LinkSettings>>,
+    ) {
+        let delay = backoff_duration(*attempt);
+        log::debug!("Backing off for {:?} (attempt {})", delay, attempt);
+        tokio::select! {
+            _ = self.clock.sleep(delay) => *attempt += 1,
+            _ = settings_changed(settings) => *attempt = 0,
+        }
     }
 }
 
+// Never resolves without a settings channel or once its sender is gone
+async fn settings_changed(settings: &mut Option<watch::Receiver<LinkSettings>>) {
+    if let Some(settings) = settings {
+        if settings.changed().await.is_ok() {
+            return;
+        }
+    }
+    std::future::pending().await
+}
+
+type TcpConnect = Pin<Box<dyn Future<Output = Result<std::io::Result<TcpStream>, Elapsed>> + Send>>;
+
+// Connects once `delay` is up. The wait happens inside the select! branch, so
+// UDP keeps flowing while TCP is refused.
+fn connect_tcp(clock: &dyn Clock, target: SocketAddr, delay: Duration) -> TcpConnect {
+    let sleep = clock.sleep(delay);
+    Box::pin(async move {
+        sleep.await;
+        timeout(TCP_CONNECT_TIMEOUT, TcpStream::connect(target)).await
+    })
+}
+
+fn backoff_duration(attempt: u32) -> Duration {
+    BACKOFF_START
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(BACKOFF_MAX)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn test_session_replays_latest_state() {
+        let mut session = TcpSession::default();
+        let frame = session.apply(SessionUpdate::GameData("LRL".to_string()));
+        assert_eq!(frame, tcp::build_game_data_frame("LRL"));
+        session.apply(SessionUpdate::GameData("RLR".to_string()));
+
+        let info = MatchInfo {
+            match_type: MatchType::Qualification,
+            match_number: 7,
+            ..Default::default()
+        };
+        session.apply(SessionUpdate::MatchInfo(info.clone()));
+        let descriptors = vec![JoystickDescriptor::default(); 2];
+        session.apply(SessionUpdate::JoystickDescriptors(descriptors.clone()));
+
+        let mut expected = tcp::build_joystick_descriptor_frames(&descriptors);
+        expected.extend(tcp::build_match_info(&info));
+        expected.extend(tcp::build_game_data_frame("RLR"));
+        assert_eq!(session.frames(), expected);
+    }
 
     #[test]
     fn test_team_to_ip() {
@@ -375,6 +826,67 @@ mod tests {
         assert_eq!(cm.lost_packets(), 0);
     }
 
+    #[tokio::test]
+    async fn test_target_override_skips_discovery() {
+        let mut cm = ConnectionManager::new(1234);
+        cm.set_target_override(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        let addr = cm.resolve_address().await;
+        assert_eq!(addr.to_string(), "127.0.0.1:1110");
+    }
+
+    #[tokio::test]
+    async fn test_custom_ports() {
+        let mut cm = ConnectionManager::new(1234);
+        cm.set_usb_mode(true);
+        cm.set_ports(Ports {
+            robot_udp: 5110,
+            ds_udp: 5150,
+            tcp: 5740,
+        });
+        let addr = cm.resolve_address().await;
+        assert_eq!(addr.to_string(), "172.22.11.2:5110");
+
+        cm.set_target_override(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        let addr = cm.resolve_address().await;
+        assert_eq!(addr.to_string(), "127.0.0.1:5110");
+    }
+
+    #[test]
+    fn test_state_follows_link_health() {
+        let mut cm = ConnectionManager::new(1234);
+        let mut rx = cm.subscribe();
+
+        cm.set_udp(true, false);
+        assert_eq!(cm.state(), ConnectionState::UdpConnected);
+        cm.set_tcp(true);
+        assert_eq!(cm.state(), ConnectionState::TcpConnected);
+        cm.set_udp(true, true);
+        assert_eq!(cm.state(), ConnectionState::CodeRunning);
+
+        let status = *rx.borrow_and_update();
+        assert!(status.udp && status.tcp && status.code_running);
+
+        // Losing UDP drops everything that depends on it, TCP is independent
+        cm.set_udp(false, true);
+        let status = *rx.borrow_and_update();
+        assert_eq!(status.state, ConnectionState::Disconnected);
+        assert!(!status.code_running);
+        assert!(status.tcp);
+        assert!(!status.state.is_connected());
+    }
+
+    #[test]
+    fn test_udp_timeout_deadline() {
+        let mut cm = ConnectionManager::new(1234);
+        let t0 = Instant::now();
+        assert!(!cm.udp_timed_out(t0, t0 + Duration::from_millis(900)));
+        assert!(cm.udp_timed_out(t0, t0 + Duration::from_millis(1100)));
+
+        cm.record_received(1, t0 + Duration::from_millis(800));
+        assert!(!cm.udp_timed_out(t0, t0 + Duration::from_millis(1500)));
+        assert!(cm.udp_timed_out(t0, t0 + Duration::from_millis(1900)));
+    }
+
     #[test]
     fn test_set_team() {
         let mut cm = ConnectionManager::new(1234);
@@ -383,9 +895,99 @@ mod tests {
         assert_eq!(cm.state(), ConnectionState::Disconnected);
     }
 
+    #[test]
+    fn test_trip_time_matches_sequence() {
+        let mut cm = ConnectionManager::new(1234);
+        let t0 = Instant::now();
+        cm.record_sent(1, t0);
+        cm.record_sent(2, t0 + Duration::from_millis(20));
+        cm.record_received(1, t0 + Duration::from_millis(4));
+        cm.record_received(2, t0 + Duration::from_millis(26));
+        assert!((cm.trip_time_ms() - 5.0).abs() < 0.001);
+        assert_eq!(cm.lost_packets(), 0);
+
+        let stats = cm.link_stats();
+        assert_eq!(stats.sent_count, 2);
+        assert_eq!(stats.received_count, 2);
+    }
+
+    #[test]
+    fn test_trip_time_window_is_rolling() {
+        let mut cm = ConnectionManager::new(1234);
+        let t0 = Instant::now();
+        for seq in 0..TRIP_TIME_WINDOW as u16 {
+            cm.record_sent(seq, t0);
+            cm.record_received(seq, t0 + Duration::from_millis(100));
+        }
+        for seq in TRIP_TIME_WINDOW as u16..2 * TRIP_TIME_WINDOW as u16 {
+            cm.record_sent(seq, t0);
+            cm.record_received(seq, t0 + Duration::from_millis(2));
+        }
+        assert!((cm.trip_time_ms() - 2.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_lost_packets_from_sequence_gaps() {
+        let mut cm = ConnectionManager::new(1234);
+        let t0 = Instant::now();
+        cm.record_received(1, t0);
+        cm.record_received(2, t0);
+        cm.record_received(5, t0);
+        assert_eq!(cm.lost_packets(), 2);
+
+        // Duplicates and late replies are not counted
+        cm.record_received(5, t0);
+        cm.record_received(4, t0);
+        assert_eq!(cm.lost_packets(), 2);
+    }
+
+    #[test]
+    fn test_lost_packets_across_wraparound() {
+        let mut cm = ConnectionManager::new(1234);
+        let t0 = Instant::now();
+        cm.record_received(u16::MAX - 1, t0);
+        cm.record_received(1, t0);
+        assert_eq!(cm.lost_packets(), 2);
+    }
+
+    #[test]
+    fn test_date_tags_only_when_requested() {
+        let mut cm = ConnectionManager::new(1234);
+        cm.timezone = "America/Chicago".to_string();
+        let alliance = Alliance::new(AllianceColor::Red, 1);
+        let control = ControlFlags::default();
+        let request = RequestFlags::default();
+
+        let plain = cm.build_packet(&control, &request, &alliance, &[], None);
+        assert_eq!(plain.len(), 6);
+
+        cm.date_requested = true;
+        let packet = cm.build_packet(&control, &request, &alliance, &[], None);
+        assert_eq!(packet[6], 0x0b);
+        assert_eq!(packet[7], 0x0f);
+        assert_eq!(packet[18] as usize, 1 + "America/Chicago".len());
+        assert_eq!(packet[19], 0x10);
+        assert_eq!(&packet[20..], b"America/Chicago");
+    }
+
+    #[test]
+    fn test_countdown_tag_only_during_match() {
+        let cm = ConnectionManager::new(1234);
+        let alliance = Alliance::new(AllianceColor::Red, 1);
+        let control = ControlFlags::default();
+        let request = RequestFlags::default();
+
+        let plain = cm.build_packet(&control, &request, &alliance, &[], None);
+        assert_eq!(plain.len(), 6);
+
+        let packet = cm.build_packet(&control, &request, &alliance, &[], Some(15.0));
+        assert_eq!(&packet[6..8], &[0x05, 0x07]);
+        assert_eq!(&packet[8..], &15.0f32.to_be_bytes());
+    }
+
     #[test]
     fn test_backoff_capping() {
-        let backoff = |attempt: u32| -> u64 { std::cmp::min(100 * 2u64.pow(attempt), 2000) };
+        let backoff = |attempt: u32| backoff_duration(attempt).as_millis();
         assert_eq!(backoff(0), 100);
         assert_eq!(backoff(1), 200);
         assert_eq!(backoff(2), 400);
@@ -393,5 +995,60 @@ mod tests {
         assert_eq!(backoff(4), 1600);
         assert_eq!(backoff(5), 2000);
         assert_eq!(backoff(10), 2000);
+        assert_eq!(backoff(u32::MAX), 2000);
+    }
+
+    #[tokio::test]
+    async fn test_backoff_uses_clock() {
+        let clock = MockClock::new();
+        let mut cm = ConnectionManager::new(1234);
+        cm.set_clock(Arc::new(clock.clone()));
+
+        let t0 = clock.now();
+        let mut waited = Vec::new();
+        let mut attempt = 0;
+        for _ in 0..7 {
+            let before = clock.now();
+            cm.backoff_delay(&mut attempt, &mut None).await;
+            waited.push((clock.now() - before).as_millis());
+        }
+        assert_eq!(waited, vec![100, 200, 400, 800, 1600, 2000, 2000]);
+        assert_eq!(clock.now() - t0, Duration::from_millis(7100));
+        assert_eq!(attempt, 7);
+    }
+
+    #[tokio::test]
+    async fn test_settings_change_cuts_backoff_short() {
+        let cm = ConnectionManager::new(1234);
+        let (settings_tx, settings_rx) = watch::channel(LinkSettings {
+            team: 1234,
+            ..Default::default()
+        });
+        let mut settings = Some(settings_rx);
+
+        let mut attempt = 5;
+        let backoff = cm.backoff_delay(&mut attempt, &mut settings);
+        settings_tx.send_modify(|settings| settings.team = 5678);
+        timeout(Duration::from_millis(500), backoff)
+            .await
+            .expect("backoff should end on a settings change");
+        assert_eq!(attempt, 0);
+    }
+
+    #[test]
+    fn test_udp_timeout_with_mock_clock() {
+        let clock = MockClock::new();
+        let mut cm = ConnectionManager::new(1234);
+        cm.set_clock(Arc::new(clock.clone()));
+        let started = clock.now();
+
+        clock.advance(Duration::from_millis(1000));
+        assert!(!cm.udp_timed_out(started, cm.clock.now()));
+        clock.advance(Duration::from_millis(1));
+        assert!(cm.udp_timed_out(started, cm.clock.now()));
+
+        cm.record_received(1, cm.clock.now());
+        clock.advance(RECEIVE_TIMEOUT);
+        assert!(!cm.udp_timed_out(started, cm.clock.now()));
     }
 }
@@ -0,0 +1,277 @@
+use mdns_sd::{HostnameResolutionEvent, ServiceDaemon};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+use tokio::time::{timeout, Duration};
+
+pub const USB_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::new(172, 22, 11, 2));
+
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Candidate {
+    Cached,
+    Mdns,
+    Static,
+    Usb,
+}
+
+pub fn team_to_ip(team: u32) -> IpAddr {
+    let te = (team / 100) as u8;
+    let am = (team % 100) as u8;
+    IpAddr::V4(Ipv4Addr::new(10, te, am, 2))
+}
+
+pub fn mdns_hostname(team: u32) -> String {
+    format!("roboRIO-{}-FRC.local.", team)
+}
+
+// mDNS names are case-insensitive and may or may not carry the root dot
+fn hostname_matches(found: &str, wanted: &str) -> bool {
+    found
+        .trim_end_matches('.')
+        .eq_ignore_ascii_case(wanted.trim_end_matches('.'))
+}
+
+// Prefer IPv4, the roboRIO also advertises link-local IPv6 addresses that
+// are rarely routable from the DS.
+fn pick_address(addrs: impl IntoIterator<Item = IpAddr>) -> Option<IpAddr> {
+    let mut fallback = None;
+    for addr in addrs {
+        if addr.is_ipv4() {
+            return Some(addr);
+        }
+        fallback.get_or_insert(addr);
+    }
+    fallback
+}
+
+// A roboRIO accepts TCP connections on the DS port whether or not robot
+// code is running, so a completed handshake means the address is live.
+async fn probe(ip: IpAddr, port: u16) -> bool {
+    matches!(
+        timeout(
+            RESOLVE_TIMEOUT,
+            TcpStream::connect(SocketAddr::new(ip, port))
+        )
+        .await,
+        Ok(Ok(_))
+    )
+}
+
+async fn mdns_lookup(daemon: ServiceDaemon, hostname: String) -> Option<IpAddr> {
+    let receiver =
+        match daemon.resolve_hostname(&hostname, Some(RESOLVE_TIMEOUT.as_millis() as u64)) {
+            Ok(receiver) => receiver,
+            Err(e) => {
+                log::warn!("mDNS query for {} failed: {}", hostname, e);
+                return None;
+            }
+        };
+
+    let result = timeout(RESOLVE_TIMEOUT, async {
+        while let Ok(event) = receiver.recv_async().await {
+            match event {
+                HostnameResolutionEvent::AddressesFound(found, addrs)
+                    if hostname_matches(&found, &hostname) =>
+                {
+                    if let Some(addr) = pick_address(addrs) {
+                        return Some(addr);
+                    }
+                }
+                HostnameResolutionEvent::SearchTimeout(_)
+                | HostnameResolutionEvent::SearchStopped(_) => return None,
+                _ => {}
+            }
+        }
+        None
+    })
+    .await
+    .ok()
+    .flatten();
+
+    let _ = daemon.stop_resolve_hostname(&hostname);
+    result
+}
+
+// Probes every candidate at once and returns the first one that answers.
+pub async fn race(
+    candidates: Vec<(Candidate, IpAddr)>,
+    mdns: Option<(ServiceDaemon, String)>,
+    port: u16,
+) -> Option<(Candidate, IpAddr)> {
+    let mut probes = JoinSet::new();
+
+    for (candidate, ip) in candidates {
+        probes.spawn(async move { probe(ip, port).await.then_some((candidate, ip)) });
+    }
+
+    if let Some((daemon, hostname)) = mdns {
+        probes.spawn(async move {
+            let ip = mdns_lookup(daemon, hostname).await?;
+            log::debug!("mDNS resolved {}", ip);
+            probe(ip, port).await.then_some((Candidate::Mdns, ip))
+        });
+    }
+
+    let winner = timeout(RESOLVE_TIMEOUT * 2, async {
+        while let Some(result) = probes.join_next().await {
+            if let Ok(Some(winner)) = result {
+                return Some(winner);
+            }
+        }
+        None
+    })
+    .await
+    .ok()
+    .flatten();
+
+    // Stop the slower probes
+    probes.abort_all();
+    winner
+}
+
+pub struct Resolver {
+    daemon: Option<ServiceDaemon>,
+    cache: Option<(u32, IpAddr)>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self {
+            daemon: None,
+            cache: None,
+        }
+    }
+
+    pub fn cached(&self, team: u32) -> Option<IpAddr> {
+        self.cache
+            .filter(|(cached_team, _)| *cached_team == team)
+            .map(|(_, ip)| ip)
+    }
+
+    // The daemon owns a socket and a thread, so it is created once and
+    // reused for every reconnect.
+    fn daemon(&mut self) -> Option<ServiceDaemon> {
+        if self.daemon.is_none() {
+            match ServiceDaemon::new() {
+                Ok(daemon) => self.daemon = Some(daemon),
+                Err(e) => log::warn!("Failed to start mDNS daemon: {}", e),
+            }
+        }
+        self.daemon.clone()
+    }
+
+    pub async fn resolve(&mut self, team: u32, tcp_port: u16) -> IpAddr {
+        let static_ip = team_to_ip(team);
+        let mut candidates = vec![
+            (Candidate::Static, static_ip),
+            (Candidate::Usb, USB_ADDRESS),
+        ];
+        if let Some(ip) = self.cached(team) {
+            if ip != static_ip && ip != USB_ADDRESS {
+                candidates.insert(0, (Candidate::Cached, ip));
+            }
+        }
+
+        let hostname = mdns_hostname(team);
+        log::info!("Looking for roboRIO at {} and {:?}", hostname, candidates);
+        let mdns = self.daemon().map(|daemon| (daemon, hostname));
+
+        match race(candidates, mdns, tcp_port).await {
+            Some((candidate, ip)) => {
+                log::info!("Found roboRIO at {} ({:?})", ip, candidate);
+                self.cache = Some((team, ip));
+                ip
+            }
+            None => {
+                let ip = self.cached(team).unwrap_or(static_ip);
+                log::info!("No roboRIO answered, falling back to {}", ip);
+                ip
+            }
+        }
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Resolver {
+    fn drop(&mut self) {
+        if let Some(daemon) = self.daemon.take() {
+            let _ = daemon.shutdown();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_hostname_matching_is_exact() {
+        let wanted = mdns_hostname(254);
+        assert_eq!(wanted, "roboRIO-254-FRC.local.");
+        assert!(hostname_matches("roboRIO-254-FRC.local.", &wanted));
+        assert!(hostname_matches("roborio-254-frc.local", &wanted));
+        assert!(!hostname_matches("roboRIO-25-FRC.local.", &wanted));
+        assert!(!hostname_matches("roboRIO-2540-FRC.local.", &wanted));
+    }
+
+    #[test]
+    fn test_pick_address_prefers_ipv4() {
+        let v6: IpAddr = "fe80::1".parse().unwrap();
+        let v4: IpAddr = "10.2.54.2".parse().unwrap();
+        assert_eq!(pick_address([v6, v4]), Some(v4));
+        assert_eq!(pick_address([v6]), Some(v6));
+        assert_eq!(pick_address([]), None);
+    }
+
+    #[test]
+    fn test_cache_is_per_team() {
+        let mut resolver = Resolver::new();
+        resolver.cache = Some((254, "10.2.54.7".parse().unwrap()));
+        assert_eq!(resolver.cached(254), Some("10.2.54.7".parse().unwrap()));
+        assert_eq!(resolver.cached(25), None);
+    }
+
+    #[tokio::test]
+    async fn test_race_picks_responsive_candidate() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // 127.0.0.2 is loopback too but nothing listens there, so it refuses
+        let winner = race(
+            vec![
+                (Candidate::Static, "127.0.0.2".parse().unwrap()),
+                (Candidate::Cached, IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            ],
+            None,
+            port,
+        )
+        .await;
+        assert_eq!(
+            winner,
+            Some((Candidate::Cached, IpAddr::V4(Ipv4Addr::LOCALHOST)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_race_without_responses() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let winner = race(
+            vec![(Candidate::Static, IpAddr::V4(Ipv4Addr::LOCALHOST))],
+            None,
+            port,
+        )
+        .await;
+        assert_eq!(winner, None);
+    }
+}
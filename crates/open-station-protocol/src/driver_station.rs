@@ -1,39 +1,66 @@
-use crate::connection::{ConnectionManager, ConnectionState};
+use crate::clock::{Clock, SystemClock};
+use crate::connection::{
+    ConnectionManager, ConnectionState, ConnectionStatus, ControlUpdate, LinkSettings, LinkStats,
+    Ports, SessionUpdate,
+};
+use crate::fms::{FmsLink, FmsPorts};
+use crate::packet::fms::FmsControl;
 use crate::packet::incoming::RioPacket;
-use crate::packet::tcp;
 use crate::types::*;
-use tokio::sync::{mpsc, watch};
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::time::{timeout, Duration};
+
+const STATE_TIMEOUT: Duration = Duration::from_secs(1);
+// About two seconds of packets for a slow reader before it starts skipping
+const PACKET_BACKLOG: usize = 100;
 
 struct DsChannels {
-    control_rx: mpsc::UnboundedReceiver<(ControlFlags, RequestFlags, Vec<JoystickData>, Alliance)>,
-    tcp_outbound_rx: mpsc::UnboundedReceiver<Vec<u8>>,
-    packet_tx: mpsc::UnboundedSender<RioPacket>,
+    control_rx: mpsc::UnboundedReceiver<ControlUpdate>,
+    session_rx: mpsc::UnboundedReceiver<SessionUpdate>,
+    packet_tx: mpsc::UnboundedSender<(RioPacket, LinkStats)>,
     tcp_message_tx: mpsc::UnboundedSender<TcpMessage>,
-    packet_rx: mpsc::UnboundedReceiver<RioPacket>,
+    packet_rx: mpsc::UnboundedReceiver<(RioPacket, LinkStats)>,
     tcp_message_rx: mpsc::UnboundedReceiver<TcpMessage>,
     state_tx: watch::Sender<RobotState>,
+    robot_packets_tx: broadcast::Sender<(RioPacket, LinkStats)>,
+    connection_tx: watch::Sender<ConnectionStatus>,
+    fms_tx: watch::Sender<Option<FmsControl>>,
     stdout_tx: mpsc::UnboundedSender<String>,
     messages_tx: mpsc::UnboundedSender<TcpMessage>,
 }
 
 pub struct DriverStation {
-    team: u32,
+    // Shared with the running link, which reconnects when it changes
+    link: watch::Sender<LinkSettings>,
     alliance: Alliance,
     control: ControlFlags,
     request: RequestFlags,
     joysticks: Vec<JoystickData>,
+    joystick_descriptors: Vec<JoystickDescriptor>,
+    match_time: Option<f32>,
+    match_info: MatchInfo,
     game_data: String,
-    use_usb: bool,
+    fms_ports: Option<FmsPorts>,
+    fms: watch::Receiver<Option<FmsControl>>,
+    fms_attached: bool,
+    clock: Arc<dyn Clock>,
     estopped: bool,
 
-    control_tx: mpsc::UnboundedSender<(ControlFlags, RequestFlags, Vec<JoystickData>, Alliance)>,
-    tcp_outbound_tx: mpsc::UnboundedSender<Vec<u8>>,
+    control_tx: mpsc::UnboundedSender<ControlUpdate>,
+    session_tx: mpsc::UnboundedSender<SessionUpdate>,
 
     channels: Option<DsChannels>,
 }
 
 pub struct DsReceiver {
     pub state: watch::Receiver<RobotState>,
+    pub connection: watch::Receiver<ConnectionStatus>,
+    // Latest field control, None while no FMS is attached
+    pub fms: watch::Receiver<Option<FmsControl>>,
+    // Every status packet from the robot with the link stats at that point
+    pub packets: broadcast::Receiver<(RioPacket, LinkStats)>,
     pub stdout: mpsc::UnboundedReceiver<String>,
     pub messages: mpsc::UnboundedReceiver<TcpMessage>,
 }
@@ -42,7 +69,7 @@ impl DriverStation {
     pub fn new(team: u32, alliance: Alliance) -> (Self, DsReceiver) {
         let (control_tx, control_rx) = mpsc::unbounded_channel();
 
-        let (tcp_outbound_tx, tcp_outbound_rx) = mpsc::unbounded_channel();
+        let (session_tx, session_rx) = mpsc::unbounded_channel();
 
         let (packet_tx, packet_rx) = mpsc::unbounded_channel();
 
@@ -66,38 +93,60 @@ impl DriverStation {
         };
         let (state_tx, state_rx) = watch::channel(initial_state);
 
+        let (connection_tx, connection_rx) = watch::channel(ConnectionStatus::default());
+
+        let (fms_tx, fms_rx) = watch::channel(None);
+
+        let (robot_packets_tx, robot_packets_rx) = broadcast::channel(PACKET_BACKLOG);
+
         let (stdout_tx, stdout_rx) = mpsc::unbounded_channel();
 
         let (messages_tx, messages_rx) = mpsc::unbounded_channel();
 
         let channels = DsChannels {
             control_rx,
-            tcp_outbound_rx,
+            session_rx,
             packet_tx,
             tcp_message_tx,
             packet_rx,
             tcp_message_rx,
             state_tx,
+            robot_packets_tx,
+            connection_tx,
+            fms_tx,
             stdout_tx,
             messages_tx,
         };
 
         let ds = DriverStation {
-            team,
+            link: watch::channel(LinkSettings {
+                team,
+                ..Default::default()
+            })
+            .0,
             alliance,
             control: ControlFlags::default(),
             request: RequestFlags::default(),
             joysticks: Vec::new(),
+            joystick_descriptors: Vec::new(),
+            match_time: None,
+            match_info: MatchInfo::default(),
             game_data: String::new(),
-            use_usb: false,
+            fms_ports: None,
+            fms: fms_rx.clone(),
+            fms_attached: false,
+            clock: Arc::new(SystemClock),
             estopped: false,
             control_tx,
-            tcp_outbound_tx,
+            session_tx,
             channels: Some(channels),
         };
 
         let receiver = DsReceiver {
             state: state_rx,
+            connection: connection_rx,
+            fms: fms_rx,
+            packets: robot_packets_rx,
             stdout: stdout_rx,
             messages: messages_rx,
         };
@@ -105,18 +154,27 @@ impl DriverStation {
         (ds, receiver)
     }
 
-    pub async fn run(&mut self) {
-        let mut channels = self.channels.take().expect("run() called more than once");
+    pub fn start(&mut self) {
+        let mut channels = self.channels.take().expect("start() called more than once");
 
-        let _ = self.control_tx.send((
-            self.control,
-            self.request,
-            self.joysticks.clone(),
-            self.alliance,
-        ));
+        self.send_control();
 
-        let mut conn_mgr = ConnectionManager::new(self.team);
-        conn_mgr.set_usb_mode(self.use_usb);
+        let mut conn_mgr = ConnectionManager::new(self.link.borrow().team);
+        conn_mgr.set_settings(self.link.subscribe());
+        conn_mgr.set_clock(self.clock.clone());
+        conn_mgr.set_status_sender(channels.connection_tx.clone());
+        conn_mgr.set_fms(channels.fms_tx.subscribe());
+        let mut connection_rx = conn_mgr.subscribe();
+
+        if let Some(ports) = self.fms_ports {
+            let link = FmsLink::new(
+                self.link.subscribe(),
+                ports,
+                self.clock.clone(),
+                channels.fms_tx,
+            );
+            tokio::spawn(link.run(channels.state_tx.subscribe()));
+        }
 
         tokio::spawn(async move {
             conn_mgr
@@ -124,12 +182,13 @@ impl DriverStation {
                     channels.control_rx,
                     channels.packet_tx,
                     channels.tcp_message_tx,
-                    channels.tcp_outbound_rx,
+                    channels.session_rx,
                 )
                 .await;
         });
 
         let state_tx = channels.state_tx.clone();
+        let robot_packets_tx = channels.robot_packets_tx.clone();
         tokio::spawn(async move {
             let mut current_state = RobotState {
                 connected: false,
@@ -148,9 +207,37 @@ impl DriverStation {
                 lost_packets: 0,
             };
 
-            while let Some(packet) = channels.packet_rx.recv().await {
-                update_robot_state(&mut current_state, &packet, ConnectionState::Connected);
-                let _ = state_tx.send(current_state.clone());
+            loop {
+                let received = tokio::select! {
+                    received = timeout(STATE_TIMEOUT, channels.packet_rx.recv()) => received,
+                    Ok(()) = connection_rx.changed() => {
+                        // The manager saw the link drop before our own timeout did
+                        if !connection_rx.borrow_and_update().state.is_connected() && current_state.connected {
+                            mark_disconnected(&mut current_state);
+                            let _ = state_tx.send(current_state.clone());
+                        }
+                        continue;
+                    }
+                };
+
+                match received {
+                    Ok(Some((packet, stats))) => {
+                        let conn_state = connection_rx.borrow().state;
+                        update_robot_state(&mut current_state, &packet, conn_state);
+                        current_state.trip_time_ms = stats.trip_time_ms;
+                        current_state.lost_packets = stats.lost_packets;
+                        let _ = state_tx.send(current_state.clone());
+                        let _ = robot_packets_tx.send((packet, stats));
+                    }
+                    Ok(None) => break,
+                    Err(_) => {
+                        // No packets for a while, the robot is gone
+                        if current_state.connected {
+                            mark_disconnected(&mut current_state);
+                            let _ = state_tx.send(current_state.clone());
+                        }
+                    }
+                }
             }
         });
 
@@ -168,6 +255,10 @@ impl DriverStation {
                 }
             }
         });
+    }
+
+    pub async fn run(&mut self) {
+        self.start();
 
         loop {
             tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
@@ -199,7 +290,7 @@ impl DriverStation {
     }
 
     pub fn set_team(&mut self, team: u32) {
-        self.team = team;
+        self.update_link(|link| link.team = team);
     }
 
     pub fn set_alliance(&mut self, alliance: Alliance) {
@@ -212,15 +303,84 @@ impl DriverStation {
         self.send_control();
     }
 
+    pub fn set_joystick_descriptors(&mut self, descriptors: Vec<JoystickDescriptor>) {
+        if self.joystick_descriptors != descriptors {
+            self.joystick_descriptors = descriptors.clone();
+            let _ = self
+                .session_tx
+                .send(SessionUpdate::JoystickDescriptors(descriptors));
+        }
+    }
+
+    // Remaining time in the current match period, None outside a match so
+    // the robot reads -1
+    pub fn set_match_time(&mut self, remaining_secs: Option<f32>) {
+        if self.match_time != remaining_secs {
+            self.match_time = remaining_secs;
+            self.send_control();
+        }
+    }
+
+    pub fn match_time(&self) -> Option<f32> {
+        self.match_time
+    }
+
+    // Like descriptors and game data, resent whenever the TCP stream comes up
+    pub fn set_match_info(&mut self, info: MatchInfo) {
+        if self.match_info != info {
+            self.match_info = info.clone();
+            let _ = self.session_tx.send(SessionUpdate::MatchInfo(info));
+        }
+    }
+
+    pub fn match_info(&self) -> &MatchInfo {
+        &self.match_info
+    }
+
     pub fn set_game_data(&mut self, data: String) {
-        self.game_data = data.clone();
-        // Send game data frame via TCP
-        let frame = tcp::build_game_data_frame(&data);
-        let _ = self.tcp_outbound_tx.send(frame);
+        if self.game_data != data {
+            self.game_data = data.clone();
+            let _ = self.session_tx.send(SessionUpdate::GameData(data));
+        }
+    }
+
+    pub fn game_data(&self) -> &str {
+        &self.game_data
     }
 
     pub fn set_usb_mode(&mut self, usb: bool) {
-        self.use_usb = usb;
+        self.update_link(|link| link.use_usb = usb);
+    }
+
+    pub fn set_target_override(&mut self, addr: Option<IpAddr>) {
+        self.update_link(|link| link.target_override = addr);
+    }
+
+    pub fn set_ports(&mut self, ports: Ports) {
+        self.update_link(|link| link.ports = ports);
+    }
+
+    pub fn link_settings(&self) -> LinkSettings {
+        *self.link.borrow()
+    }
+
+    // Only wakes the running link when something actually changed
+    fn update_link(&self, update: impl FnOnce(&mut LinkSettings)) {
+        self.link.send_if_modified(|link| {
+            let before = *link;
+            update(link);
+            *link != before
+        });
+    }
+
+    // Listens for a field when set, only read by start()
+    pub fn set_fms_ports(&mut self, ports: Option<FmsPorts>) {
+        self.fms_ports = ports;
+    }
+
+    // Only read by start()
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
     }
 
     pub fn reboot_roborio(&mut self) {
@@ -245,18 +405,31 @@ impl DriverStation {
         self.send_control();
     }
 
-    fn send_control(&self) {
+    pub fn fms_control(&self) -> Option<FmsControl> {
+        *self.fms.borrow()
+    }
+
+    fn send_control(&mut self) {
+        // A local enable never carries over the field attaching or leaving
+        if self.fms.has_changed().unwrap_or(false) {
+            let attached = self.fms.borrow_and_update().is_some();
+            if attached != self.fms_attached {
+                self.fms_attached = attached;
+                self.control.enabled = false;
+            }
+        }
         let _ = self.control_tx.send((
             self.control,
             self.request,
             self.joysticks.clone(),
             self.alliance,
+            self.match_time,
         ));
     }
 }
 
 fn update_robot_state(state: &mut RobotState, packet: &RioPacket, conn_state: ConnectionState) {
-    state.connected = conn_state != ConnectionState::Disconnected;
+    state.connected = conn_state.is_connected();
     state.code_running = !packet.status.code_initializing;
     state.voltage = packet.voltage;
     state.status = packet.status;
@@ -278,9 +451,19 @@ fn update_robot_state(state: &mut RobotState, packet: &RioPacket, conn_state: Co
     }
 }
 
+fn mark_disconnected(state: &mut RobotState) {
+    state.connected = false;
+    state.code_running = false;
+    state.voltage = BatteryVoltage { volts: 0.0 };
+    state.status.enabled = false;
+    state.status.brownout = false;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::packet::outgoing;
+    use std::net::SocketAddr;
 
     #[test]
     fn test_enable_disable() {
@@ -337,10 +520,194 @@ mod tests {
         assert_eq!(ds.joysticks[0].axes.len(), 3);
     }
 
+    #[test]
+    fn test_mark_disconnected_clears_live_fields() {
+        let mut state = RobotState {
+            connected: true,
+            code_running: true,
+            voltage: BatteryVoltage { volts: 12.3 },
+            status: StatusFlags::from_byte(0b0000_1100),
+            telemetry: TelemetryData::default(),
+            sequence: 42,
+            trip_time_ms: 3.0,
+            lost_packets: 1,
+        };
+        mark_disconnected(&mut state);
+        assert!(!state.connected);
+        assert!(!state.code_running);
+        assert_eq!(state.voltage.volts, 0.0);
+        assert!(!state.status.enabled);
+        assert!(!state.status.brownout);
+    }
+
+    #[test]
+    fn test_joysticks_sent_with_control_flags() {
+        let (mut ds, _rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+        let mut control_rx = ds.channels.take().unwrap().control_rx;
+        ds.set_mode(Mode::Autonomous);
+        ds.enable();
+        ds.set_joysticks(vec![JoystickData {
+            axes: vec![-128, 127],
+            buttons: vec![true],
+            povs: vec![-1],
+        }]);
+
+        let mut latest = None;
+        while let Ok(msg) = control_rx.try_recv() {
+            latest = Some(msg);
+        }
+        let (control, _, joysticks, _, match_time) = latest.expect("control update should be sent");
+        assert!(control.enabled);
+        assert_eq!(control.mode, Mode::Autonomous);
+        assert_eq!(joysticks.len(), 1);
+        assert_eq!(joysticks[0].axes, vec![-128, 127]);
+        assert_eq!(match_time, None);
+
+        ds.set_match_time(Some(42.0));
+        let (.., match_time) = control_rx.try_recv().unwrap();
+        assert_eq!(match_time, Some(42.0));
+        ds.set_match_time(Some(42.0));
+        assert!(control_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_reboot_survives_joystick_update() {
+        let (mut ds, _rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+        let mut control_rx = ds.channels.take().unwrap().control_rx;
+        let mut cm = ConnectionManager::new(1234);
+        ds.reboot_roborio();
+        ds.set_joysticks(vec![JoystickData::default()]);
+
+        // Both updates land before the next tick, as the poll loop does
+        let mut latest = None;
+        while let Ok(update) = control_rx.try_recv() {
+            cm.latch_request(update.1);
+            latest = Some(update);
+        }
+        let latest = latest.unwrap();
+        assert!(!latest.1.reboot_roborio);
+
+        let packet = outgoing::parse_ds_packet(&cm.control_packet(&latest, None)).unwrap();
+        assert!(packet.request.reboot_roborio);
+        assert!(!packet.request.restart_code);
+    }
+
+    #[test]
+    fn test_descriptors_sent_only_on_change() {
+        let (mut ds, _rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+        let mut session_rx = ds.channels.take().unwrap().session_rx;
+        let mut descriptors = vec![JoystickDescriptor::default(); 6];
+
+        ds.set_joystick_descriptors(descriptors.clone());
+        ds.set_joystick_descriptors(descriptors.clone());
+        assert_eq!(
+            session_rx.try_recv().unwrap(),
+            SessionUpdate::JoystickDescriptors(descriptors.clone())
+        );
+        assert!(session_rx.try_recv().is_err());
+
+        descriptors[0].name = "Gamepad".to_string();
+        ds.set_joystick_descriptors(descriptors.clone());
+        assert_eq!(
+            session_rx.try_recv().unwrap(),
+            SessionUpdate::JoystickDescriptors(descriptors)
+        );
+    }
+
+    #[test]
+    fn test_local_enable_dropped_when_fms_attaches() {
+        let (mut ds, _rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+        let channels = ds.channels.take().unwrap();
+        let mut control_rx = channels.control_rx;
+        ds.enable();
+        assert!(control_rx.try_recv().unwrap().0.enabled);
+
+        channels.fms_tx.send_replace(Some(FmsControl {
+            sequence: 0,
+            estop: false,
+            enabled: true,
+            mode: Mode::Teleop,
+            alliance: Alliance::new(AllianceColor::Blue, 1),
+            level: MatchType::Practice,
+            match_number: 1,
+            replay_number: 1,
+            remaining_secs: 150,
+        }));
+        ds.set_mode(Mode::Autonomous);
+        assert!(!control_rx.try_recv().unwrap().0.enabled);
+        assert!(ds.fms_control().is_some());
+
+        // Enabling again under the field is fine, it is dropped on detach too
+        ds.enable();
+        control_rx.try_recv().unwrap();
+        channels.fms_tx.send_replace(None);
+        ds.set_mode(Mode::Teleop);
+        assert!(!control_rx.try_recv().unwrap().0.enabled);
+    }
+
     #[test]
     fn test_game_data() {
         let (mut ds, _rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+        let mut session_rx = ds.channels.take().unwrap().session_rx;
+        ds.set_game_data("LRL".to_string());
         ds.set_game_data("LRL".to_string());
-        assert_eq!(ds.game_data, "LRL");
+        assert_eq!(ds.game_data(), "LRL");
+        assert_eq!(
+            session_rx.try_recv().unwrap(),
+            SessionUpdate::GameData("LRL".to_string())
+        );
+        assert!(session_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_match_info_sent_on_change() {
+        let (mut ds, _rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+        let info = MatchInfo {
+            event_name: "Scrimmage".to_string(),
+            match_type: MatchType::Practice,
+            match_number: 3,
+            replay_number: 1,
+        };
+        ds.set_match_info(info.clone());
+        ds.set_match_info(info.clone());
+        assert_eq!(ds.match_info(), &info);
+
+        let rx = &mut ds.channels.as_mut().unwrap().session_rx;
+        assert_eq!(rx.try_recv().unwrap(), SessionUpdate::MatchInfo(info));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_team_change_after_start_resolves_new_address() {
+        let (mut ds, mut rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+        let ports = Ports {
+            robot_udp: 41400,
+            ds_udp: 41401,
+            tcp: 41402,
+        };
+        ds.set_ports(ports);
+        ds.start();
+
+        let resolved = |team| {
+            let target = SocketAddr::new(ConnectionManager::team_to_ip(team), ports.robot_udp);
+            move |status: &ConnectionStatus| status.target == Some(target)
+        };
+
+        let first = timeout(
+            Duration::from_secs(10),
+            rx.connection.wait_for(resolved(1234)),
+        )
+        .await
+        .is_ok_and(|status| status.is_ok());
+        assert!(first, "the link should resolve team 1234");
+
+        ds.set_team(5678);
+        let second = timeout(
+            Duration::from_secs(10),
+            rx.connection.wait_for(resolved(5678)),
+        )
+        .await
+        .is_ok_and(|status| status.is_ok());
+        assert!(second, "the running link should resolve team 5678");
     }
 }
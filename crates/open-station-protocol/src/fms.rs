@@ -0,0 +1,261 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::net::UdpSocket;
+use tokio::sync::watch;
+use tokio::time::{interval, Duration};
+
+use crate::clock::Clock;
+use crate::connection::LinkSettings;
+use crate::packet::fms::{self, DsStatus, FmsControl};
+use crate::types::*;
+
+// The field is considered gone after this long without a control packet
+const FMS_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Standard FRC field ports, overridable like the robot ports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FmsPorts {
+    // Where the DS listens for control packets
+    pub ds_udp: u16,
+    // Where status replies go on the FMS
+    pub fms_udp: u16,
+}
+
+impl Default for FmsPorts {
+    fn default() -> Self {
+        Self {
+            ds_udp: 1120,
+            fms_udp: 1160,
+        }
+    }
+}
+
+// While the field is attached it decides enable, mode, alliance and match
+// time. A local E-Stop still stops the robot.
+pub fn apply_fms(
+    control: ControlFlags,
+    alliance: Alliance,
+    match_time: Option<f32>,
+    fms: Option<&FmsControl>,
+) -> (ControlFlags, Alliance, Option<f32>) {
+    match fms {
+        Some(fms) => (
+            ControlFlags {
+                estop: control.estop || fms.estop,
+                fms_connected: true,
+                enabled: fms.enabled && !fms.estop && !control.estop,
+                mode: fms.mode,
+            },
+            fms.alliance,
+            Some(fms.remaining_secs as f32),
+        ),
+        None => (
+            ControlFlags {
+                fms_connected: false,
+                ..control
+            },
+            alliance,
+            match_time,
+        ),
+    }
+}
+
+pub struct FmsLink {
+    // The team reported to the field follows the DS settings
+    settings: watch::Receiver<LinkSettings>,
+    ports: FmsPorts,
+    clock: Arc<dyn Clock>,
+    control_tx: watch::Sender<Option<FmsControl>>,
+    sequence: u16,
+    last_received: Option<Instant>,
+    // The field that attached, others are ignored until it times out
+    peer: Option<IpAddr>,
+}
+
+impl FmsLink {
+    pub fn new(
+        settings: watch::Receiver<LinkSettings>,
+        ports: FmsPorts,
+        clock: Arc<dyn Clock>,
+        control_tx: watch::Sender<Option<FmsControl>>,
+    ) -> Self {
+        Self {
+            settings,
+            ports,
+            clock,
+            control_tx,
+            sequence: 0,
+            last_received: None,
+            peer: None,
+        }
+    }
+
+    // Publishes the latest field control, None while no field is attached,
+    // and answers every control packet with the robot's status.
+    pub async fn run(mut self, robot_state: watch::Receiver<RobotState>) {
+        let listen_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), self.ports.ds_udp);
+        let socket = match UdpSocket::bind(listen_addr).await {
+            Ok(sock) => sock,
+            Err(e) => {
+                log::error!("Failed to bind FMS socket {}: {}", listen_addr, e);
+                return;
+            }
+        };
+        log::info!("Listening for FMS on {}", listen_addr);
+
+        let mut ticker = interval(Duration::from_millis(100));
+        let mut buf = vec![0u8; 256];
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    if self.timed_out(self.clock.now()) {
+                        log::warn!("No FMS packet for 1 second, detaching");
+                        self.last_received = None;
+                        self.peer = None;
+                        self.control_tx.send_replace(None);
+                    }
+                }
+
+                result = socket.recv_from(&mut buf) => {
+                    let (len, from) = match result {
+                        Ok(received) => received,
+                        Err(e) => {
+                            log::warn!("FMS receive error: {}", e);
+                            continue;
+                        }
+                    };
+                    if !self.accepts(from.ip()) {
+                        log::debug!("Ignoring FMS packet from {}", from.ip());
+                        continue;
+                    }
+                    let control = match fms::parse_fms_packet(&buf[..len]) {
+                        Ok(control) => control,
+                        Err(e) => {
+                            log::warn!("Failed to parse FMS packet: {}", e);
+                            continue;
+                        }
+                    };
+
+                    if self.peer.is_none() {
+                        log::info!("FMS attached from {}", from.ip());
+                        self.peer = Some(from.ip());
+                    }
+                    self.last_received = Some(self.clock.now());
+                    self.control_tx.send_replace(Some(control));
+
+                    let reply = fms::build_ds_status(&self.status(&robot_state.borrow()));
+                    let to = SocketAddr::new(from.ip(), self.ports.fms_udp);
+                    if let Err(e) = socket.send_to(&reply, to).await {
+                        log::warn!("FMS send error: {}", e);
+                    }
+                    self.sequence = self.sequence.wrapping_add(1);
+                }
+            }
+        }
+    }
+
+    fn accepts(&self, from: IpAddr) -> bool {
+        self.peer.is_none_or(|peer| peer == from)
+    }
+
+    fn timed_out(&self, now: Instant) -> bool {
+        self.last_received
+            .is_some_and(|at| now.saturating_duration_since(at) >= FMS_TIMEOUT)
+    }
+
+    fn status(&self, state: &RobotState) -> DsStatus {
+        DsStatus {
+            sequence: self.sequence,
+            estop: state.status.estop,
+            robot_comms: state.connected,
+            enabled: state.connected && state.status.enabled,
+            mode: state.status.mode,
+            team: self.settings.borrow().team as u16,
+            voltage: state.voltage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    fn field(enabled: bool, estop: bool) -> FmsControl {
+        FmsControl {
+            sequence: 1,
+            estop,
+            enabled,
+            mode: Mode::Autonomous,
+            alliance: Alliance::new(AllianceColor::Blue, 3),
+            level: MatchType::Qualification,
+            match_number: 4,
+            replay_number: 1,
+            remaining_secs: 12,
+        }
+    }
+
+    #[test]
+    fn test_fms_overrides_local_control() {
+        let local = ControlFlags {
+            enabled: false,
+            mode: Mode::Teleop,
+            ..Default::default()
+        };
+        let red1 = Alliance::new(AllianceColor::Red, 1);
+
+        let (control, alliance, match_time) =
+            apply_fms(local, red1, None, Some(&field(true, false)));
+        assert!(control.fms_connected);
+        assert!(control.enabled);
+        assert_eq!(control.mode, Mode::Autonomous);
+        assert_eq!(alliance, Alliance::new(AllianceColor::Blue, 3));
+        assert_eq!(match_time, Some(12.0));
+
+        let (control, alliance, match_time) = apply_fms(local, red1, Some(3.0), None);
+        assert_eq!(control, local);
+        assert_eq!(alliance, red1);
+        assert_eq!(match_time, Some(3.0));
+    }
+
+    #[test]
+    fn test_estop_wins_over_fms_enable() {
+        let local = ControlFlags {
+            estop: true,
+            ..Default::default()
+        };
+        let red1 = Alliance::new(AllianceColor::Red, 1);
+        let (control, ..) = apply_fms(local, red1, None, Some(&field(true, false)));
+        assert!(control.estop);
+        assert!(!control.enabled);
+
+        let (control, ..) = apply_fms(
+            ControlFlags::default(),
+            red1,
+            None,
+            Some(&field(true, true)),
+        );
+        assert!(control.estop);
+        assert!(!control.enabled);
+    }
+
+    #[test]
+    fn test_fms_timeout_with_mock_clock() {
+        let clock = MockClock::new();
+        let mut link = FmsLink::new(
+            watch::channel(LinkSettings::default()).1,
+            FmsPorts::default(),
+            Arc::new(clock.clone()),
+            watch::channel(None).0,
+        );
+        assert!(!link.timed_out(clock.now()));
+
+        link.last_received = Some(clock.now());
+        clock.advance(Duration::from_millis(999));
+        assert!(!link.timed_out(clock.now()));
+        clock.advance(Duration::from_millis(1));
+        assert!(link.timed_out(clock.now()));
+    }
+}
@@ -1,4 +1,7 @@
+pub mod clock;
 pub mod connection;
+pub mod discovery;
 pub mod driver_station;
+pub mod fms;
 pub mod packet;
 pub mod types;
@@ -0,0 +1,177 @@
+use crate::packet::incoming::PacketError;
+use crate::types::*;
+
+// The FMS sends control packets to each driver station and expects a status
+// packet back for every one. Neither side carries tags.
+pub const FMS_CONTROL_LEN: usize = 22;
+pub const DS_STATUS_LEN: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FmsControl {
+    pub sequence: u16,
+    pub estop: bool,
+    pub enabled: bool,
+    pub mode: Mode,
+    pub alliance: Alliance,
+    // Tournament level, same values as the match info frame
+    pub level: MatchType,
+    pub match_number: u16,
+    pub replay_number: u8,
+    pub remaining_secs: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DsStatus {
+    pub sequence: u16,
+    pub estop: bool,
+    pub robot_comms: bool,
+    pub enabled: bool,
+    pub mode: Mode,
+    pub team: u16,
+    pub voltage: BatteryVoltage,
+}
+
+pub fn parse_fms_packet(data: &[u8]) -> Result<FmsControl, PacketError> {
+    if data.len() < FMS_CONTROL_LEN {
+        return Err(PacketError::TooShort {
+            expected: FMS_CONTROL_LEN,
+            actual: data.len(),
+        });
+    }
+
+    let comm_version = data[2];
+    if comm_version != 0x00 {
+        return Err(PacketError::InvalidVersion(comm_version));
+    }
+
+    // Same bit layout as our own control byte, minus the FMS bit
+    let control = ControlFlags::from_byte(data[3]);
+    let alliance = Alliance::from_byte(data[5]).ok_or(PacketError::InvalidAlliance(data[5]))?;
+
+    // Bytes 10..20 are the field's date and time, which the DS ignores
+    Ok(FmsControl {
+        sequence: u16::from_be_bytes([data[0], data[1]]),
+        estop: control.estop,
+        enabled: control.enabled,
+        mode: control.mode,
+        alliance,
+        level: MatchType::from_byte(data[6]),
+        match_number: u16::from_be_bytes([data[7], data[8]]),
+        replay_number: data[9],
+        remaining_secs: u16::from_be_bytes([data[20], data[21]]),
+    })
+}
+
+pub fn build_ds_status(status: &DsStatus) -> Vec<u8> {
+    let mut flags = status.mode.to_bits();
+    if status.estop {
+        flags |= 1 << 7;
+    }
+    if status.robot_comms {
+        flags |= 1 << 5;
+    }
+    if status.enabled {
+        flags |= 1 << 2;
+    }
+    let (volts_hi, volts_lo) = status.voltage.to_bytes();
+
+    let mut buf = Vec::with_capacity(DS_STATUS_LEN);
+    buf.extend_from_slice(&status.sequence.to_be_bytes());
+    buf.push(0x00);
+    buf.push(flags);
+    buf.extend_from_slice(&status.team.to_be_bytes());
+    buf.push(volts_hi);
+    buf.push(volts_lo);
+    buf
+}
+
+pub fn parse_ds_status(data: &[u8]) -> Result<DsStatus, PacketError> {
+    if data.len() < DS_STATUS_LEN {
+        return Err(PacketError::TooShort {
+            expected: DS_STATUS_LEN,
+            actual: data.len(),
+        });
+    }
+
+    let comm_version = data[2];
+    if comm_version != 0x00 {
+        return Err(PacketError::InvalidVersion(comm_version));
+    }
+
+    let flags = data[3];
+    Ok(DsStatus {
+        sequence: u16::from_be_bytes([data[0], data[1]]),
+        estop: flags & (1 << 7) != 0,
+        robot_comms: flags & (1 << 5) != 0,
+        enabled: flags & (1 << 2) != 0,
+        mode: Mode::from_bits(flags).unwrap_or(Mode::Teleop),
+        team: u16::from_be_bytes([data[4], data[5]]),
+        voltage: BatteryVoltage::from_bytes(data[6], data[7]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fms_packet() {
+        let mut data = vec![0x01, 0x02, 0x00];
+        data.push(0b0000_0110); // enabled, autonomous
+        data.push(0x00); // request
+        data.push(0x04); // blue 2
+        data.push(0x02); // qualification
+        data.extend_from_slice(&12u16.to_be_bytes());
+        data.push(0x01); // first play
+        data.extend_from_slice(&[0; 10]);
+        data.extend_from_slice(&15u16.to_be_bytes());
+
+        let control = parse_fms_packet(&data).unwrap();
+        assert_eq!(control.sequence, 0x0102);
+        assert!(control.enabled);
+        assert!(!control.estop);
+        assert_eq!(control.mode, Mode::Autonomous);
+        assert_eq!(control.alliance, Alliance::new(AllianceColor::Blue, 2));
+        assert_eq!(control.level, MatchType::Qualification);
+        assert_eq!(control.match_number, 12);
+        assert_eq!(control.replay_number, 1);
+        assert_eq!(control.remaining_secs, 15);
+    }
+
+    #[test]
+    fn test_parse_fms_packet_errors() {
+        assert!(matches!(
+            parse_fms_packet(&[0; 10]),
+            Err(PacketError::TooShort { expected: 22, .. })
+        ));
+
+        let mut data = vec![0; FMS_CONTROL_LEN];
+        data[5] = 9;
+        assert!(matches!(
+            parse_fms_packet(&data),
+            Err(PacketError::InvalidAlliance(9))
+        ));
+        data[2] = 0x01;
+        assert!(matches!(
+            parse_fms_packet(&data),
+            Err(PacketError::InvalidVersion(1))
+        ));
+    }
+
+    #[test]
+    fn test_ds_status_round_trip() {
+        let status = DsStatus {
+            sequence: 7,
+            estop: false,
+            robot_comms: true,
+            enabled: true,
+            mode: Mode::Teleop,
+            team: 1234,
+            voltage: BatteryVoltage { volts: 12.5 },
+        };
+        let data = build_ds_status(&status);
+        assert_eq!(data.len(), DS_STATUS_LEN);
+        assert_eq!(data[3], 0b0010_0100);
+        assert_eq!(parse_ds_status(&data).unwrap(), status);
+    }
+}
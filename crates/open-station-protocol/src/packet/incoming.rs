@@ -9,6 +9,8 @@ pub enum PacketError {
     InvalidVersion(u8),
     #[error("unknown tag: 0x{0:02x}")]
     UnknownTag(u8),
+    #[error("invalid alliance station: {0}")]
+    InvalidAlliance(u8),
 }
 
 #[derive(Debug, Clone)]
@@ -1,3 +1,4 @@
+pub mod fms;
 pub mod incoming;
 pub mod outgoing;
 pub mod tcp;
@@ -1,5 +1,45 @@
+use crate::packet::incoming::PacketError;
 use crate::types::*;
 
+#[derive(Debug, Clone)]
+pub struct DsPacket {
+    pub sequence: u16,
+    pub control: ControlFlags,
+    pub request: RequestFlags,
+    pub alliance: Alliance,
+    pub tags: Vec<DsTag>,
+}
+
+#[derive(Debug, Clone)]
+pub enum DsTag {
+    Joystick(JoystickData),
+    DateTime {
+        microseconds: u32,
+        seconds: u8,
+        minutes: u8,
+        hours: u8,
+        day: u8,
+        month: u8,
+        year: u8,
+    },
+    Timezone(String),
+    // Seconds left in the current match period
+    Countdown(f32),
+    Unknown(u8, Vec<u8>),
+}
+
+impl DsPacket {
+    pub fn joysticks(&self) -> Vec<JoystickData> {
+        self.tags
+            .iter()
+            .filter_map(|tag| match tag {
+                DsTag::Joystick(js) => Some(js.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
 pub fn build_ds_packet(
     sequence: u16,
     control: &ControlFlags,
@@ -58,6 +98,107 @@ pub fn append_joystick_tag(buf: &mut Vec<u8>, joystick: &JoystickData) {
     }
 }
 
+pub fn parse_ds_packet(data: &[u8]) -> Result<DsPacket, PacketError> {
+    if data.len() < 6 {
+        return Err(PacketError::TooShort {
+            expected: 6,
+            actual: data.len(),
+        });
+    }
+
+    let sequence = u16::from_be_bytes([data[0], data[1]]);
+
+    let comm_version = data[2];
+    if comm_version != 0x01 {
+        return Err(PacketError::InvalidVersion(comm_version));
+    }
+
+    let control = ControlFlags::from_byte(data[3]);
+    let request = RequestFlags::from_byte(data[4]);
+    let alliance = Alliance::from_byte(data[5]).ok_or(PacketError::InvalidAlliance(data[5]))?;
+
+    let tags = parse_tags(&data[6..]);
+
+    Ok(DsPacket {
+        sequence,
+        control,
+        request,
+        alliance,
+        tags,
+    })
+}
+
+fn parse_tags(mut data: &[u8]) -> Vec<DsTag> {
+    let mut tags = Vec::new();
+
+    while data.len() >= 2 {
+        let size = data[0] as usize;
+        let tag_id = data[1];
+
+        if size == 0 || data.len() < 1 + size {
+            break;
+        }
+
+        let payload = &data[2..1 + size];
+
+        let tag = match tag_id {
+            0x0c => parse_joystick(payload),
+            0x0f if payload.len() >= 10 => DsTag::DateTime {
+                microseconds: u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]),
+                seconds: payload[4],
+                minutes: payload[5],
+                hours: payload[6],
+                day: payload[7],
+                month: payload[8],
+                year: payload[9],
+            },
+            0x10 => DsTag::Timezone(String::from_utf8_lossy(payload).into_owned()),
+            0x07 if payload.len() >= 4 => DsTag::Countdown(f32::from_be_bytes([
+                payload[0], payload[1], payload[2], payload[3],
+            ])),
+            _ => DsTag::Unknown(tag_id, payload.to_vec()),
+        };
+
+        tags.push(tag);
+        data = &data[1 + size..];
+    }
+
+    tags
+}
+
+fn parse_joystick(payload: &[u8]) -> DsTag {
+    let mut js = JoystickData::default();
+    let mut rest = payload;
+
+    let Some((&axis_count, tail)) = rest.split_first() else {
+        return DsTag::Joystick(js);
+    };
+    let axis_count = (axis_count as usize).min(tail.len());
+    js.axes = tail[..axis_count].iter().map(|&b| b as i8).collect();
+    rest = &tail[axis_count..];
+
+    let Some((&button_count, tail)) = rest.split_first() else {
+        return DsTag::Joystick(js);
+    };
+    let button_bytes = (button_count as usize).div_ceil(8).min(tail.len());
+    js.buttons = (0..button_count as usize)
+        .filter(|i| i / 8 < button_bytes)
+        .map(|i| tail[i / 8] & (1 << (i % 8)) != 0)
+        .collect();
+    rest = &tail[button_bytes..];
+
+    let Some((&pov_count, tail)) = rest.split_first() else {
+        return DsTag::Joystick(js);
+    };
+    js.povs = tail
+        .chunks_exact(2)
+        .take(pov_count as usize)
+        .map(|c| i16::from_be_bytes([c[0], c[1]]))
+        .collect();
+
+    DsTag::Joystick(js)
+}
+
 pub fn append_datetime_tag(buf: &mut Vec<u8>) {
     use chrono::Datelike;
     use chrono::Timelike;
@@ -96,6 +237,20 @@ pub fn append_timezone_tag(buf: &mut Vec<u8>, tz: &str) {
     buf.extend_from_slice(tz.as_bytes());
 }
 
+// Read back by robot code as DriverStation.getMatchTime()
+pub fn append_countdown_tag(buf: &mut Vec<u8>, remaining_secs: f32) {
+    buf.push(0x05);
+    buf.push(0x07);
+    buf.extend_from_slice(&remaining_secs.to_be_bytes());
+}
+
+pub fn system_timezone() -> String {
+    iana_time_zone::get_timezone().unwrap_or_else(|e| {
+        log::warn!("Could not determine system timezone, using UTC: {}", e);
+        "UTC".to_string()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,6 +319,105 @@ mod tests {
         assert_eq!(buf[4], 0b00000101);
     }
 
+    #[test]
+    fn test_datetime_tag() {
+        let mut buf = Vec::new();
+        append_datetime_tag(&mut buf);
+        assert_eq!(buf.len(), 12);
+        assert_eq!(buf[0], 0x0b);
+        assert_eq!(buf[1], 0x0f);
+        assert!(buf[6] < 60, "seconds out of range");
+        assert!(buf[10] < 12, "month should be zero based");
+    }
+
+    #[test]
+    fn test_parse_ds_packet_round_trip() {
+        let control = ControlFlags {
+            estop: false,
+            fms_connected: false,
+            enabled: true,
+            mode: Mode::Autonomous,
+        };
+        let request = RequestFlags {
+            reboot_roborio: false,
+            restart_code: true,
+        };
+        let js = JoystickData {
+            axes: vec![0, 127, -128],
+            buttons: vec![true, false, true, false, false, false, false, false, true],
+            povs: vec![270],
+        };
+        let mut packet = build_ds_packet(
+            0x0102,
+            &control,
+            &request,
+            &Alliance::new(AllianceColor::Blue, 2),
+            &[js.clone(), JoystickData::default()],
+        );
+        append_timezone_tag(&mut packet, "Europe/Berlin");
+
+        let parsed = parse_ds_packet(&packet).unwrap();
+        assert_eq!(parsed.sequence, 0x0102);
+        assert_eq!(parsed.control, control);
+        assert_eq!(parsed.request, request);
+        assert_eq!(parsed.alliance, Alliance::new(AllianceColor::Blue, 2));
+
+        let joysticks = parsed.joysticks();
+        assert_eq!(joysticks.len(), 2);
+        assert_eq!(joysticks[0].axes, js.axes);
+        assert_eq!(joysticks[0].buttons, js.buttons);
+        assert_eq!(joysticks[0].povs, js.povs);
+        assert!(joysticks[1].axes.is_empty());
+
+        match parsed.tags.last() {
+            Some(DsTag::Timezone(tz)) => assert_eq!(tz, "Europe/Berlin"),
+            other => panic!("expected Timezone tag, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_ds_packet_datetime() {
+        let mut packet = build_ds_packet(
+            1,
+            &ControlFlags::default(),
+            &RequestFlags::default(),
+            &Alliance::new(AllianceColor::Red, 1),
+            &[],
+        );
+        append_datetime_tag(&mut packet);
+        let parsed = parse_ds_packet(&packet).unwrap();
+        assert!(matches!(parsed.tags[0], DsTag::DateTime { .. }));
+    }
+
+    #[test]
+    fn test_parse_ds_packet_invalid_alliance() {
+        let data = [0x00, 0x01, 0x01, 0x00, 0x00, 0x09];
+        let err = parse_ds_packet(&data).unwrap_err();
+        assert!(err.to_string().contains("invalid alliance"));
+    }
+
+    #[test]
+    fn test_countdown_tag() {
+        let mut buf = Vec::new();
+        append_countdown_tag(&mut buf, 134.5);
+        assert_eq!(buf, vec![0x05, 0x07, 0x43, 0x06, 0x80, 0x00]);
+
+        let mut packet = build_ds_packet(
+            1,
+            &ControlFlags::default(),
+            &RequestFlags::default(),
+            &Alliance::new(AllianceColor::Red, 1),
+            &[JoystickData::default()],
+        );
+        append_countdown_tag(&mut packet, 12.25);
+        let parsed = parse_ds_packet(&packet).unwrap();
+        assert_eq!(parsed.joysticks().len(), 1);
+        match parsed.tags.last() {
+            Some(DsTag::Countdown(secs)) => assert_eq!(*secs, 12.25),
+            other => panic!("expected Countdown tag, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_timezone_tag() {
         let mut buf = Vec::new();
@@ -1,4 +1,4 @@
-use crate::types::TcpMessage;
+use crate::types::{JoystickDescriptor, MatchInfo, TcpMessage};
 
 pub struct TcpFrameReader {
     buffer: Vec<u8>,
@@ -165,34 +165,52 @@ pub fn build_game_data_frame(data: &str) -> Vec<u8> {
     encode_tcp_frame(0x0e, data.as_bytes())
 }
 
-pub fn build_joystick_descriptor_frame(
-    slot: u8,
-    name: &str,
-    axis_count: u8,
-    button_count: u8,
-    pov_count: u8,
-) -> Vec<u8> {
-    let mut payload = vec![slot, 0, 0, name.len() as u8];
-    payload.extend_from_slice(name.as_bytes());
-    payload.push(axis_count);
-    payload.push(button_count);
-    payload.push(pov_count);
+pub fn build_joystick_descriptor_frame(slot: u8, descriptor: &JoystickDescriptor) -> Vec<u8> {
+    let name = &descriptor.name.as_bytes()[..descriptor.name.len().min(255)];
+    let axis_types = &descriptor.axis_types[..descriptor.axis_types.len().min(255)];
+
+    let mut payload = vec![
+        slot,
+        descriptor.joystick_type.is_xbox() as u8,
+        descriptor.joystick_type.to_byte(),
+        name.len() as u8,
+    ];
+    payload.extend_from_slice(name);
+    payload.push(axis_types.len() as u8);
+    payload.extend_from_slice(axis_types);
+    payload.push(descriptor.button_count);
+    payload.push(descriptor.pov_count);
 
     encode_tcp_frame(0x02, &payload)
 }
 
+pub fn build_joystick_descriptor_frames(descriptors: &[JoystickDescriptor]) -> Vec<u8> {
+    let mut frames = Vec::new();
+    for (slot, descriptor) in descriptors.iter().take(6).enumerate() {
+        frames.extend(build_joystick_descriptor_frame(slot as u8, descriptor));
+    }
+    frames
+}
+
 pub fn build_match_info_frame(match_name: &str, match_type: u8) -> Vec<u8> {
+    let name = &match_name.as_bytes()[..match_name.len().min(255)];
+
     let mut payload = Vec::new();
-    payload.push(match_name.len() as u8);
-    payload.extend_from_slice(match_name.as_bytes());
+    payload.push(name.len() as u8);
+    payload.extend_from_slice(name);
     payload.push(match_type);
 
     encode_tcp_frame(0x07, &payload)
 }
 
+pub fn build_match_info(info: &MatchInfo) -> Vec<u8> {
+    build_match_info_frame(&info.name(), info.match_type.to_byte())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{JoystickType, MatchType};
 
     #[test]
     fn test_encode_decode_frame() {
@@ -269,10 +287,68 @@ mod tests {
         assert_eq!(&frame[3..], b"LRL");
     }
 
+    #[test]
+    fn test_match_info_frame() {
+        let info = MatchInfo {
+            event_name: "Week 0".to_string(),
+            match_type: MatchType::Qualification,
+            match_number: 12,
+            replay_number: 2,
+        };
+        let frame = build_match_info(&info);
+        let name = b"Week 0 Qualification 12 Replay 2";
+        assert_eq!(frame[2], 0x07);
+        assert_eq!(frame[3] as usize, name.len());
+        assert_eq!(&frame[4..4 + name.len()], name);
+        assert_eq!(frame[4 + name.len()], 2);
+
+        let long = build_match_info_frame(&"x".repeat(300), 1);
+        assert_eq!(long[3], 255);
+        assert_eq!(long.len(), 3 + 1 + 255 + 1);
+    }
+
     #[test]
     fn test_joystick_descriptor_frame() {
-        let frame = build_joystick_descriptor_frame(0, "Gamepad", 6, 12, 1);
+        let descriptor = JoystickDescriptor {
+            name: "Gamepad".to_string(),
+            joystick_type: JoystickType::HidGamepad,
+            axis_types: vec![0; 6],
+            button_count: 12,
+            pov_count: 1,
+        };
+        let frame = build_joystick_descriptor_frame(0, &descriptor);
         assert_eq!(frame[2], 0x02);
         assert_eq!(frame[3], 0);
     }
+
+    #[test]
+    fn test_joystick_descriptor_frame_layout() {
+        let descriptor = JoystickDescriptor {
+            name: "Xbox".to_string(),
+            joystick_type: JoystickType::XInputGamepad,
+            axis_types: vec![0, 1, 2],
+            button_count: 10,
+            pov_count: 1,
+        };
+        let frame = build_joystick_descriptor_frame(2, &descriptor);
+        assert_eq!(
+            frame,
+            vec![0x00, 0x0f, 0x02, 2, 1, 1, 4, b'X', b'b', b'o', b'x', 3, 0, 1, 2, 10, 1]
+        );
+    }
+
+    #[test]
+    fn test_joystick_descriptor_frames_cover_every_slot() {
+        let descriptors = vec![JoystickDescriptor::default(); 6];
+        let frames = build_joystick_descriptor_frames(&descriptors);
+        let mut reader = TcpFrameReader::new();
+        reader.feed(&frames);
+        for slot in 0..6 {
+            let (tag, payload) = reader.next_frame().unwrap();
+            assert_eq!(tag, 0x02);
+            assert_eq!(payload[0], slot);
+            assert_eq!(payload[2], 0xFF);
+        }
+        assert!(reader.next_frame().is_none());
+    }
 }
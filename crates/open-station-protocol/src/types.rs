@@ -130,6 +130,13 @@ impl RequestFlags {
         }
         byte
     }
+
+    pub fn from_byte(byte: u8) -> RequestFlags {
+        RequestFlags {
+            reboot_roborio: (byte >> 3) & 1 != 0,
+            restart_code: (byte >> 2) & 1 != 0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -142,6 +149,24 @@ pub struct StatusFlags {
 }
 
 impl StatusFlags {
+    pub fn to_byte(self) -> u8 {
+        let mut byte = 0u8;
+        if self.estop {
+            byte |= 1 << 7;
+        }
+        if self.code_initializing {
+            byte |= 1 << 4;
+        }
+        if self.brownout {
+            byte |= 1 << 3;
+        }
+        if self.enabled {
+            byte |= 1 << 2;
+        }
+        byte |= self.mode.to_bits();
+        byte
+    }
+
     pub fn from_byte(byte: u8) -> StatusFlags {
         StatusFlags {
             estop: (byte >> 7) & 1 != 0,
@@ -179,6 +204,123 @@ pub struct JoystickData {
     pub povs: Vec<i16>,
 }
 
+// WPILib HIDType values reported in joystick descriptors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JoystickType {
+    #[default]
+    Unknown,
+    XInputGamepad,
+    HidJoystick,
+    HidGamepad,
+}
+
+impl JoystickType {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            JoystickType::Unknown => 0xFF,
+            JoystickType::XInputGamepad => 1,
+            JoystickType::HidJoystick => 20,
+            JoystickType::HidGamepad => 21,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> JoystickType {
+        match byte {
+            1 => JoystickType::XInputGamepad,
+            20 => JoystickType::HidJoystick,
+            21 => JoystickType::HidGamepad,
+            _ => JoystickType::Unknown,
+        }
+    }
+
+    pub fn is_xbox(self) -> bool {
+        self == JoystickType::XInputGamepad
+    }
+}
+
+// WPILib AxisType values
+pub const AXIS_TYPE_X: u8 = 0;
+pub const AXIS_TYPE_Y: u8 = 1;
+pub const AXIS_TYPE_Z: u8 = 2;
+pub const AXIS_TYPE_TWIST: u8 = 3;
+pub const AXIS_TYPE_THROTTLE: u8 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct JoystickDescriptor {
+    pub name: String,
+    pub joystick_type: JoystickType,
+    pub axis_types: Vec<u8>,
+    pub button_count: u8,
+    pub pov_count: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchType {
+    #[default]
+    None,
+    Practice,
+    Qualification,
+    Elimination,
+}
+
+impl MatchType {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            MatchType::None => 0,
+            MatchType::Practice => 1,
+            MatchType::Qualification => 2,
+            MatchType::Elimination => 3,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> MatchType {
+        match byte {
+            1 => MatchType::Practice,
+            2 => MatchType::Qualification,
+            3 => MatchType::Elimination,
+            _ => MatchType::None,
+        }
+    }
+}
+
+impl fmt::Display for MatchType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchType::None => write!(f, "None"),
+            MatchType::Practice => write!(f, "Practice"),
+            MatchType::Qualification => write!(f, "Qualification"),
+            MatchType::Elimination => write!(f, "Elimination"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MatchInfo {
+    pub event_name: String,
+    pub match_type: MatchType,
+    pub match_number: u16,
+    pub replay_number: u8,
+}
+
+impl MatchInfo {
+    // The match frame only has room for a name and a type, so the number
+    // and replay ride along in the name, e.g. "Week 0 Qualification 12"
+    pub fn name(&self) -> String {
+        let mut parts = Vec::new();
+        let event = self.event_name.trim();
+        if !event.is_empty() {
+            parts.push(event.to_string());
+        }
+        if self.match_type != MatchType::None {
+            parts.push(format!("{} {}", self.match_type, self.match_number));
+            if self.replay_number > 1 {
+                parts.push(format!("Replay {}", self.replay_number));
+            }
+        }
+        parts.join(" ")
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct RumbleOutput {
     pub left: f32,
@@ -313,6 +455,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_status_flags_round_trip() {
+        for byte in [0b0000_0000, 0b1001_1110, 0b0000_1101, 0b0001_0010] {
+            assert_eq!(StatusFlags::from_byte(byte).to_byte(), byte);
+        }
+    }
+
     #[test]
     fn test_status_flags_brownout() {
         let flags = StatusFlags::from_byte(0b0000_1000);
@@ -330,6 +479,19 @@ mod tests {
             restart_code: true,
         };
         assert_eq!(flags.to_byte(), 0b0000_1100);
+        assert_eq!(RequestFlags::from_byte(0b0000_1100), flags);
+        assert_eq!(RequestFlags::from_byte(0), RequestFlags::default());
+    }
+
+    #[test]
+    fn test_joystick_type_encoding() {
+        assert_eq!(JoystickType::Unknown.to_byte(), 0xFF);
+        assert_eq!(JoystickType::XInputGamepad.to_byte(), 1);
+        assert_eq!(JoystickType::HidGamepad.to_byte(), 21);
+        assert_eq!(JoystickType::from_byte(21), JoystickType::HidGamepad);
+        assert_eq!(JoystickType::from_byte(0xFF), JoystickType::Unknown);
+        assert!(JoystickType::XInputGamepad.is_xbox());
+        assert!(!JoystickType::HidGamepad.is_xbox());
     }
 
     #[test]
@@ -0,0 +1,3 @@
+pub mod packet;
+pub mod script;
+pub mod sim;
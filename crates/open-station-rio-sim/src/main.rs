@@ -0,0 +1,149 @@
+use open_station_rio_sim::script::{self, SimEvent};
+use open_station_rio_sim::sim::{self, Observed, SimConfig};
+use std::net::IpAddr;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: open-station-rio-sim [OPTIONS]
+
+Options:
+  --bind ADDR        Address to listen on (default 127.0.0.1)
+  --udp-port PORT    UDP port for DS packets (default 1110)
+  --tcp-port PORT    TCP port for the DS stream (default 1740)
+  --ds-port PORT     UDP port replies are sent to (default 1150)
+  --voltage VOLTS    Nominal battery voltage (default 12.5)
+  --loss FRACTION    Fraction of replies to drop, 0.0 to 1.0
+  --script SCRIPT    Timed events, e.g. 5:brownout,8:recover,12:crash,15:restart
+  -h, --help         Show this help
+
+Script events: brownout, recover, crash, restart, voltage=V, loss=F,
+               droptcp, stdout=TEXT, warning=TEXT, error=TEXT";
+
+struct Args {
+    config: SimConfig,
+    loss: Option<f32>,
+    script: Vec<(std::time::Duration, SimEvent)>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        config: SimConfig::default(),
+        loss: None,
+        script: Vec::new(),
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "-h" || arg == "--help" {
+            return Err(String::new());
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {arg}"))?;
+        let invalid = || format!("invalid value for {arg}: {value}");
+
+        match arg.as_str() {
+            "--bind" => args.config.bind_addr = value.parse::<IpAddr>().map_err(|_| invalid())?,
+            "--udp-port" => args.config.udp_port = value.parse().map_err(|_| invalid())?,
+            "--tcp-port" => args.config.tcp_port = value.parse().map_err(|_| invalid())?,
+            "--ds-port" => args.config.ds_port = value.parse().map_err(|_| invalid())?,
+            "--voltage" => args.config.voltage = value.parse().map_err(|_| invalid())?,
+            "--loss" => {
+                let loss: f32 = value.parse().map_err(|_| invalid())?;
+                if !(0.0..=1.0).contains(&loss) {
+                    return Err(format!("--loss must be between 0 and 1, got {value}"));
+                }
+                args.loss = Some(loss);
+            }
+            "--script" => args.script = script::parse_script(&value).map_err(|e| e.to_string())?,
+            _ => return Err(format!("unknown option {arg}")),
+        }
+    }
+
+    Ok(args)
+}
+
+fn describe(observed: &Observed) -> String {
+    let alliance = observed
+        .alliance
+        .map(|a| format!("{:?} {}", a.color, a.station))
+        .unwrap_or_else(|| "-".to_string());
+    format!(
+        "{} {} | alliance {} | code {} | brownout {} | joysticks {} | tcp {}",
+        observed.control.mode,
+        if observed.control.estop {
+            "E-STOPPED"
+        } else if observed.control.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        },
+        alliance,
+        if observed.code_running {
+            "running"
+        } else {
+            "stopped"
+        },
+        observed.brownout,
+        observed
+            .joysticks
+            .iter()
+            .filter(|j| !j.axes.is_empty())
+            .count(),
+        if observed.tcp_connected { "up" } else { "down" },
+    )
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) if e.is_empty() => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let handle = match sim::start(args.config).await {
+        Ok(handle) => handle,
+        Err(e) => {
+            eprintln!("error: failed to start simulator: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(loss) = args.loss {
+        handle.send(SimEvent::PacketLoss(loss));
+    }
+    if !args.script.is_empty() {
+        handle.schedule(args.script);
+    }
+
+    let mut rx = handle.subscribe();
+    let mut last = String::new();
+    let mut last_game_data = None;
+    while rx.changed().await.is_ok() {
+        let observed = rx.borrow_and_update().clone();
+
+        let line = describe(&observed);
+        if line != last {
+            println!("{line}");
+            last = line;
+        }
+
+        if observed.game_data != last_game_data {
+            if let Some(data) = &observed.game_data {
+                println!("game data: {data:?}");
+            }
+            last_game_data = observed.game_data;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
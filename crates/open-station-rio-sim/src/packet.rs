@@ -0,0 +1,330 @@
+use open_station_protocol::packet::incoming::RioTag;
+use open_station_protocol::packet::tcp::encode_tcp_frame;
+use open_station_protocol::types::*;
+
+pub fn build_rio_packet(
+    sequence: u16,
+    status: StatusFlags,
+    trace: u8,
+    voltage: BatteryVoltage,
+    request_date: bool,
+    tags: &[RioTag],
+) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(64);
+
+    let (volts_hi, volts_lo) = voltage.to_bytes();
+    buf.extend_from_slice(&sequence.to_be_bytes());
+    buf.push(0x01);
+    buf.push(status.to_byte());
+    buf.push(trace);
+    buf.push(volts_hi);
+    buf.push(volts_lo);
+    buf.push(request_date as u8);
+
+    for tag in tags {
+        append_rio_tag(&mut buf, tag);
+    }
+
+    buf
+}
+
+pub fn append_rio_tag(buf: &mut Vec<u8>, tag: &RioTag) {
+    let (id, payload) = match tag {
+        RioTag::JoystickOutput {
+            outputs,
+            left_rumble,
+            right_rumble,
+        } => {
+            let mut payload = outputs.to_be_bytes().to_vec();
+            payload.extend_from_slice(&left_rumble.to_be_bytes());
+            payload.extend_from_slice(&right_rumble.to_be_bytes());
+            (0x01, payload)
+        }
+        RioTag::DiskUsage(free) => (0x04, free.to_be_bytes().to_vec()),
+        RioTag::CpuUsage(values) => {
+            let mut payload = vec![values.len() as u8];
+            for &value in values {
+                let hi = value.clamp(0.0, 255.0).floor();
+                payload.push(hi as u8);
+                payload.push(((value - hi) * 256.0) as u8);
+            }
+            (0x05, payload)
+        }
+        RioTag::RamUsage(ram) => (0x06, ram.to_be_bytes().to_vec()),
+        RioTag::PdpData(currents) => (0x08, encode_pdp_currents(currents)),
+        RioTag::CanMetrics(can) => {
+            let mut payload = vec![can.utilization.clamp(0.0, 255.0) as u8];
+            payload.extend_from_slice(&(can.bus_off_count.min(0xFFFF) as u16).to_be_bytes());
+            payload.extend_from_slice(&(can.tx_full_count.min(0xFFFF) as u16).to_be_bytes());
+            payload.push(can.rx_error_count);
+            payload.push(can.tx_error_count);
+            (0x0e, payload)
+        }
+        RioTag::Unknown(id, payload) => (*id, payload.clone()),
+    };
+
+    buf.push((1 + payload.len()) as u8);
+    buf.push(id);
+    buf.extend_from_slice(&payload);
+}
+
+// 16 channels of 10-bit currents (0.125 A per count), packed four to a
+// 5-byte group, followed by the trailing byte the real PDP sends.
+fn encode_pdp_currents(currents: &[f32]) -> Vec<u8> {
+    let raw = |i: usize| -> u16 {
+        let amps = currents.get(i).copied().unwrap_or(0.0);
+        ((amps / 0.125).round() as u16).min(0x3FF)
+    };
+
+    let mut payload = Vec::with_capacity(21);
+    for group in 0..4 {
+        let a = raw(group * 4);
+        let b = raw(group * 4 + 1);
+        let c = raw(group * 4 + 2);
+        let d = raw(group * 4 + 3);
+        payload.push((a >> 2) as u8);
+        payload.push((((a & 0x03) << 6) | (b >> 4)) as u8);
+        payload.push((((b & 0x0F) << 4) | (c >> 6)) as u8);
+        payload.push((((c & 0x3F) << 2) | (d >> 8)) as u8);
+        payload.push((d & 0xFF) as u8);
+    }
+    payload.push(0);
+    payload
+}
+
+pub fn build_stdout_frame(text: &str) -> Vec<u8> {
+    encode_tcp_frame(0x0c, text.as_bytes())
+}
+
+pub fn build_message_frame(text: &str) -> Vec<u8> {
+    encode_tcp_frame(0x00, text.as_bytes())
+}
+
+pub fn build_version_frame(device_type: u8, device_id: u8, name: &str, version: &str) -> Vec<u8> {
+    let mut payload = vec![device_type, device_id, name.len() as u8];
+    payload.extend_from_slice(name.as_bytes());
+    payload.push(version.len() as u8);
+    payload.extend_from_slice(version.as_bytes());
+    encode_tcp_frame(0x0a, &payload)
+}
+
+pub fn build_error_frame(
+    timestamp: f64,
+    sequence: u16,
+    error_code: i32,
+    is_error: bool,
+    details: &str,
+    location: &str,
+    call_stack: &str,
+) -> Vec<u8> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&timestamp.to_be_bytes());
+    payload.extend_from_slice(&sequence.to_be_bytes());
+    payload.extend_from_slice(&error_code.to_be_bytes());
+    payload.extend_from_slice(&(is_error as u16).to_be_bytes());
+    for text in [details, location, call_stack] {
+        payload.extend_from_slice(&(text.len() as u16).to_be_bytes());
+        payload.extend_from_slice(text.as_bytes());
+    }
+    encode_tcp_frame(0x0b, &payload)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DsTcpFrame {
+    JoystickDescriptor {
+        slot: u8,
+        descriptor: JoystickDescriptor,
+    },
+    MatchInfo {
+        name: String,
+        match_type: u8,
+    },
+    GameData(String),
+    Unknown(u8, Vec<u8>),
+}
+
+pub fn parse_ds_tcp_frame(tag: u8, payload: &[u8]) -> Option<DsTcpFrame> {
+    match tag {
+        0x02 => {
+            let (&slot, rest) = payload.split_first()?;
+            let (&_is_xbox, rest) = rest.split_first()?;
+            let (&joystick_type, rest) = rest.split_first()?;
+            let (&name_len, rest) = rest.split_first()?;
+            let name = String::from_utf8_lossy(rest.get(..name_len as usize)?).into_owned();
+            let rest = &rest[name_len as usize..];
+            let (&axis_count, rest) = rest.split_first()?;
+            let axis_types = rest.get(..axis_count as usize)?.to_vec();
+            let rest = &rest[axis_count as usize..];
+            let (&button_count, rest) = rest.split_first()?;
+            let (&pov_count, _) = rest.split_first()?;
+
+            Some(DsTcpFrame::JoystickDescriptor {
+                slot,
+                descriptor: JoystickDescriptor {
+                    name,
+                    joystick_type: JoystickType::from_byte(joystick_type),
+                    axis_types,
+                    button_count,
+                    pov_count,
+                },
+            })
+        }
+        0x07 => {
+            let (&name_len, rest) = payload.split_first()?;
+            let name = String::from_utf8_lossy(rest.get(..name_len as usize)?).into_owned();
+            let match_type = *rest.get(name_len as usize)?;
+            Some(DsTcpFrame::MatchInfo { name, match_type })
+        }
+        0x0e => Some(DsTcpFrame::GameData(
+            String::from_utf8_lossy(payload).into_owned(),
+        )),
+        _ => Some(DsTcpFrame::Unknown(tag, payload.to_vec())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use open_station_protocol::packet::incoming::parse_rio_packet;
+    use open_station_protocol::packet::tcp::{self, TcpFrameReader};
+
+    fn frame_parts(frame: &[u8]) -> (u8, Vec<u8>) {
+        let mut reader = TcpFrameReader::new();
+        reader.feed(frame);
+        reader.next_frame().unwrap()
+    }
+
+    #[test]
+    fn test_rio_packet_round_trip() {
+        let status = StatusFlags::from_byte(0b0000_1110);
+        let tags = vec![
+            RioTag::CanMetrics(CanMetrics {
+                utilization: 37.0,
+                bus_off_count: 2,
+                tx_full_count: 3,
+                rx_error_count: 4,
+                tx_error_count: 5,
+            }),
+            RioTag::CpuUsage(vec![12.5, 80.25]),
+            RioTag::RamUsage(1 << 20),
+            RioTag::DiskUsage(1 << 28),
+        ];
+        let data = build_rio_packet(
+            0xBEEF,
+            status,
+            0x30,
+            BatteryVoltage { volts: 12.5 },
+            true,
+            &tags,
+        );
+
+        let packet = parse_rio_packet(&data).unwrap();
+        assert_eq!(packet.sequence, 0xBEEF);
+        assert_eq!(packet.status, status);
+        assert_eq!(packet.trace, 0x30);
+        assert!((packet.voltage.volts - 12.5).abs() < 0.01);
+        assert!(packet.request_date);
+        assert_eq!(packet.tags.len(), 4);
+        match &packet.tags[1] {
+            RioTag::CpuUsage(values) => assert_eq!(values, &vec![12.5, 80.25]),
+            other => panic!("expected CpuUsage, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_pdp_round_trip() {
+        let currents: Vec<f32> = (0..16).map(|i| i as f32 * 1.5).collect();
+        let mut data = build_rio_packet(
+            1,
+            StatusFlags::from_byte(0),
+            0,
+            BatteryVoltage { volts: 12.0 },
+            false,
+            &[],
+        );
+        append_rio_tag(&mut data, &RioTag::PdpData(currents.clone()));
+
+        let packet = parse_rio_packet(&data).unwrap();
+        match &packet.tags[0] {
+            RioTag::PdpData(decoded) => assert_eq!(decoded, &currents),
+            other => panic!("expected PdpData, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_error_frame_round_trip() {
+        let frame = build_error_frame(1.5, 7, -44, true, "Boom", "Robot.java:12", "at main");
+        let (tag, payload) = frame_parts(&frame);
+        match tcp::parse_tcp_message(tag, &payload).unwrap() {
+            TcpMessage::ErrorReport {
+                timestamp,
+                sequence,
+                error_code,
+                is_error,
+                details,
+                location,
+                call_stack,
+            } => {
+                assert_eq!(timestamp, 1.5);
+                assert_eq!(sequence, 7);
+                assert_eq!(error_code, -44);
+                assert!(is_error);
+                assert_eq!(details, "Boom");
+                assert_eq!(location, "Robot.java:12");
+                assert_eq!(call_stack, "at main");
+            }
+            other => panic!("expected ErrorReport, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_version_frame_round_trip() {
+        let frame = build_version_frame(0, 0, "roboRIO Image", "2025_v2.0");
+        let (tag, payload) = frame_parts(&frame);
+        match tcp::parse_tcp_message(tag, &payload).unwrap() {
+            TcpMessage::VersionInfo { name, version, .. } => {
+                assert_eq!(name, "roboRIO Image");
+                assert_eq!(version, "2025_v2.0");
+            }
+            other => panic!("expected VersionInfo, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_descriptor_frame() {
+        let descriptor = JoystickDescriptor {
+            name: "Xbox Controller".to_string(),
+            joystick_type: JoystickType::XInputGamepad,
+            axis_types: vec![0, 1, 2, 2, 3, 4],
+            button_count: 10,
+            pov_count: 1,
+        };
+        let frame = tcp::build_joystick_descriptor_frame(3, &descriptor);
+        let (tag, payload) = frame_parts(&frame);
+        assert_eq!(
+            parse_ds_tcp_frame(tag, &payload),
+            Some(DsTcpFrame::JoystickDescriptor {
+                slot: 3,
+                descriptor
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_game_data_and_match_info() {
+        let (tag, payload) = frame_parts(&tcp::build_game_data_frame("LRL"));
+        assert_eq!(
+            parse_ds_tcp_frame(tag, &payload),
+            Some(DsTcpFrame::GameData("LRL".to_string()))
+        );
+
+        let (tag, payload) = frame_parts(&tcp::build_match_info_frame("Practice 3", 1));
+        assert_eq!(
+            parse_ds_tcp_frame(tag, &payload),
+            Some(DsTcpFrame::MatchInfo {
+                name: "Practice 3".to_string(),
+                match_type: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimEvent {
+    Brownout,
+    Recover,
+    SetVoltage(f32),
+    CrashCode,
+    RestartCode,
+    PacketLoss(f32),
+    // Closes the DS TCP stream, the DS reconnects on its own
+    DropTcp,
+    Stdout(String),
+    Warning(String),
+    Error(String),
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ScriptError {
+    #[error("expected SECS:EVENT, got {0:?}")]
+    MissingTime(String),
+    #[error("invalid time {0:?}")]
+    InvalidTime(String),
+    #[error("unknown event {0:?}")]
+    UnknownEvent(String),
+    #[error("invalid value for {event}: {value:?}")]
+    InvalidValue { event: String, value: String },
+}
+
+impl FromStr for SimEvent {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = match s.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value)),
+            None => (s.trim(), None),
+        };
+
+        let number = |value: Option<&str>| -> Result<f32, ScriptError> {
+            value
+                .and_then(|v| v.trim().parse::<f32>().ok())
+                .ok_or_else(|| ScriptError::InvalidValue {
+                    event: name.to_string(),
+                    value: value.unwrap_or_default().to_string(),
+                })
+        };
+        let text = |value: Option<&str>| value.unwrap_or_default().to_string();
+
+        match name {
+            "brownout" => Ok(SimEvent::Brownout),
+            "recover" => Ok(SimEvent::Recover),
+            "voltage" => Ok(SimEvent::SetVoltage(number(value)?)),
+            "crash" => Ok(SimEvent::CrashCode),
+            "restart" => Ok(SimEvent::RestartCode),
+            "loss" => {
+                let loss = number(value)?;
+                if !(0.0..=1.0).contains(&loss) {
+                    return Err(ScriptError::InvalidValue {
+                        event: name.to_string(),
+                        value: text(value),
+                    });
+                }
+                Ok(SimEvent::PacketLoss(loss))
+            }
+            "droptcp" => Ok(SimEvent::DropTcp),
+            "stdout" => Ok(SimEvent::Stdout(text(value))),
+            "warning" => Ok(SimEvent::Warning(text(value))),
+            "error" => Ok(SimEvent::Error(text(value))),
+            _ => Err(ScriptError::UnknownEvent(s.to_string())),
+        }
+    }
+}
+
+// A script is a comma separated list of `SECS:EVENT` entries, for example
+// `5:brownout,8:recover,12:crash,15:restart,20:loss=0.2`.
+pub fn parse_script(script: &str) -> Result<Vec<(Duration, SimEvent)>, ScriptError> {
+    let mut steps = Vec::new();
+
+    for entry in script.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (time, event) = entry
+            .split_once(':')
+            .ok_or_else(|| ScriptError::MissingTime(entry.to_string()))?;
+        let secs: f64 = time
+            .trim()
+            .parse()
+            .ok()
+            .filter(|s: &f64| s.is_finite() && *s >= 0.0)
+            .ok_or_else(|| ScriptError::InvalidTime(time.to_string()))?;
+        steps.push((Duration::from_secs_f64(secs), event.parse()?));
+    }
+
+    steps.sort_by_key(|(at, _)| *at);
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events() {
+        assert_eq!("brownout".parse(), Ok(SimEvent::Brownout));
+        assert_eq!("voltage=11.5".parse(), Ok(SimEvent::SetVoltage(11.5)));
+        assert_eq!("loss=0.25".parse(), Ok(SimEvent::PacketLoss(0.25)));
+        assert_eq!("droptcp".parse(), Ok(SimEvent::DropTcp));
+        assert_eq!(
+            "stdout=hello robot".parse(),
+            Ok(SimEvent::Stdout("hello robot".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_events() {
+        assert!("explode".parse::<SimEvent>().is_err());
+        assert!("loss=2".parse::<SimEvent>().is_err());
+        assert!("voltage=abc".parse::<SimEvent>().is_err());
+    }
+
+    #[test]
+    fn test_parse_script_sorted() {
+        let script = parse_script("8:recover, 5:brownout,12.5:crash").unwrap();
+        assert_eq!(
+            script,
+            vec![
+                (Duration::from_secs(5), SimEvent::Brownout),
+                (Duration::from_secs(8), SimEvent::Recover),
+                (Duration::from_millis(12500), SimEvent::CrashCode),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_script_errors() {
+        assert_eq!(
+            parse_script("brownout"),
+            Err(ScriptError::MissingTime("brownout".to_string()))
+        );
+        assert_eq!(
+            parse_script("-1:crash"),
+            Err(ScriptError::InvalidTime("-1".to_string()))
+        );
+    }
+}
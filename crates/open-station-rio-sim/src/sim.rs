@@ -0,0 +1,602 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::{mpsc, watch};
+use tokio::time::{timeout, Duration, Instant};
+
+use open_station_protocol::packet::incoming::RioTag;
+use open_station_protocol::packet::outgoing::{self, DsTag};
+use open_station_protocol::packet::tcp::TcpFrameReader;
+use open_station_protocol::types::*;
+
+use crate::packet::{self, DsTcpFrame};
+use crate::script::SimEvent;
+
+const TELEMETRY_INTERVAL: u32 = 25;
+const BROWNOUT_VOLTAGE: f32 = 6.8;
+const STARTUP_BANNER: &str = "********** Robot program startup complete **********";
+
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    pub bind_addr: IpAddr,
+    pub udp_port: u16,
+    pub tcp_port: u16,
+    pub ds_port: u16,
+    pub voltage: f32,
+    pub code_start_delay: Duration,
+    pub seed: u32,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            udp_port: 1110,
+            tcp_port: 1740,
+            ds_port: 1150,
+            voltage: 12.5,
+            code_start_delay: Duration::from_millis(500),
+            seed: 0x2F6E_9A41,
+        }
+    }
+}
+
+// Everything the simulated roboRIO has seen from the driver station, plus
+// its own externally visible state.
+#[derive(Debug, Clone, Default)]
+pub struct Observed {
+    pub ds_packets: u32,
+    pub replies_sent: u32,
+    pub replies_dropped: u32,
+    pub control: ControlFlags,
+    pub alliance: Option<Alliance>,
+    pub joysticks: Vec<JoystickData>,
+    pub descriptors: Vec<JoystickDescriptor>,
+    pub game_data: Option<String>,
+    pub match_info: Option<(String, u8)>,
+    // From the countdown tag of the latest packet
+    pub match_time: Option<f32>,
+    pub timezone: Option<String>,
+    pub date_received: bool,
+    pub reboot_requests: u32,
+    pub restart_requests: u32,
+    pub tcp_connected: bool,
+    pub tcp_connections: u32,
+    pub code_running: bool,
+    pub brownout: bool,
+}
+
+pub struct SimHandle {
+    events: mpsc::UnboundedSender<SimEvent>,
+    observed: watch::Receiver<Observed>,
+    udp_addr: SocketAddr,
+    tcp_addr: SocketAddr,
+}
+
+impl SimHandle {
+    pub fn send(&self, event: SimEvent) {
+        let _ = self.events.send(event);
+    }
+
+    pub fn schedule(&self, script: Vec<(Duration, SimEvent)>) {
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            let start = Instant::now();
+            for (at, event) in script {
+                tokio::time::sleep_until(start + at).await;
+                log::info!("Script: {:?}", event);
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    pub fn observed(&self) -> Observed {
+        self.observed.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Observed> {
+        self.observed.clone()
+    }
+
+    pub async fn wait_for(
+        &self,
+        limit: Duration,
+        predicate: impl FnMut(&Observed) -> bool,
+    ) -> Option<Observed> {
+        let mut rx = self.observed.clone();
+        let result = match timeout(limit, rx.wait_for(predicate)).await {
+            Ok(Ok(observed)) => Some(observed.clone()),
+            _ => None,
+        };
+        result
+    }
+
+    pub fn udp_addr(&self) -> SocketAddr {
+        self.udp_addr
+    }
+
+    pub fn tcp_addr(&self) -> SocketAddr {
+        self.tcp_addr
+    }
+}
+
+enum TcpEvent {
+    Connected,
+    Disconnected,
+    Frame(DsTcpFrame),
+}
+
+enum TcpCommand {
+    Send(Vec<u8>),
+    Drop,
+}
+
+pub async fn start(config: SimConfig) -> io::Result<SimHandle> {
+    let udp = UdpSocket::bind(SocketAddr::new(config.bind_addr, config.udp_port)).await?;
+    let listener = TcpListener::bind(SocketAddr::new(config.bind_addr, config.tcp_port)).await?;
+    let udp_addr = udp.local_addr()?;
+    let tcp_addr = listener.local_addr()?;
+    log::info!(
+        "Simulated roboRIO listening on UDP {} and TCP {}",
+        udp_addr,
+        tcp_addr
+    );
+
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+    let (observed_tx, observed_rx) = watch::channel(Observed::default());
+    let (tcp_out_tx, tcp_out_rx) = mpsc::unbounded_channel();
+    let (tcp_event_tx, tcp_event_rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(run_tcp(listener, tcp_out_rx, tcp_event_tx));
+
+    let engine = Engine::new(config, observed_tx, tcp_out_tx);
+    tokio::spawn(engine.run(udp, event_rx, tcp_event_rx));
+
+    Ok(SimHandle {
+        events: event_tx,
+        observed: observed_rx,
+        udp_addr,
+        tcp_addr,
+    })
+}
+
+struct Engine {
+    config: SimConfig,
+    observed: Observed,
+    observed_tx: watch::Sender<Observed>,
+    tcp_out: mpsc::UnboundedSender<TcpCommand>,
+    boot: Instant,
+    rng: u32,
+    voltage: f32,
+    packet_loss: f32,
+    code_start_at: Option<Instant>,
+    request_date: bool,
+    last_request: RequestFlags,
+    error_sequence: u16,
+}
+
+impl Engine {
+    fn new(
+        config: SimConfig,
+        observed_tx: watch::Sender<Observed>,
+        tcp_out: mpsc::UnboundedSender<TcpCommand>,
+    ) -> Self {
+        let now = Instant::now();
+        Self {
+            voltage: config.voltage,
+            rng: config.seed.max(1),
+            code_start_at: Some(now + config.code_start_delay),
+            config,
+            observed: Observed::default(),
+            observed_tx,
+            tcp_out,
+            boot: now,
+            packet_loss: 0.0,
+            request_date: true,
+            last_request: RequestFlags::default(),
+            error_sequence: 0,
+        }
+    }
+
+    async fn run(
+        mut self,
+        udp: UdpSocket,
+        mut event_rx: mpsc::UnboundedReceiver<SimEvent>,
+        mut tcp_event_rx: mpsc::UnboundedReceiver<TcpEvent>,
+    ) {
+        let mut buf = vec![0u8; 2048];
+        let mut events_open = true;
+
+        loop {
+            tokio::select! {
+                result = udp.recv_from(&mut buf) => {
+                    match result {
+                        Ok((len, from)) => {
+                            if let Some(reply) = self.handle_ds_packet(&buf[..len]) {
+                                let to = SocketAddr::new(from.ip(), self.config.ds_port);
+                                if let Err(e) = udp.send_to(&reply, to).await {
+                                    log::warn!("UDP send error: {}", e);
+                                }
+                            }
+                        }
+                        Err(e) => log::warn!("UDP receive error: {}", e),
+                    }
+                }
+
+                event = event_rx.recv(), if events_open => {
+                    match event {
+                        Some(event) => self.handle_event(event),
+                        // Keep simulating after the handle is dropped
+                        None => events_open = false,
+                    }
+                }
+
+                Some(event) = tcp_event_rx.recv() => {
+                    self.handle_tcp_event(event);
+                }
+            }
+
+            self.observed_tx.send_replace(self.observed.clone());
+        }
+    }
+
+    fn handle_ds_packet(&mut self, data: &[u8]) -> Option<Vec<u8>> {
+        let packet = match outgoing::parse_ds_packet(data) {
+            Ok(packet) => packet,
+            Err(e) => {
+                log::warn!("Failed to parse DS packet: {}", e);
+                return None;
+            }
+        };
+
+        self.update_code_state();
+
+        self.observed.ds_packets += 1;
+        self.observed.control = packet.control;
+        self.observed.alliance = Some(packet.alliance);
+        self.observed.joysticks = packet.joysticks();
+        self.observed.match_time = None;
+
+        for tag in &packet.tags {
+            match tag {
+                DsTag::DateTime { .. } => {
+                    if self.request_date {
+                        log::info!("Received date and time from DS");
+                    }
+                    self.observed.date_received = true;
+                    self.request_date = false;
+                }
+                DsTag::Timezone(tz) => self.observed.timezone = Some(tz.clone()),
+                DsTag::Countdown(secs) => self.observed.match_time = Some(*secs),
+                _ => {}
+            }
+        }
+
+        // Request bits are held for a few packets, act on the rising edge
+        if packet.request.reboot_roborio && !self.last_request.reboot_roborio {
+            self.observed.reboot_requests += 1;
+            self.reboot();
+        }
+        if packet.request.restart_code && !self.last_request.restart_code {
+            self.observed.restart_requests += 1;
+            self.restart_code();
+        }
+        self.last_request = packet.request;
+
+        if self.packet_loss > 0.0 && self.next_random() < self.packet_loss {
+            self.observed.replies_dropped += 1;
+            return None;
+        }
+
+        let reply = self.build_reply(packet.sequence, &packet.control);
+        self.observed.replies_sent += 1;
+        Some(reply)
+    }
+
+    fn build_reply(&self, sequence: u16, control: &ControlFlags) -> Vec<u8> {
+        let code_running = self.observed.code_running;
+        let enabled = control.enabled && code_running && !control.estop;
+
+        let status = StatusFlags {
+            estop: control.estop,
+            code_initializing: !code_running,
+            brownout: self.observed.brownout,
+            enabled,
+            mode: control.mode,
+        };
+
+        let voltage = if self.observed.brownout {
+            BROWNOUT_VOLTAGE
+        } else if enabled {
+            self.voltage - 0.4
+        } else {
+            self.voltage
+        };
+
+        let tags = if self
+            .observed
+            .replies_sent
+            .is_multiple_of(TELEMETRY_INTERVAL)
+        {
+            self.telemetry(enabled)
+        } else {
+            Vec::new()
+        };
+
+        packet::build_rio_packet(
+            sequence,
+            status,
+            trace_byte(code_running, enabled, control.mode),
+            BatteryVoltage { volts: voltage },
+            self.request_date,
+            &tags,
+        )
+    }
+
+    fn telemetry(&self, enabled: bool) -> Vec<RioTag> {
+        let load = if enabled { 2.0 } else { 0.0 };
+        vec![
+            RioTag::CpuUsage(vec![18.0 + load * 10.0, 12.0 + load * 5.0]),
+            RioTag::RamUsage(96 * 1024 * 1024),
+            RioTag::DiskUsage(180 * 1024 * 1024),
+            RioTag::CanMetrics(CanMetrics {
+                utilization: 20.0 + load * 15.0,
+                ..Default::default()
+            }),
+            RioTag::PdpData(
+                (0..16)
+                    .map(|i| if i < 4 { load * 4.0 } else { 0.0 })
+                    .collect(),
+            ),
+        ]
+    }
+
+    fn handle_event(&mut self, event: SimEvent) {
+        match event {
+            SimEvent::Brownout => self.observed.brownout = true,
+            SimEvent::Recover => self.observed.brownout = false,
+            SimEvent::SetVoltage(volts) => self.voltage = volts,
+            SimEvent::CrashCode => self.crash_code(),
+            SimEvent::RestartCode => self.restart_code(),
+            SimEvent::PacketLoss(loss) => self.packet_loss = loss.clamp(0.0, 1.0),
+            SimEvent::DropTcp => {
+                let _ = self.tcp_out.send(TcpCommand::Drop);
+            }
+            SimEvent::Stdout(text) => self.send_tcp(packet::build_stdout_frame(&text)),
+            SimEvent::Warning(text) => self.send_report(false, &text),
+            SimEvent::Error(text) => self.send_report(true, &text),
+        }
+    }
+
+    fn handle_tcp_event(&mut self, event: TcpEvent) {
+        match event {
+            TcpEvent::Connected => {
+                self.observed.tcp_connected = true;
+                self.observed.tcp_connections += 1;
+                if self.observed.code_running {
+                    self.send_tcp(packet::build_stdout_frame(STARTUP_BANNER));
+                }
+            }
+            // Anything learned over TCP has to be resent on the next stream
+            TcpEvent::Disconnected => {
+                self.observed.tcp_connected = false;
+                self.observed.descriptors.clear();
+                self.observed.game_data = None;
+                self.observed.match_info = None;
+            }
+            TcpEvent::Frame(DsTcpFrame::JoystickDescriptor { slot, descriptor }) => {
+                let slot = slot as usize;
+                if slot < 6 {
+                    if self.observed.descriptors.len() <= slot {
+                        self.observed
+                            .descriptors
+                            .resize(slot + 1, JoystickDescriptor::default());
+                    }
+                    self.observed.descriptors[slot] = descriptor;
+                }
+            }
+            TcpEvent::Frame(DsTcpFrame::MatchInfo { name, match_type }) => {
+                self.observed.match_info = Some((name, match_type));
+            }
+            TcpEvent::Frame(DsTcpFrame::GameData(data)) => {
+                log::info!("Game data: {:?}", data);
+                self.observed.game_data = Some(data);
+            }
+            TcpEvent::Frame(DsTcpFrame::Unknown(tag, _)) => {
+                log::debug!("Ignoring TCP frame 0x{:02x}", tag);
+            }
+        }
+    }
+
+    fn update_code_state(&mut self) {
+        if let Some(at) = self.code_start_at {
+            if Instant::now() >= at {
+                self.code_start_at = None;
+                self.observed.code_running = true;
+                log::info!("Robot code started");
+                self.send_tcp(packet::build_stdout_frame(STARTUP_BANNER));
+            }
+        }
+    }
+
+    fn crash_code(&mut self) {
+        if !self.observed.code_running {
+            return;
+        }
+        self.observed.code_running = false;
+        self.code_start_at = None;
+        log::info!("Robot code crashed");
+        self.send_tcp(packet::build_stdout_frame(
+            "Unhandled exception: java.lang.NullPointerException",
+        ));
+        self.send_report(true, "Unhandled exception in robot code");
+    }
+
+    fn restart_code(&mut self) {
+        log::info!("Restarting robot code");
+        self.observed.code_running = false;
+        self.code_start_at = Some(Instant::now() + self.config.code_start_delay);
+    }
+
+    fn reboot(&mut self) {
+        log::info!("Rebooting");
+        self.observed.code_running = false;
+        self.observed.date_received = false;
+        self.request_date = true;
+        self.code_start_at = Some(Instant::now() + self.config.code_start_delay * 2);
+    }
+
+    fn send_report(&mut self, is_error: bool, details: &str) {
+        self.error_sequence = self.error_sequence.wrapping_add(1);
+        let frame = packet::build_error_frame(
+            self.boot.elapsed().as_secs_f64(),
+            self.error_sequence,
+            if is_error { -1 } else { 1 },
+            is_error,
+            details,
+            "Robot.java:42",
+            "at frc.robot.Robot.robotPeriodic(Robot.java:42)",
+        );
+        self.send_tcp(frame);
+    }
+
+    fn send_tcp(&self, frame: Vec<u8>) {
+        let _ = self.tcp_out.send(TcpCommand::Send(frame));
+    }
+
+    // xorshift32, deterministic for a given seed
+    fn next_random(&mut self) -> f32 {
+        let mut x = self.rng;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng = x;
+        (x >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+fn trace_byte(code_running: bool, enabled: bool, mode: Mode) -> u8 {
+    let mut trace = 1 << 4; // is roboRIO
+    if code_running {
+        trace |= 1 << 5;
+    }
+    if !enabled {
+        trace |= 1;
+    }
+    trace |= match mode {
+        Mode::Test => 1 << 3,
+        Mode::Autonomous => 1 << 2,
+        Mode::Teleop => 1 << 1,
+    };
+    trace
+}
+
+async fn run_tcp(
+    listener: TcpListener,
+    mut out_rx: mpsc::UnboundedReceiver<TcpCommand>,
+    events: mpsc::UnboundedSender<TcpEvent>,
+) {
+    let mut stream: Option<TcpStream> = None;
+    let mut reader = TcpFrameReader::new();
+    let mut read_buf = vec![0u8; 4096];
+
+    loop {
+        tokio::select! {
+            result = listener.accept() => {
+                match result {
+                    Ok((mut new_stream, peer)) => {
+                        log::info!("DS connected over TCP from {}", peer);
+                        for frame in version_frames() {
+                            if let Err(e) = new_stream.write_all(&frame).await {
+                                log::warn!("TCP write error: {}", e);
+                            }
+                        }
+                        // A new DS connection replaces the old one
+                        stream = Some(new_stream);
+                        reader = TcpFrameReader::new();
+                        let _ = events.send(TcpEvent::Connected);
+                    }
+                    Err(e) => log::warn!("TCP accept error: {}", e),
+                }
+            }
+
+            result = async {
+                match stream.as_mut() {
+                    Some(s) => s.read(&mut read_buf).await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                match result {
+                    Ok(0) | Err(_) => {
+                        log::info!("DS TCP connection closed");
+                        stream = None;
+                        let _ = events.send(TcpEvent::Disconnected);
+                    }
+                    Ok(n) => {
+                        reader.feed(&read_buf[..n]);
+                        while let Some((tag, payload)) = reader.next_frame() {
+                            if let Some(frame) = packet::parse_ds_tcp_frame(tag, &payload) {
+                                let _ = events.send(TcpEvent::Frame(frame));
+                            }
+                        }
+                    }
+                }
+            }
+
+            Some(command) = out_rx.recv() => {
+                match command {
+                    TcpCommand::Send(frame) => {
+                        if let Some(s) = stream.as_mut() {
+                            if let Err(e) = s.write_all(&frame).await {
+                                log::warn!("TCP write error: {}", e);
+                                stream = None;
+                                let _ = events.send(TcpEvent::Disconnected);
+                            }
+                        }
+                    }
+                    TcpCommand::Drop => {
+                        if stream.take().is_some() {
+                            log::info!("Dropping DS TCP connection");
+                            let _ = events.send(TcpEvent::Disconnected);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn version_frames() -> Vec<Vec<u8>> {
+    vec![
+        packet::build_version_frame(0, 0, "roboRIO Image", "2025_v2.0"),
+        packet::build_version_frame(0, 0, "FRC_Lib_Version", "Open Station Sim"),
+        packet::build_version_frame(8, 0, "PDP", "1.40"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_byte() {
+        assert_eq!(trace_byte(true, false, Mode::Teleop), 0b0011_0011);
+        assert_eq!(trace_byte(false, true, Mode::Autonomous), 0b0001_0100);
+    }
+
+    #[test]
+    fn test_random_is_deterministic_and_bounded() {
+        let (observed_tx, _) = watch::channel(Observed::default());
+        let (tcp_tx, _) = mpsc::unbounded_channel();
+        let mut a = Engine::new(SimConfig::default(), observed_tx.clone(), tcp_tx.clone());
+        let mut b = Engine::new(SimConfig::default(), observed_tx, tcp_tx);
+        for _ in 0..100 {
+            let x = a.next_random();
+            assert_eq!(x, b.next_random());
+            assert!((0.0..1.0).contains(&x));
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use open_station_protocol::connection::{ConnectionState, Ports};
+use open_station_protocol::driver_station::{DriverStation, DsReceiver};
+use open_station_protocol::types::*;
+use open_station_rio_sim::script::SimEvent;
+use open_station_rio_sim::sim::{self, SimConfig, SimHandle};
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+use tokio::time::timeout;
+
+const WAIT: Duration = Duration::from_secs(5);
+
+// Every test gets its own block of ports so the pairs can run side by side
+async fn start_pair(base_port: u16) -> (SimHandle, DriverStation, DsReceiver) {
+    let ports = Ports {
+        robot_udp: base_port,
+        ds_udp: base_port + 1,
+        tcp: base_port + 2,
+    };
+    let sim = sim::start(SimConfig {
+        udp_port: ports.robot_udp,
+        ds_port: ports.ds_udp,
+        tcp_port: ports.tcp,
+        code_start_delay: Duration::from_millis(100),
+        ..Default::default()
+    })
+    .await
+    .expect("simulator should bind loopback ports");
+
+    let (mut ds, rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+    ds.set_target_override(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    ds.set_ports(ports);
+    ds.start();
+
+    (sim, ds, rx)
+}
+
+async fn wait_for_robot(
+    rx: &mut DsReceiver,
+    predicate: impl FnMut(&RobotState) -> bool,
+) -> RobotState {
+    timeout(WAIT, rx.state.wait_for(predicate))
+        .await
+        .expect("timed out waiting for robot state")
+        .expect("state channel closed")
+        .clone()
+}
+
+async fn next_message(
+    rx: &mut DsReceiver,
+    mut predicate: impl FnMut(&TcpMessage) -> bool,
+) -> TcpMessage {
+    timeout(WAIT, async {
+        loop {
+            let msg = rx.messages.recv().await.expect("message channel closed");
+            if predicate(&msg) {
+                return msg;
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for TCP message")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_connects_and_reports_robot_state() {
+    let (_sim, _ds, mut rx) = start_pair(41110).await;
+
+    let status = timeout(
+        WAIT,
+        rx.connection
+            .wait_for(|c| c.state == ConnectionState::CodeRunning && c.tcp),
+    )
+    .await
+    .expect("timed out waiting for connection lifecycle")
+    .map(|status| *status)
+    .unwrap();
+    assert!(status.udp);
+    assert_eq!(status.target.map(|t| t.port()), Some(41110));
+
+    let state = wait_for_robot(&mut rx, |s| {
+        s.connected && s.code_running && !s.telemetry.cpu_usage.is_empty()
+    })
+    .await;
+    assert!((state.voltage.volts - 12.5).abs() < 0.05);
+    assert!(!state.status.enabled);
+    assert_eq!(state.telemetry.pdp_currents.len(), 16);
+    assert!(state.telemetry.ram_usage > 0);
+
+    // Raw packets for loggers, from now on
+    let mut packets = rx.packets.resubscribe();
+    let (packet, stats) = timeout(WAIT, packets.recv())
+        .await
+        .expect("timed out waiting for a packet")
+        .unwrap();
+    assert!((packet.voltage.volts - 12.5).abs() < 0.05);
+    assert!(stats.received_count > 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_control_and_joysticks_reach_robot() {
+    let (sim, mut ds, mut rx) = start_pair(41120).await;
+    wait_for_robot(&mut rx, |s| s.code_running).await;
+
+    ds.set_alliance(Alliance::new(AllianceColor::Blue, 2));
+    ds.set_mode(Mode::Autonomous);
+    ds.set_joysticks(vec![
+        JoystickData {
+            axes: vec![12, -34],
+            buttons: vec![true, false, true],
+            povs: vec![90],
+        },
+        JoystickData::default(),
+    ]);
+    ds.enable();
+
+    let observed = sim
+        .wait_for(WAIT, |o| o.control.enabled && o.joysticks.len() == 2)
+        .await
+        .expect("simulator never saw enabled control packets");
+    assert_eq!(observed.control.mode, Mode::Autonomous);
+    assert_eq!(
+        observed.alliance,
+        Some(Alliance::new(AllianceColor::Blue, 2))
+    );
+    assert_eq!(observed.joysticks[0].axes, vec![12, -34]);
+    assert_eq!(observed.joysticks[0].buttons, vec![true, false, true]);
+    assert_eq!(observed.joysticks[0].povs, vec![90]);
+
+    let state = wait_for_robot(&mut rx, |s| s.status.enabled).await;
+    assert_eq!(state.status.mode, Mode::Autonomous);
+
+    ds.estop();
+    wait_for_robot(&mut rx, |s| s.status.estop && !s.status.enabled).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tcp_session() {
+    let (sim, mut ds, mut rx) = start_pair(41130).await;
+
+    let mut descriptors = vec![JoystickDescriptor::default(); 6];
+    descriptors[0] = JoystickDescriptor {
+        name: "Xbox Controller".to_string(),
+        joystick_type: JoystickType::XInputGamepad,
+        axis_types: vec![0, 1, 2, 2, 3, 4],
+        button_count: 10,
+        pov_count: 1,
+    };
+    ds.set_joystick_descriptors(descriptors.clone());
+
+    let version = next_message(&mut rx, |m| matches!(m, TcpMessage::VersionInfo { .. })).await;
+    match version {
+        TcpMessage::VersionInfo { name, .. } => assert_eq!(name, "roboRIO Image"),
+        _ => unreachable!(),
+    }
+
+    let observed = sim
+        .wait_for(WAIT, |o| o.descriptors.len() == 6)
+        .await
+        .expect("simulator never received joystick descriptors");
+    assert_eq!(observed.descriptors, descriptors);
+
+    ds.set_game_data("LRL".to_string());
+    sim.wait_for(WAIT, |o| o.game_data.as_deref() == Some("LRL"))
+        .await
+        .expect("simulator never received game data");
+
+    let banner = timeout(WAIT, rx.stdout.recv())
+        .await
+        .expect("timed out waiting for stdout")
+        .unwrap();
+    assert!(banner.contains("Robot program startup complete"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_scripted_brownout_and_crash() {
+    let (sim, mut ds, mut rx) = start_pair(41140).await;
+    wait_for_robot(&mut rx, |s| s.code_running).await;
+    sim.wait_for(WAIT, |o| o.tcp_connected).await.unwrap();
+
+    sim.schedule(vec![
+        (Duration::ZERO, SimEvent::Brownout),
+        (Duration::from_millis(300), SimEvent::Recover),
+    ]);
+    let state = wait_for_robot(&mut rx, |s| s.status.brownout).await;
+    assert!(state.voltage.volts < 7.0);
+    wait_for_robot(&mut rx, |s| !s.status.brownout && s.voltage.volts > 12.0).await;
+
+    sim.send(SimEvent::CrashCode);
+    wait_for_robot(&mut rx, |s| !s.code_running).await;
+    let report = next_message(&mut rx, |m| matches!(m, TcpMessage::ErrorReport { .. })).await;
+    match report {
+        TcpMessage::ErrorReport {
+            is_error, details, ..
+        } => {
+            assert!(is_error);
+            assert!(details.contains("Unhandled exception"));
+        }
+        _ => unreachable!(),
+    }
+
+    ds.restart_code();
+    sim.wait_for(WAIT, |o| o.restart_requests == 1)
+        .await
+        .unwrap();
+    wait_for_robot(&mut rx, |s| s.code_running).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_packet_loss_is_counted() {
+    let (sim, _ds, mut rx) = start_pair(41150).await;
+    wait_for_robot(&mut rx, |s| s.connected).await;
+
+    sim.send(SimEvent::PacketLoss(0.3));
+    let state = wait_for_robot(&mut rx, |s| s.lost_packets >= 5).await;
+    assert!(state.trip_time_ms > 0.0);
+    assert!(sim.observed().replies_dropped >= 5);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_date_request_answered() {
+    let (sim, _ds, _rx) = start_pair(41160).await;
+
+    let observed = sim
+        .wait_for(WAIT, |o| o.date_received && o.timezone.is_some())
+        .await
+        .expect("DS never answered the date request");
+    assert!(!observed.timezone.unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_match_time_reaches_robot() {
+    let (sim, mut ds, mut rx) = start_pair(41170).await;
+    wait_for_robot(&mut rx, |s| s.connected).await;
+
+    ds.set_match_time(Some(15.0));
+    sim.wait_for(WAIT, |o| o.match_time == Some(15.0))
+        .await
+        .expect("simulator never saw the countdown tag");
+
+    ds.set_match_time(None);
+    sim.wait_for(WAIT, |o| o.match_time.is_none())
+        .await
+        .expect("countdown tag kept being sent");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_match_info_sent_on_connect_and_change() {
+    let (sim, mut ds, _rx) = start_pair(41180).await;
+
+    // Set before the TCP stream exists, so only the connect can deliver it
+    ds.set_match_info(MatchInfo {
+        event_name: "Scrimmage".to_string(),
+        match_type: MatchType::Practice,
+        match_number: 1,
+        replay_number: 1,
+    });
+    sim.wait_for(WAIT, |o| {
+        o.match_info == Some(("Scrimmage Practice 1".to_string(), 1))
+    })
+    .await
+    .expect("simulator never received match info on connect");
+
+    ds.set_match_info(MatchInfo {
+        event_name: "Scrimmage".to_string(),
+        match_type: MatchType::Qualification,
+        match_number: 4,
+        replay_number: 1,
+    });
+    sim.wait_for(WAIT, |o| {
+        o.match_info == Some(("Scrimmage Qualification 4".to_string(), 2))
+    })
+    .await
+    .expect("simulator never received the updated match info");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_session_replayed_on_tcp_reconnect() {
+    let (sim, mut ds, _rx) = start_pair(41190).await;
+
+    // Queued before the first TCP stream is up
+    ds.set_game_data("LRL".to_string());
+    ds.set_joystick_descriptors(vec![JoystickDescriptor::default(); 6]);
+    ds.set_match_info(MatchInfo {
+        match_type: MatchType::Elimination,
+        match_number: 2,
+        ..Default::default()
+    });
+    let replayed = |o: &sim::Observed| {
+        o.tcp_connected
+            && o.game_data.as_deref() == Some("LRL")
+            && o.descriptors.len() == 6
+            && o.match_info == Some(("Elimination 2".to_string(), 3))
+    };
+    sim.wait_for(WAIT, |o| o.tcp_connections == 1 && replayed(o))
+        .await
+        .expect("session never reached the simulator");
+
+    // The simulator forgets the session when the stream drops
+    sim.send(SimEvent::DropTcp);
+    sim.wait_for(WAIT, |o| o.tcp_connections == 2 && replayed(o))
+        .await
+        .expect("session was not replayed after reconnecting");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_udp_stays_up_while_tcp_refused() {
+    let ports = Ports {
+        robot_udp: 41200,
+        ds_udp: 41201,
+        tcp: 41202,
+    };
+    // The simulator listens for TCP elsewhere, so every connect is refused
+    let _sim = sim::start(SimConfig {
+        udp_port: ports.robot_udp,
+        ds_port: ports.ds_udp,
+        tcp_port: 41203,
+        code_start_delay: Duration::from_millis(100),
+        ..Default::default()
+    })
+    .await
+    .expect("simulator should bind loopback ports");
+    let (mut ds, mut rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+    ds.set_target_override(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    ds.set_ports(ports);
+    ds.start();
+
+    timeout(WAIT, rx.connection.wait_for(|c| c.udp))
+        .await
+        .expect("timed out waiting for UDP")
+        .unwrap();
+
+    // Longer than a retry delay plus the receive timeout
+    let dropped = timeout(Duration::from_secs(4), rx.connection.wait_for(|c| !c.udp))
+        .await
+        .is_ok();
+    assert!(!dropped, "UDP dropped while TCP was refused");
+    assert!(!rx.connection.borrow().tcp);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_network_change_after_start_reconnects() {
+    let ports = Ports {
+        robot_udp: 41210,
+        ds_udp: 41211,
+        tcp: 41212,
+    };
+    let _sim = sim::start(SimConfig {
+        udp_port: ports.robot_udp,
+        ds_port: ports.ds_udp,
+        tcp_port: ports.tcp,
+        code_start_delay: Duration::from_millis(100),
+        ..Default::default()
+    })
+    .await
+    .expect("simulator should bind loopback ports");
+
+    // Starts out pointed at the USB address on ports nothing listens on
+    let (mut ds, mut rx) = DriverStation::new(1234, Alliance::new(AllianceColor::Red, 1));
+    ds.set_usb_mode(true);
+    ds.set_ports(Ports {
+        robot_udp: 41215,
+        ds_udp: 41216,
+        tcp: 41217,
+    });
+    ds.start();
+
+    timeout(WAIT, rx.connection.wait_for(|c| c.target.is_some()))
+        .await
+        .expect("timed out waiting for the first address")
+        .unwrap();
+    assert!(!rx.connection.borrow().udp);
+
+    ds.set_usb_mode(false);
+    ds.set_target_override(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    ds.set_ports(ports);
+
+    let status = timeout(
+        WAIT,
+        rx.connection
+            .wait_for(|c| c.state == ConnectionState::CodeRunning && c.tcp),
+    )
+    .await
+    .expect("timed out reconnecting with the new settings")
+    .map(|status| *status)
+    .unwrap();
+    assert_eq!(
+        status.target,
+        Some((Ipv4Addr::LOCALHOST, ports.robot_udp).into())
+    );
+}
@@ -1,4 +1,6 @@
-use open_station_core::config::PracticeTiming;
+use open_station_core::config::{MatchInfoConfig, MatchSchedule, NetworkConfig, PracticeTiming};
+use open_station_core::hotkeys::{HotkeyAction, HotkeyError};
+use open_station_core::practice::SkipTarget;
 use open_station_core::state::AppState;
 use open_station_protocol::types::*;
 use std::sync::Mutex;
@@ -65,12 +67,47 @@ pub fn set_game_data(state: State<'_, AppStateHandle>, data: String) {
     state.lock().unwrap().set_game_data(data);
 }
 
+#[allow(dead_code)]
+#[tauri::command]
+pub fn set_match_info(state: State<'_, AppStateHandle>, info: MatchInfoConfig) {
+    state.lock().unwrap().set_match_info(info);
+}
+
 #[allow(dead_code)]
 #[tauri::command]
 pub fn set_usb_connection(state: State<'_, AppStateHandle>, enabled: bool) {
     state.lock().unwrap().set_usb_mode(enabled);
 }
 
+// An empty address goes back to team/mDNS/USB discovery
+#[allow(dead_code)]
+#[tauri::command]
+pub fn set_network(
+    state: State<'_, AppStateHandle>,
+    target_address: String,
+    robot_udp_port: u16,
+    ds_udp_port: u16,
+    tcp_port: u16,
+) -> Result<(), String> {
+    let target_address = match target_address.trim() {
+        "" => None,
+        addr => Some(
+            addr.parse()
+                .map_err(|_| format!("invalid robot address {addr:?}"))?,
+        ),
+    };
+    if [robot_udp_port, ds_udp_port, tcp_port].contains(&0) {
+        return Err("ports must be 1 to 65535".to_string());
+    }
+    state.lock().unwrap().set_network(NetworkConfig {
+        target_address,
+        robot_udp_port,
+        ds_udp_port,
+        tcp_port,
+    });
+    Ok(())
+}
+
 #[allow(dead_code)]
 #[tauri::command]
 pub fn reboot_roborio(state: State<'_, AppStateHandle>) {
@@ -95,6 +132,33 @@ pub fn stop_practice_mode(state: State<'_, AppStateHandle>) {
     state.lock().unwrap().stop_practice();
 }
 
+#[allow(dead_code)]
+#[tauri::command]
+pub fn pause_practice_mode(state: State<'_, AppStateHandle>) {
+    state.lock().unwrap().pause_practice();
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub fn resume_practice_mode(state: State<'_, AppStateHandle>) {
+    state.lock().unwrap().resume_practice();
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub fn skip_practice_phase(state: State<'_, AppStateHandle>, target: String) -> Result<(), String> {
+    let skip_to = match target.as_str() {
+        "teleop" => SkipTarget::Teleop,
+        "endgame" => SkipTarget::Endgame,
+        _ => return Err(format!("unknown practice skip target {target:?}")),
+    };
+    if state.lock().unwrap().skip_practice(skip_to) {
+        Ok(())
+    } else {
+        Err(format!("no practice match period to skip to {target}"))
+    }
+}
+
 #[allow(dead_code)]
 #[tauri::command]
 pub fn set_practice_timing(
@@ -112,6 +176,18 @@ pub fn set_practice_timing(
     });
 }
 
+#[allow(dead_code)]
+#[tauri::command]
+pub fn set_practice_audio(state: State<'_, AppStateHandle>, enabled: bool) {
+    state.lock().unwrap().set_practice_audio(enabled);
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub fn set_match_schedule(state: State<'_, AppStateHandle>, schedule: Option<MatchSchedule>) {
+    state.lock().unwrap().set_match_schedule(schedule);
+}
+
 #[allow(dead_code)]
 #[tauri::command]
 pub fn reorder_joysticks(state: State<'_, AppStateHandle>, order: Vec<String>) {
@@ -136,6 +212,34 @@ pub fn rescan_joysticks(state: State<'_, AppStateHandle>) {
     state.lock().unwrap().rescan_joysticks();
 }
 
+#[allow(dead_code)]
+#[tauri::command]
+pub fn acknowledge_joystick_loss(state: State<'_, AppStateHandle>) {
+    state.lock().unwrap().acknowledge_joystick_loss();
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub fn rebind_hotkey(
+    state: State<'_, AppStateHandle>,
+    action: String,
+    binding: String,
+) -> Result<(), String> {
+    let action = HotkeyAction::from_name(&action)
+        .ok_or_else(|| HotkeyError::UnknownAction(action.clone()).to_string())?;
+    state
+        .lock()
+        .unwrap()
+        .rebind_hotkey(action, &binding)
+        .map_err(|e| e.to_string())
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub fn set_hotkey_test_mode(state: State<'_, AppStateHandle>, enabled: bool) {
+    state.lock().unwrap().set_hotkey_test_mode(enabled);
+}
+
 #[allow(dead_code)]
 #[tauri::command]
 pub fn launch_dashboard(state: State<'_, AppStateHandle>) {
@@ -152,6 +256,7 @@ pub fn get_config(state: State<'_, AppStateHandle>) -> serde_json::Value {
         "use_usb": config.use_usb,
         "dashboard_command": config.dashboard_command,
         "game_data": config.game_data,
+        "match_info": config.match_info,
         "practice_timing": {
             "countdown_secs": config.practice_timing.countdown_secs,
             "auto_secs": config.practice_timing.auto_secs,
@@ -159,6 +264,26 @@ pub fn get_config(state: State<'_, AppStateHandle>) -> serde_json::Value {
             "teleop_secs": config.practice_timing.teleop_secs,
         },
         "practice_audio": config.practice_audio,
+        "match_schedule": config.match_schedule().phases,
+        "custom_schedule": config.match_schedule.is_some(),
+        "network": {
+            "target_address": config.network.target_address.map(|a| a.to_string()),
+            "robot_udp_port": config.network.robot_udp_port,
+            "ds_udp_port": config.network.ds_udp_port,
+            "tcp_port": config.network.tcp_port,
+        },
+        "fms": {
+            "enabled": config.fms.enabled,
+            "ds_udp_port": config.fms.ds_udp_port,
+            "fms_udp_port": config.fms.fms_udp_port,
+        },
+        "hotkeys": {
+            "estop": config.hotkeys.estop,
+            "enable": config.hotkeys.enable,
+            "disable": config.hotkeys.disable,
+            "a_stop": config.hotkeys.a_stop,
+            "rescan": config.hotkeys.rescan,
+        },
     })
 }
 
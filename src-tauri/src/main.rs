@@ -54,16 +54,26 @@ fn main() {
             commands::set_team_number,
             commands::set_alliance,
             commands::set_game_data,
+            commands::set_match_info,
             commands::set_usb_connection,
+            commands::set_network,
             commands::reboot_roborio,
             commands::restart_robot_code,
             commands::start_practice_mode,
             commands::stop_practice_mode,
+            commands::pause_practice_mode,
+            commands::resume_practice_mode,
+            commands::skip_practice_phase,
             commands::set_practice_timing,
+            commands::set_practice_audio,
+            commands::set_match_schedule,
             commands::reorder_joysticks,
             commands::lock_joystick,
             commands::unlock_joystick,
             commands::rescan_joysticks,
+            commands::acknowledge_joystick_loss,
+            commands::rebind_hotkey,
+            commands::set_hotkey_test_mode,
             commands::launch_dashboard,
             commands::get_config,
             commands::save_config,
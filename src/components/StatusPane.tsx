@@ -37,6 +37,23 @@ function StatusIndicator({ label, active }: { label: string; active: boolean })
   );
 }
 
+function CommsIndicator({ udp, tcp, title }: { udp: boolean; tcp: boolean; title: string }) {
+  const half = (active: boolean) =>
+    active ? "bg-green-500 border-green-400" : "bg-red-500/30 border-red-400/50";
+
+  return (
+    <div className="flex flex-col items-center gap-1" title={title}>
+      <div className="flex w-4 h-4">
+        <div className={`w-2 h-4 rounded-l-full border-2 border-r-0 ${half(udp)}`} />
+        <div className={`w-2 h-4 rounded-r-full border-2 border-l-0 ${half(tcp)}`} />
+      </div>
+      <span className="text-[10px] text-gray-400 uppercase tracking-wider">
+        Comms
+      </span>
+    </div>
+  );
+}
+
 export default function StatusPane({ state }: Props) {
   const statusString = getStatusString(state);
   const vColor = voltageColor(state.voltage, state.brownout);
@@ -53,9 +70,14 @@ export default function StatusPane({ state }: Props) {
       </div>
 
       <div className="flex gap-6">
-        <StatusIndicator label="Comms" active={state.connected} />
+        <CommsIndicator
+          udp={state.udp_connected}
+          tcp={state.tcp_connected}
+          title={`UDP ${state.udp_connected ? "up" : "down"}, TCP ${state.tcp_connected ? "up" : "down"} (${state.connection_state})`}
+        />
         <StatusIndicator label="Code" active={state.code_running} />
         <StatusIndicator label="Joysticks" active={state.any_joystick_connected} />
+        {state.fms_connected && <StatusIndicator label="FMS" active />}
       </div>
 
       <div
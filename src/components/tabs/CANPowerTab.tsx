@@ -16,12 +16,13 @@ function FaultCounter({ label, value }: { label: string; value: number }) {
 }
 
 export default function CANPowerTab({ state }: Props) {
+  const t = state.telemetry;
   const can = state.connected ? {
-    utilization: 0,
-    bus_off_count: 0,
-    tx_full_count: 0,
-    rx_error_count: 0,
-    tx_error_count: 0,
+    utilization: t.can_utilization,
+    bus_off_count: t.can_bus_off_count,
+    tx_full_count: t.can_tx_full_count,
+    rx_error_count: t.can_rx_error_count,
+    tx_error_count: t.can_tx_error_count,
   } : null;
 
   return (
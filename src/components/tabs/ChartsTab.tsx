@@ -36,7 +36,10 @@ export default function ChartsTab({ state }: Props) {
           tripTime: state.trip_time_ms,
           lostPackets: state.lost_packets,
           voltage: state.voltage,
-          cpu: 0,
+          cpu: state.telemetry.cpu_usage.length
+            ? state.telemetry.cpu_usage.reduce((a, b) => a + b, 0) /
+              state.telemetry.cpu_usage.length
+            : 0,
           mode: state.mode,
           enabled: state.enabled,
         };
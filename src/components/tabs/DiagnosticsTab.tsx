@@ -25,12 +25,19 @@ export default function DiagnosticsTab({ state }: Props) {
       <div className="text-xs text-gray-500 uppercase tracking-wider mt-2">Connection</div>
       <div className="flex flex-col gap-1.5">
         <Indicator label="Robot" active={state.connected} />
+        <Indicator label="UDP" active={state.udp_connected} />
+        <Indicator label="TCP" active={state.tcp_connected} />
         <Indicator label="Robot Code" active={state.code_running} />
+        <Indicator label="FMS" active={state.fms_connected} />
       </div>
+      <div className="text-xs text-gray-400">State: {state.connection_state}</div>
 
       <div className="text-xs text-gray-500 uppercase tracking-wider mt-2">Memory</div>
       <div className="text-xs text-gray-400">
-        RAM: {state.connected ? `${(state.trip_time_ms).toFixed(0)} ms trip` : "N/A"}
+        RAM: {state.connected ? `${(state.telemetry.ram_usage / 1024 / 1024).toFixed(1)} MB` : "N/A"}
+      </div>
+      <div className="text-xs text-gray-400">
+        Disk: {state.connected ? `${(state.telemetry.disk_free / 1024 / 1024).toFixed(1)} MB free` : "N/A"}
       </div>
 
       <div className="flex flex-col gap-2 mt-3">
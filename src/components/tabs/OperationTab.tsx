@@ -10,6 +10,7 @@ export default function OperationTab({ state }: Props) {
   const cmd = useTauriCommand();
   const [selectedMode, setSelectedMode] = useState(state.mode);
   const [elapsedMs, setElapsedMs] = useState(0);
+  const [skipError, setSkipError] = useState<string | null>(null);
   const enabledRef = useRef(state.enabled);
   const timerRef = useRef<ReturnType<typeof setInterval>>(undefined);
 
@@ -42,6 +43,12 @@ export default function OperationTab({ state }: Props) {
   const modes = ["Teleoperated", "Autonomous", "Test"];
   const isPractice = state.practice_phase !== "Idle" && state.practice_phase !== "Done";
 
+  const skip = (target: "teleop" | "endgame") =>
+    cmd
+      .skipPracticePhase(target)
+      .then(() => setSkipError(null))
+      .catch((e) => setSkipError(String(e)));
+
   return (
     <div className="flex flex-col gap-3">
       <div className="text-xs text-gray-500 uppercase tracking-wider">Mode</div>
@@ -65,12 +72,49 @@ export default function OperationTab({ state }: Props) {
         >
           {isPractice ? `Practice (${state.practice_phase})` : "Practice"}
         </button>
+        {isPractice && (
+          <div className="grid grid-cols-3 gap-1">
+            <button
+              onClick={() => state.practice_paused ? cmd.resumePracticeMode() : cmd.pausePracticeMode()}
+              className="px-1 py-1 rounded text-[10px] bg-[#2a2a2a] text-gray-300 hover:bg-[#333]"
+            >
+              {state.practice_paused ? "Resume" : "Pause"}
+            </button>
+            <button
+              onClick={() => skip("teleop")}
+              className="px-1 py-1 rounded text-[10px] bg-[#2a2a2a] text-gray-300 hover:bg-[#333]"
+            >
+              Teleop
+            </button>
+            <button
+              onClick={() => skip("endgame")}
+              className="px-1 py-1 rounded text-[10px] bg-[#2a2a2a] text-gray-300 hover:bg-[#333]"
+            >
+              Endgame
+            </button>
+          </div>
+        )}
+        {isPractice && skipError && (
+          <div className="text-[10px] text-red-400">{skipError}</div>
+        )}
       </div>
 
+      {state.joystick_lockout && (
+        <div className="flex flex-col gap-1 mt-2 p-2 rounded border border-yellow-600/50 bg-yellow-900/20">
+          <div className="text-xs text-yellow-400">{state.joystick_lockout}</div>
+          <button
+            onClick={() => cmd.acknowledgeJoystickLoss()}
+            className="py-1 rounded text-xs bg-yellow-700 hover:bg-yellow-600 text-white transition-colors"
+          >
+            Acknowledge
+          </button>
+        </div>
+      )}
+
       <div className="flex gap-2 mt-2">
         <button
           onClick={() => cmd.enable()}
-          disabled={!state.connected || !state.code_running || state.estopped}
+          disabled={!state.connected || !state.code_running || state.estopped || !!state.joystick_lockout || state.fms_connected}
           className="flex-1 py-2 rounded font-bold text-xs bg-green-600 hover:bg-green-500 disabled:bg-green-900 disabled:text-green-700 disabled:cursor-not-allowed transition-colors"
         >
           Enable
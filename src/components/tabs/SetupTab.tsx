@@ -1,5 +1,5 @@
 import { useState, useEffect } from "react";
-import { RobotState } from "../../types";
+import { HotkeyBindings, MatchInfo, NetworkSettings, RobotState } from "../../types";
 import { useTauriCommand } from "../../hooks/useTauriCommand";
 
 interface Props {
@@ -11,11 +11,13 @@ export default function SetupTab({ state }: Props) {
   const [teamInput, setTeamInput] = useState(state.team_number.toString());
   const [gameData, setGameData] = useState("");
   const [useUsb, setUseUsb] = useState(false);
+  const [practiceAudio, setPracticeAudio] = useState(true);
 
   useEffect(() => {
     cmd.getConfig().then((config) => {
       setGameData(config.game_data);
       setUseUsb(config.use_usb);
+      setPracticeAudio(config.practice_audio);
     }).catch(() => {});
   }, []);
 
@@ -54,6 +56,9 @@ export default function SetupTab({ state }: Props) {
         placeholder="e.g. LRL"
       />
 
+      <div className="text-xs text-gray-500 uppercase tracking-wider mt-2">Match</div>
+      <MatchInfoInputs state={state} />
+
       <label className="flex items-center gap-2 mt-2">
         <input
           type="checkbox"
@@ -67,8 +72,235 @@ export default function SetupTab({ state }: Props) {
         <span className="text-xs text-gray-300">Connect via USB</span>
       </label>
 
+      <div className="text-xs text-gray-500 uppercase tracking-wider mt-2">Robot Address</div>
+      <NetworkInputs />
+
       <div className="text-xs text-gray-500 uppercase tracking-wider mt-3">Practice Timing (sec)</div>
       <PracticeTimingInputs />
+      <label className="flex items-center gap-2">
+        <input
+          type="checkbox"
+          checked={practiceAudio}
+          onChange={(e) => {
+            setPracticeAudio(e.target.checked);
+            cmd.setPracticeAudio(e.target.checked);
+            cmd.saveConfig();
+          }}
+          className="rounded"
+        />
+        <span className="text-xs text-gray-300">Match sounds</span>
+      </label>
+
+      <div className="text-xs text-gray-500 uppercase tracking-wider mt-3">Hotkeys</div>
+      <HotkeyInputs state={state} />
+    </div>
+  );
+}
+
+const HOTKEY_LABELS: { action: keyof HotkeyBindings; label: string }[] = [
+  { action: "estop", label: "E-Stop" },
+  { action: "enable", label: "Enable" },
+  { action: "disable", label: "Disable" },
+  { action: "a_stop", label: "A-Stop" },
+  { action: "rescan", label: "Rescan" },
+];
+
+function HotkeyInputs({ state }: Props) {
+  const cmd = useTauriCommand();
+  const [bindings, setBindings] = useState<HotkeyBindings | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const reload = () =>
+    cmd.getConfig().then((config) => setBindings(config.hotkeys)).catch(() => {});
+
+  useEffect(() => {
+    reload();
+  }, []);
+
+  const rebind = (action: keyof HotkeyBindings, binding: string) => {
+    cmd
+      .rebindHotkey(action, binding)
+      .then(() => {
+        setError(null);
+        cmd.saveConfig();
+        reload();
+      })
+      .catch((e) => {
+        setError(String(e));
+        reload();
+      });
+  };
+
+  if (!bindings) return null;
+
+  return (
+    <div className="flex flex-col gap-1.5">
+      {HOTKEY_LABELS.map(({ action, label }) => (
+        <div key={action} className="flex items-center gap-2">
+          <div className="text-[10px] text-gray-500 w-12">{label}</div>
+          <input
+            type="text"
+            value={bindings[action]}
+            onChange={(e) => setBindings({ ...bindings, [action]: e.target.value })}
+            onBlur={(e) => rebind(action, e.target.value)}
+            onKeyDown={(e) => e.key === "Enter" && rebind(action, e.currentTarget.value)}
+            className="bg-[#2a2a2a] border border-gray-600 rounded px-1.5 py-0.5 text-xs font-mono flex-1"
+          />
+        </div>
+      ))}
+      {error && <div className="text-[10px] text-red-400">{error}</div>}
+
+      <label className="flex items-center gap-2 mt-1">
+        <input
+          type="checkbox"
+          checked={state.hotkey_test_mode}
+          onChange={(e) => cmd.setHotkeyTestMode(e.target.checked)}
+          className="rounded"
+        />
+        <span className="text-xs text-gray-300">Test hotkeys (disables robot)</span>
+      </label>
+      {state.hotkey_test_mode && (
+        <div className="text-xs font-mono text-gray-300">
+          {state.hotkey_test
+            ? `${state.hotkey_test.keys} → ${state.hotkey_test.action ?? "nothing"}`
+            : "Press a key…"}
+        </div>
+      )}
+    </div>
+  );
+}
+
+const MATCH_TYPES: { value: MatchInfo["match_type"]; label: string }[] = [
+  { value: "none", label: "None" },
+  { value: "practice", label: "Practice" },
+  { value: "qualification", label: "Qualification" },
+  { value: "elimination", label: "Elimination" },
+];
+
+function MatchInfoInputs({ state }: Props) {
+  const cmd = useTauriCommand();
+  const [info, setInfo] = useState<MatchInfo | null>(null);
+
+  useEffect(() => {
+    cmd.getConfig().then((config) => setInfo(config.match_info)).catch(() => {});
+  }, []);
+
+  const save = (next: MatchInfo) => {
+    setInfo(next);
+    cmd.setMatchInfo(next);
+    cmd.saveConfig();
+  };
+
+  if (!info) return null;
+
+  return (
+    <div className="flex flex-col gap-1.5">
+      <input
+        type="text"
+        value={info.event_name}
+        onChange={(e) => setInfo({ ...info, event_name: e.target.value })}
+        onBlur={() => save(info)}
+        className="bg-[#2a2a2a] border border-gray-600 rounded px-2 py-1 text-sm w-full"
+        placeholder="Event name"
+      />
+      <div className="grid grid-cols-3 gap-2">
+        <select
+          value={info.match_type}
+          onChange={(e) =>
+            save({ ...info, match_type: e.target.value as MatchInfo["match_type"] })
+          }
+          className="bg-[#2a2a2a] border border-gray-600 rounded px-1 py-0.5 text-xs"
+        >
+          {MATCH_TYPES.map(({ value, label }) => (
+            <option key={value} value={value}>
+              {label}
+            </option>
+          ))}
+        </select>
+        {[
+          { label: "Match", key: "match_number" as const, max: 65535 },
+          { label: "Replay", key: "replay_number" as const, max: 255 },
+        ].map(({ label, key, max }) => (
+          <input
+            key={key}
+            type="number"
+            min="0"
+            max={max}
+            title={label}
+            value={info[key]}
+            onChange={(e) =>
+              setInfo({ ...info, [key]: Math.min(max, parseInt(e.target.value) || 0) })
+            }
+            onBlur={() => save(info)}
+            className="bg-[#2a2a2a] border border-gray-600 rounded px-1.5 py-0.5 text-xs font-mono w-full"
+          />
+        ))}
+      </div>
+      {state.match_name && (
+        <div className="text-[10px] text-gray-500">Robot sees: {state.match_name}</div>
+      )}
+    </div>
+  );
+}
+
+const PORT_FIELDS: { key: "robot_udp_port" | "ds_udp_port" | "tcp_port"; label: string }[] = [
+  { key: "robot_udp_port", label: "Robot UDP" },
+  { key: "ds_udp_port", label: "DS UDP" },
+  { key: "tcp_port", label: "TCP" },
+];
+
+function NetworkInputs() {
+  const cmd = useTauriCommand();
+  const [network, setNetwork] = useState<NetworkSettings | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    cmd.getConfig().then((config) => setNetwork(config.network)).catch(() => {});
+  }, []);
+
+  const save = (next: NetworkSettings) => {
+    cmd
+      .setNetwork(next)
+      .then(() => {
+        setError(null);
+        cmd.saveConfig();
+      })
+      .catch((e) => setError(String(e)));
+  };
+
+  if (!network) return null;
+
+  return (
+    <div className="flex flex-col gap-1.5">
+      <input
+        type="text"
+        value={network.target_address ?? ""}
+        onChange={(e) => setNetwork({ ...network, target_address: e.target.value || null })}
+        onBlur={() => save(network)}
+        onKeyDown={(e) => e.key === "Enter" && save(network)}
+        className="bg-[#2a2a2a] border border-gray-600 rounded px-2 py-1 text-sm font-mono w-full"
+        placeholder="From team number, e.g. 127.0.0.1 for sim"
+      />
+      <div className="grid grid-cols-3 gap-2">
+        {PORT_FIELDS.map(({ key, label }) => (
+          <div key={key}>
+            <div className="text-[10px] text-gray-500">{label}</div>
+            <input
+              type="number"
+              min="1"
+              max="65535"
+              value={network[key]}
+              onChange={(e) =>
+                setNetwork({ ...network, [key]: Math.min(65535, parseInt(e.target.value) || 0) })
+              }
+              onBlur={() => save(network)}
+              className="bg-[#2a2a2a] border border-gray-600 rounded px-1.5 py-0.5 text-xs font-mono w-full"
+            />
+          </div>
+        ))}
+      </div>
+      {error && <div className="text-[10px] text-red-400">{error}</div>}
+      <div className="text-[10px] text-gray-500">Saving reconnects to the robot</div>
     </div>
   );
 }
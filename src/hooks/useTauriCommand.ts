@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import { ConfigData } from "../types";
+import { ConfigData, MatchInfo, NetworkSettings, SchedulePhase } from "../types";
 
 export function useTauriCommand() {
   return {
@@ -11,19 +11,39 @@ export function useTauriCommand() {
     setAlliance: (color: string, station: number) =>
       invoke("set_alliance", { color, station }),
     setGameData: (data: string) => invoke("set_game_data", { data }),
+    setMatchInfo: (info: MatchInfo) => invoke("set_match_info", { info }),
     setUsbConnection: (enabled: boolean) =>
       invoke("set_usb_connection", { enabled }),
+    setNetwork: (network: NetworkSettings) =>
+      invoke("set_network", {
+        targetAddress: network.target_address ?? "",
+        robotUdpPort: network.robot_udp_port,
+        dsUdpPort: network.ds_udp_port,
+        tcpPort: network.tcp_port,
+      }),
     rebootRoborio: () => invoke("reboot_roborio"),
     restartRobotCode: () => invoke("restart_robot_code"),
     startPracticeMode: () => invoke("start_practice_mode"),
     stopPracticeMode: () => invoke("stop_practice_mode"),
+    pausePracticeMode: () => invoke("pause_practice_mode"),
+    resumePracticeMode: () => invoke("resume_practice_mode"),
+    skipPracticePhase: (target: "teleop" | "endgame") =>
+      invoke("skip_practice_phase", { target }),
     setPracticeTiming: (countdown: number, auto: number, delay: number, teleop: number) =>
       invoke("set_practice_timing", { countdown, auto_secs: auto, delay, teleop }),
+    setPracticeAudio: (enabled: boolean) => invoke("set_practice_audio", { enabled }),
+    setMatchSchedule: (phases: SchedulePhase[] | null) =>
+      invoke("set_match_schedule", { schedule: phases && { phases } }),
     reorderJoysticks: (order: string[]) => invoke("reorder_joysticks", { order }),
     lockJoystick: (uuid: string, slot: number) =>
       invoke("lock_joystick", { uuid, slot }),
     unlockJoystick: (uuid: string) => invoke("unlock_joystick", { uuid }),
     rescanJoysticks: () => invoke("rescan_joysticks"),
+    acknowledgeJoystickLoss: () => invoke("acknowledge_joystick_loss"),
+    rebindHotkey: (action: string, binding: string) =>
+      invoke("rebind_hotkey", { action, binding }),
+    setHotkeyTestMode: (enabled: boolean) =>
+      invoke("set_hotkey_test_mode", { enabled }),
     launchDashboard: () => invoke("launch_dashboard"),
     getConfig: () => invoke<ConfigData>("get_config"),
     saveConfig: () => invoke("save_config"),
@@ -8,16 +8,43 @@ export interface RobotState {
   mode: string;
   joysticks: JoystickInfo[];
   any_joystick_connected: boolean;
+  joystick_lockout: string | null;
+  hotkey_test_mode: boolean;
+  hotkey_test: HotkeyTest | null;
   practice_phase: string;
   practice_elapsed_secs: number;
   practice_remaining_secs: number;
+  practice_paused: boolean;
+  match_name: string;
+  connection_state: string;
+  udp_connected: boolean;
+  tcp_connected: boolean;
   trip_time_ms: number;
   lost_packets: number;
+  fms_connected: boolean;
+  telemetry: Telemetry;
   team_number: number;
   alliance_color: string;
   alliance_station: number;
 }
 
+export interface HotkeyTest {
+  keys: string;
+  action: string | null;
+}
+
+export interface Telemetry {
+  can_utilization: number;
+  can_bus_off_count: number;
+  can_tx_full_count: number;
+  can_rx_error_count: number;
+  can_tx_error_count: number;
+  pdp_currents: number[];
+  cpu_usage: number[];
+  ram_usage: number;
+  disk_free: number;
+}
+
 export interface JoystickInfo {
   slot: number;
   uuid: string;
@@ -46,6 +73,7 @@ export interface ConfigData {
   use_usb: boolean;
   dashboard_command: string | null;
   game_data: string;
+  match_info: MatchInfo;
   practice_timing: {
     countdown_secs: number;
     auto_secs: number;
@@ -53,6 +81,39 @@ export interface ConfigData {
     teleop_secs: number;
   };
   practice_audio: boolean;
+  match_schedule: SchedulePhase[];
+  custom_schedule: boolean;
+  network: NetworkSettings;
+  hotkeys: HotkeyBindings;
+}
+
+export interface NetworkSettings {
+  target_address: string | null;
+  robot_udp_port: number;
+  ds_udp_port: number;
+  tcp_port: number;
+}
+
+export interface MatchInfo {
+  event_name: string;
+  match_type: "none" | "practice" | "qualification" | "elimination";
+  match_number: number;
+  replay_number: number;
+}
+
+export interface SchedulePhase {
+  label: string;
+  mode: "autonomous" | "teleop" | "test";
+  duration_secs: number;
+  enabled: boolean;
+}
+
+export interface HotkeyBindings {
+  estop: string;
+  enable: string;
+  disable: string;
+  a_stop: string;
+  rescan: string;
 }
 
 export const INITIAL_STATE: RobotState = {
@@ -65,11 +126,31 @@ export const INITIAL_STATE: RobotState = {
   mode: "Teleoperated",
   joysticks: [],
   any_joystick_connected: false,
+  joystick_lockout: null,
+  hotkey_test_mode: false,
+  hotkey_test: null,
   practice_phase: "Idle",
   practice_elapsed_secs: 0,
   practice_remaining_secs: 0,
+  practice_paused: false,
+  match_name: "",
+  connection_state: "Disconnected",
+  udp_connected: false,
+  tcp_connected: false,
   trip_time_ms: 0,
   lost_packets: 0,
+  fms_connected: false,
+  telemetry: {
+    can_utilization: 0,
+    can_bus_off_count: 0,
+    can_tx_full_count: 0,
+    can_rx_error_count: 0,
+    can_tx_error_count: 0,
+    pdp_currents: [],
+    cpu_usage: [],
+    ram_usage: 0,
+    disk_free: 0,
+  },
   team_number: 0,
   alliance_color: "Red",
   alliance_station: 1,